@@ -0,0 +1,392 @@
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::cairo_serde;
+use crate::database::{AbiOverrideRecord, Database};
+use crate::rpc_types::BlockId;
+use crate::starknet::{get_class_abi, get_class_hash_at, get_contract_abi_string, get_storage_at, sn_keccak, RpcContext};
+
+/// What a proxy's implementation storage variable holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Implementation {
+    ClassHash,
+    Address,
+}
+
+/// Storage variables common proxies keep their implementation in. OpenZeppelin and Argent proxies
+/// hold a class hash they library-call; older proxies the address of an implementation contract.
+const PROXY_SLOTS: &[(&str, Implementation)] = &[
+    ("Proxy_implementation_hash", Implementation::ClassHash),
+    ("_implementation", Implementation::ClassHash),
+    ("implementation_hash", Implementation::ClassHash),
+    ("Proxy_implementation_address", Implementation::Address),
+    ("implementation_address", Implementation::Address),
+];
+
+/// ABIs a contract's events and calls are decoded with. Overrides limited to a block range apply to
+/// those blocks only; the most recent override wins where several cover a block. Without an override
+/// a proxy decodes with the implementation it had at the block.
+#[derive(Debug, Clone, Default)]
+pub struct ContractAbis {
+    default: Value,
+    ranges: Vec<(Option<u64>, Option<u64>, Value)>,
+    upgrades: Vec<(u64, Value)>, // ABI from a block on, in block order
+}
+
+impl ContractAbis {
+    /// ABI for an event or call of `block_number`; pending ones pass `u64::MAX`
+    pub fn for_block(&self, block_number: u64) -> &Value {
+        self.ranges.iter()
+            .rev()
+            .find(|(from, to, _)| from.is_none_or(|from| block_number >= from) && to.is_none_or(|to| block_number <= to))
+            .map(|(_, _, abi)| abi)
+            .or_else(|| self.upgrades.iter().rev().find(|(from, _)| block_number >= *from).map(|(_, abi)| abi))
+            .unwrap_or(&self.default)
+    }
+}
+
+/// What an indexer's `ContractAbis` are built from, kept between passes: class ABIs are fetched once per
+/// class hash, and a proxy's implementation is re-read only for blocks not resolved yet, from the storage
+/// variable it was last found in
+pub struct AbiRegistry {
+    contract_address: String,
+    deployment_id: Option<String>,
+    cli_overrides: Vec<AbiOverrideRecord>,
+    resolve_proxies: bool,
+    classes: HashMap<String, Value>,
+    own: Option<Value>,
+    overrides: Option<Vec<AbiOverrideRecord>>, // Those `abis` was built with
+    overridden: bool, // One of them covers every block
+    proxy_slot: Option<usize>,
+    implementations: Vec<(u64, Option<String>)>, // Class hash from a block on, in block order
+    resolved_through: Option<u64>,
+    abis: ContractAbis,
+}
+
+impl AbiRegistry {
+    /// `cli_overrides` may name other contracts; only this contract's are kept
+    pub fn new(contract_address: &str, deployment_id: Option<String>, cli_overrides: &[AbiOverrideRecord], resolve_proxies: bool) -> Self {
+        let normalized = Database::normalize_address(contract_address);
+        Self {
+            contract_address: contract_address.to_string(),
+            deployment_id,
+            cli_overrides: cli_overrides.iter().filter(|o| Database::normalize_address(&o.contract_address) == normalized).cloned().collect(),
+            resolve_proxies,
+            classes: HashMap::new(),
+            own: None,
+            overrides: None,
+            overridden: false,
+            proxy_slot: None,
+            implementations: Vec::new(),
+            resolved_through: None,
+            abis: ContractAbis::default(),
+        }
+    }
+
+    /// ABIs for blocks `from_block` to `to_block`: the overrides stored for the deployment (or for any
+    /// deployment), then those given on the command line, over the contract's own class ABI, extended
+    /// with its implementation's when the contract is a proxy and `resolve_proxies` is set
+    pub async fn abis(&mut self, rpc: &RpcContext, database: &Database, from_block: u64, to_block: u64) -> ContractAbis {
+        let mut overrides = database.get_abi_overrides(self.deployment_id.as_deref(), Some(&self.contract_address)).await
+            .unwrap_or_else(|e| {
+                eprintln!("⚠️  Failed to load ABI overrides of {}: {}", self.contract_address, e);
+                self.overrides.clone().unwrap_or_default()
+            });
+        overrides.extend(self.cli_overrides.iter().cloned());
+
+        let own_missing = self.own.is_none();
+        if own_missing {
+            match get_contract_abi_string(rpc, &self.contract_address).await {
+                Ok(abi) => self.own = Some(serde_json::from_str(&abi).unwrap_or(Value::Array(vec![]))),
+                Err(e) => eprintln!("⚠️  Failed to fetch the ABI of {}: {}", self.contract_address, e),
+            }
+        }
+        let own = self.own.clone().unwrap_or(Value::Array(vec![]));
+
+        let mut changed = self.overrides.as_ref() != Some(&overrides) || (own_missing && self.own.is_some());
+        if changed {
+            let mut default = None;
+            let mut ranges = Vec::new();
+            for record in &overrides {
+                let abi = match (&record.abi, &record.class_hash) {
+                    (None, Some(class_hash)) => self.class(rpc, class_hash).await,
+                    _ => override_abi(rpc, record).await,
+                };
+                match abi {
+                    Ok(abi) if record.from_block.is_none() && record.to_block.is_none() => default = Some(abi),
+                    Ok(abi) => ranges.push((record.from_block, record.to_block, abi)),
+                    Err(e) => eprintln!("⚠️  Ignoring ABI override {} of {}: {}", record.id, self.contract_address, e),
+                }
+            }
+            self.overridden = default.is_some();
+            self.abis.default = default.unwrap_or_else(|| own.clone());
+            self.abis.ranges = ranges;
+            self.overrides = Some(overrides);
+        }
+
+        // An override of every block leaves nothing to decode with the implementation
+        let overridden = self.overridden;
+        if self.resolve_proxies && !overridden {
+            let known = self.implementations.len();
+            if let Err(e) = self.resolve_implementations(rpc, from_block, to_block).await {
+                eprintln!("⚠️  Failed to read the implementation of {}: {}", self.contract_address, e);
+            }
+            changed |= self.implementations.len() != known;
+        }
+
+        if overridden {
+            self.abis.upgrades.clear();
+        } else if changed {
+            let mut upgrades = Vec::new();
+            for (from, class_hash) in self.implementations.clone() {
+                let abi = match &class_hash {
+                    Some(class_hash) => match self.class(rpc, class_hash).await {
+                        Ok(Value::Array(implementation)) => {
+                            println!("🔀 Contract {} is a proxy - decoding from block {} with implementation class {}", self.contract_address, from, class_hash);
+                            let mut merged = own.as_array().cloned().unwrap_or_default();
+                            merged.extend(implementation);
+                            Value::Array(merged)
+                        }
+                        Ok(_) => own.clone(),
+                        Err(e) => {
+                            eprintln!("⚠️  Failed to fetch implementation class {} of {}: {}", class_hash, self.contract_address, e);
+                            own.clone()
+                        }
+                    },
+                    None => own.clone(),
+                };
+                upgrades.push((from, abi));
+            }
+            self.abis.upgrades = upgrades;
+        }
+        self.abis.clone()
+    }
+
+    /// ABI of a class, fetched once
+    async fn class(&mut self, rpc: &RpcContext, class_hash: &str) -> Result<Value, String> {
+        if let Some(abi) = self.classes.get(class_hash) {
+            return Ok(abi.clone());
+        }
+        let abi = get_class_abi(rpc, class_hash).await
+            .map_err(|e| format!("Failed to fetch class {}: {}", class_hash, e))?;
+        self.classes.insert(class_hash.to_string(), abi.clone());
+        Ok(abi)
+    }
+
+    /// Extend the implementation history through `to_block`. An implementation that differs from the last
+    /// one known is searched back to the first block it was set at, so events of the blocks in between
+    /// keep the ABI they were emitted with. Assumes at most one upgrade between two resolved blocks.
+    async fn resolve_implementations(&mut self, rpc: &RpcContext, from_block: u64, to_block: u64) -> Result<(), String> {
+        let (known_block, known) = match (self.resolved_through, self.implementations.last()) {
+            (Some(through), Some((_, class_hash))) => (through, class_hash.clone()),
+            _ => {
+                let first = self.implementation_at(rpc, from_block).await?;
+                self.implementations.push((from_block, first.clone()));
+                self.resolved_through = Some(from_block);
+                (from_block, first)
+            }
+        };
+        if known_block >= to_block {
+            return Ok(());
+        }
+
+        let latest = self.implementation_at(rpc, to_block).await?;
+        if latest != known {
+            let (mut before, mut upgraded_at) = (known_block, to_block);
+            while upgraded_at - before > 1 {
+                let middle = before + (upgraded_at - before) / 2;
+                if self.implementation_at(rpc, middle).await? == latest {
+                    upgraded_at = middle;
+                } else {
+                    before = middle;
+                }
+            }
+            self.implementations.push((upgraded_at, latest));
+        }
+        self.resolved_through = Some(to_block);
+        Ok(())
+    }
+
+    /// Implementation at a block, trying the storage variable it was last found in first
+    async fn implementation_at(&mut self, rpc: &RpcContext, block_number: u64) -> Result<Option<String>, String> {
+        let block = BlockId::Number(block_number);
+        if let Some(slot) = self.proxy_slot {
+            if let Some((_, class_hash)) = read_implementation(rpc, &self.contract_address, &block, slot..slot + 1).await? {
+                return Ok(Some(class_hash));
+            }
+        }
+        let found = read_implementation(rpc, &self.contract_address, &block, 0..PROXY_SLOTS.len()).await?;
+        if let Some((slot, _)) = &found {
+            self.proxy_slot = Some(*slot);
+        }
+        Ok(found.map(|(_, class_hash)| class_hash))
+    }
+}
+
+/// Class hash behind a proxy at a block, from the first implementation storage variable that is set
+pub async fn proxy_implementation(rpc: &RpcContext, contract_address: &str, block: BlockId) -> Result<Option<String>, String> {
+    Ok(read_implementation(rpc, contract_address, &block, 0..PROXY_SLOTS.len()).await?.map(|(_, class_hash)| class_hash))
+}
+
+/// First of the `slots` of `PROXY_SLOTS` that is set, with the class hash it points to
+async fn read_implementation(
+    rpc: &RpcContext,
+    contract_address: &str,
+    block: &BlockId,
+    slots: std::ops::Range<usize>,
+) -> Result<Option<(usize, String)>, String> {
+    for slot in slots {
+        let (variable, kind) = PROXY_SLOTS[slot];
+        // A storage variable without keys lives at sn_keccak of its name
+        let value = get_storage_at(rpc, contract_address, &sn_keccak(variable), block.clone()).await
+            .map_err(|e| format!("Failed to read {}: {}", variable, e))?;
+        if cairo_serde::parse_felt(&value).is_none_or(|v| v.bits() == 0) {
+            continue;
+        }
+        return match kind {
+            Implementation::ClassHash => Ok(Some((slot, value))),
+            Implementation::Address => get_class_hash_at(rpc, block.clone(), &value).await
+                .map(|class_hash| Some((slot, class_hash)))
+                .map_err(|e| format!("Failed to get the class of implementation {}: {}", value, e)),
+        };
+    }
+    Ok(None)
+}
+
+/// ABI an override stands for: its uploaded JSON, or the ABI of its class
+pub async fn override_abi(rpc: &RpcContext, record: &AbiOverrideRecord) -> Result<Value, String> {
+    match (&record.abi, &record.class_hash) {
+        (Some(abi), _) => parse_abi(abi),
+        (None, Some(class_hash)) => get_class_abi(rpc, class_hash).await
+            .map_err(|e| format!("Failed to fetch class {}: {}", class_hash, e)),
+        (None, None) => Err("neither an ABI nor a class hash".to_string()),
+    }
+}
+
+/// An uploaded ABI: the ABI array itself, or a contract class (as compiled or as returned by
+/// `starknet_getClass`) holding it
+pub fn parse_abi(text: &str) -> Result<Value, String> {
+    let value: Value = serde_json::from_str(text).map_err(|e| format!("ABI is not valid JSON: {}", e))?;
+    match value {
+        Value::Array(_) => Ok(value),
+        Value::Object(mut class) => match class.remove("abi") {
+            Some(Value::String(abi)) => parse_abi(&abi),
+            Some(abi @ Value::Array(_)) => Ok(abi),
+            _ => Err("class has no abi".to_string()),
+        },
+        Value::String(abi) => parse_abi(&abi),
+        _ => Err("ABI must be a JSON array".to_string()),
+    }
+}
+
+/// `--abi-override ADDRESS=SOURCE[@FROM-TO]`, SOURCE being a class hash or the path of an ABI file
+pub fn parse_cli_override(spec: &str) -> Result<AbiOverrideRecord, String> {
+    let (address, source) = spec.split_once('=')
+        .ok_or_else(|| format!("'{}' is not ADDRESS=SOURCE[@FROM-TO]", spec))?;
+    let (source, range) = match source.rsplit_once('@') {
+        Some((source, range)) => (source, Some(range)),
+        None => (source, None),
+    };
+    let (from_block, to_block) = match range {
+        Some(range) => parse_block_range(range)?,
+        None => (None, None),
+    };
+
+    let is_class_hash = source.starts_with("0x") && source[2..].chars().all(|c| c.is_ascii_hexdigit());
+    let (abi, class_hash) = if is_class_hash {
+        (None, Some(source.to_string()))
+    } else {
+        let text = std::fs::read_to_string(source).map_err(|e| format!("Failed to read ABI file {}: {}", source, e))?;
+        (Some(parse_abi(&text).map_err(|e| format!("{}: {}", source, e))?.to_string()), None)
+    };
+
+    Ok(AbiOverrideRecord {
+        id: format!("cli:{}", spec),
+        deployment_id: None,
+        contract_address: Database::normalize_address(address.trim()),
+        abi,
+        class_hash,
+        from_block,
+        to_block,
+        created_at: chrono::Utc::now(),
+    })
+}
+
+/// "FROM-TO", "FROM-" or "-TO"
+fn parse_block_range(range: &str) -> Result<(Option<u64>, Option<u64>), String> {
+    let (from, to) = range.split_once('-').ok_or_else(|| format!("block range '{}' is not FROM-TO", range))?;
+    let block = |b: &str| -> Result<Option<u64>, String> {
+        let b = b.trim();
+        if b.is_empty() {
+            return Ok(None);
+        }
+        b.parse().map(Some).map_err(|_| format!("invalid block '{}' in range '{}'", b, range))
+    };
+    let (from, to) = (block(from)?, block(to)?);
+    if let (Some(from), Some(to)) = (from, to) {
+        if from > to {
+            return Err(format!("block range '{}' ends before it starts", range));
+        }
+    }
+    Ok((from, to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overrides_apply_to_their_blocks() {
+        let abi = |name: &str| serde_json::json!([{"type": "event", "name": name}]);
+        let abis = ContractAbis {
+            default: abi("Own"),
+            ranges: vec![(Some(100), Some(199), abi("V1")), (Some(150), None, abi("V2"))],
+            upgrades: Vec::new(),
+        };
+
+        assert_eq!(abis.for_block(99), &abi("Own"));
+        assert_eq!(abis.for_block(100), &abi("V1"));
+        // Both cover 150-199; the later override wins
+        assert_eq!(abis.for_block(150), &abi("V2"));
+        assert_eq!(abis.for_block(u64::MAX), &abi("V2"));
+    }
+
+    #[test]
+    fn test_proxies_decode_with_the_implementation_of_the_block() {
+        let abi = |name: &str| serde_json::json!([{"type": "event", "name": name}]);
+        let abis = ContractAbis {
+            default: abi("Own"),
+            ranges: vec![(Some(500), Some(599), abi("Override"))],
+            upgrades: vec![(100, abi("V1")), (300, abi("V2"))],
+        };
+
+        // Blocks before the first one resolved keep the contract's own ABI
+        assert_eq!(abis.for_block(99), &abi("Own"));
+        assert_eq!(abis.for_block(299), &abi("V1"));
+        assert_eq!(abis.for_block(300), &abi("V2"));
+        // Overrides limited to a range still win there
+        assert_eq!(abis.for_block(550), &abi("Override"));
+        assert_eq!(abis.for_block(u64::MAX), &abi("V2"));
+    }
+
+    #[test]
+    fn test_uploaded_abis_and_cli_specs() {
+        let abi = r#"[{"type": "event", "name": "Transfer", "keys": [], "data": []}]"#;
+        assert!(parse_abi(abi).unwrap().is_array());
+        // A class as returned by starknet_getClass, ABI as a string
+        let class = serde_json::json!({"abi": abi, "sierra_program": []}).to_string();
+        assert_eq!(parse_abi(&class).unwrap(), parse_abi(abi).unwrap());
+        assert!(parse_abi(r#"{"sierra_program": []}"#).is_err());
+        assert!(parse_abi("not json").is_err());
+
+        let record = parse_cli_override("0x49d3=0x2760f25d5a4fb2bdde5f561fd0b44a3dee78c28903577d37d669939d97036a0@1000-").unwrap();
+        assert_eq!(record.contract_address, Database::normalize_address("0x49d3"));
+        assert!(record.class_hash.is_some() && record.abi.is_none());
+        assert_eq!((record.from_block, record.to_block), (Some(1000), None));
+
+        assert_eq!(parse_block_range("-20").unwrap(), (None, Some(20)));
+        assert!(parse_block_range("20-10").is_err());
+        assert!(parse_cli_override("0x49d3").is_err());
+        assert!(parse_cli_override("0x49d3=/does/not/exist.json").is_err());
+    }
+}
@@ -0,0 +1,409 @@
+use crate::database::{AccountEventRecord, Database};
+use crate::rpc_types::{BlockId, EventFilter, TransactionReceipt};
+use crate::starknet::{decode_event, get_contract_abi_string, get_current_block_number, get_events_chunk, get_transaction_receipts, RpcContext};
+use chrono::Utc;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use tokio::time::{sleep, Duration};
+
+/// Cursor key prefix used in `indexer_state`; each account has its own cursor, `account_watch:<account>`,
+/// so accounts added later are scanned from the start block while the others carry on
+const ACCOUNT_WATCH_CURSOR: &str = "account_watch";
+
+/// Events requested per `starknet_getEvents` page
+const EVENTS_PAGE_SIZE: u32 = 1000;
+
+#[derive(Clone)]
+pub struct AccountWatchConfig {
+    pub accounts: Vec<String>,
+    /// Key positions an account is looked up at: 1 and 2 are `from`/`to` of ERC20/ERC721 Transfer events
+    pub key_positions: Vec<usize>,
+    pub start_block: Option<u64>,
+    pub chunk_size: u64,
+    pub sync_interval: u64,
+}
+
+impl Default for AccountWatchConfig {
+    fn default() -> Self {
+        Self {
+            accounts: Vec::new(),
+            key_positions: vec![1, 2],
+            start_block: None,
+            chunk_size: 2000,
+            sync_interval: 2,
+        }
+    }
+}
+
+impl AccountWatchConfig {
+    pub fn is_enabled(&self) -> bool {
+        !self.accounts.is_empty()
+    }
+}
+
+/// Follows watched accounts across every contract by scanning events without an address filter
+pub struct AccountWatcher {
+    database: Arc<Database>,
+    rpc: RpcContext,
+    config: AccountWatchConfig,
+    accounts: HashSet<String>,
+    abis: Mutex<HashMap<String, Value>>, // ABI per emitting contract, fetched on first sight
+}
+
+impl AccountWatcher {
+    pub fn new(database: Arc<Database>, rpc: RpcContext, config: AccountWatchConfig) -> Self {
+        let accounts = config.accounts.iter().map(|a| Database::normalize_address(a)).collect();
+        Self {
+            database,
+            rpc,
+            config,
+            accounts,
+            abis: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn start_syncing(&self) {
+        println!("🚀 Starting account watcher for {} accounts at key positions {:?}",
+                self.accounts.len(), self.config.key_positions);
+
+        loop {
+            match self.sync_to_head().await {
+                Ok(events) if events > 0 => println!("   👤 Account watcher recorded {} events", events),
+                Ok(_) => {}
+                Err(e) => eprintln!("❌ Error in account watcher: {}", e),
+            }
+
+            sleep(Duration::from_secs(self.config.sync_interval)).await;
+        }
+    }
+
+    async fn sync_to_head(&self) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let current_block = get_current_block_number(&self.rpc).await
+            .map_err(|e| format!("Failed to get current block: {}", e))?;
+
+        // Next block to scan for each account
+        let mut next_blocks = HashMap::new();
+        for account in &self.accounts {
+            let next_block = match self.database.get_chain_indexer_state(&self.rpc.chain_id, &account_cursor(account)).await? {
+                Some(state) => state.last_synced_block + 1,
+                // Without a configured start block only new activity is followed
+                None => self.config.start_block.unwrap_or(current_block),
+            };
+            next_blocks.insert(account.clone(), next_block);
+        }
+
+        let mut total_events = 0;
+        while let Some((accounts, from_block, to_block)) = next_scan(&next_blocks, current_block, self.config.chunk_size) {
+            let mut scans = Vec::new();
+            for &position in &self.config.key_positions {
+                scans.push(self.fetch_events(&accounts, position, from_block, to_block).await?);
+            }
+            let receipts = self.receipts(&scans).await?;
+
+            let watched: HashSet<String> = accounts.iter().cloned().collect();
+            let mut records = Vec::new();
+            for (event, event_index) in index_events(&scans, &receipts)? {
+                records.extend(self.to_records(&event, event_index, &watched).await);
+            }
+
+            total_events += records.len();
+            self.database.insert_account_events(&records).await?;
+            for account in &accounts {
+                self.database.update_chain_indexer_state(&self.rpc.chain_id, &account_cursor(account), to_block).await?;
+                next_blocks.insert(account.clone(), to_block + 1);
+            }
+        }
+
+        Ok(total_events)
+    }
+
+    /// Receipts of the transactions the scanned events were emitted in
+    async fn receipts(&self, scans: &[Vec<Value>]) -> Result<HashMap<String, TransactionReceipt>, String> {
+        let tx_hashes: Vec<String> = scans.iter()
+            .flatten()
+            .filter_map(|event| event.get("transaction_hash").and_then(|v| v.as_str()))
+            .map(str::to_string)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        if tx_hashes.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let receipts = get_transaction_receipts(&self.rpc, &tx_hashes).await
+            .map_err(|e| format!("Failed to get receipts: {}", e))?;
+        tx_hashes.into_iter().zip(receipts)
+            .map(|(tx_hash, receipt)| {
+                let receipt = receipt.map_err(|e| format!("Failed to get receipt of {}: {}", tx_hash, e))?;
+                Ok((tx_hash, receipt))
+            })
+            .collect()
+    }
+
+    /// All events of the block range with a watched account at `position`, following continuation tokens
+    async fn fetch_events(&self, accounts: &[String], position: usize, from_block: u64, to_block: u64) -> Result<Vec<Value>, String> {
+        let mut filter = EventFilter {
+            from_block: Some(BlockId::Number(from_block)),
+            to_block: Some(BlockId::Number(to_block)),
+            address: None,
+            keys: key_filter(accounts, position),
+            chunk_size: EVENTS_PAGE_SIZE,
+            continuation_token: None,
+        };
+
+        let mut events = Vec::new();
+        loop {
+            let chunk = get_events_chunk(&self.rpc, &filter).await
+                .map_err(|e| format!("Failed to get events: {}", e))?;
+            for event in &chunk.events {
+                events.push(serde_json::to_value(event).map_err(|e| e.to_string())?);
+            }
+
+            match chunk.continuation_token {
+                Some(token) => filter.continuation_token = Some(token),
+                None => return Ok(events),
+            }
+        }
+    }
+
+    async fn abi_for(&self, contract_address: &str) -> Value {
+        if let Some(abi) = self.abis.lock().unwrap().get(contract_address) {
+            return abi.clone();
+        }
+
+        let abi = get_contract_abi_string(&self.rpc, contract_address).await
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or(Value::Array(vec![]));
+        self.abis.lock().unwrap().insert(contract_address.to_string(), abi.clone());
+        abi
+    }
+
+    /// One record per account of `accounts` the event mentions
+    async fn to_records(&self, event: &Value, event_index: usize, accounts: &HashSet<String>) -> Vec<AccountEventRecord> {
+        let mentions = watched_mentions(event, accounts, &self.config.key_positions);
+        if mentions.is_empty() {
+            return Vec::new();
+        }
+
+        let contract_address = Database::normalize_address(event.get("from_address").and_then(|v| v.as_str()).unwrap_or_default());
+        let decoded = decode_event(&self.abi_for(&contract_address).await, event, &Default::default());
+        let event_type = decoded.event_type.clone();
+        let keys = event.get("keys").cloned().unwrap_or(Value::Array(vec![]));
+        let id = event_id(event, event_index);
+
+        mentions.into_iter().map(|(account_address, position)| AccountEventRecord {
+            id: id.clone(),
+            account_address,
+            key_position: position as i32,
+            contract_address: contract_address.clone(),
+            event_type: event_type.clone(),
+            event_selector: keys.get(0).and_then(|k| k.as_str()).map(Database::normalize_address).unwrap_or_default(),
+            block_number: event.get("block_number").and_then(|v| v.as_u64()).unwrap_or_default(),
+            transaction_hash: event.get("transaction_hash").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+            timestamp: Utc::now(),
+            decoded_data: Some(decoded.to_stored().to_string()), // Typed, written in each query's value format
+            raw_data: event.get("data").cloned().unwrap_or(Value::Array(vec![])).to_string(),
+            raw_keys: keys.to_string(),
+            chain_id: self.rpc.chain_id.clone(),
+        }).collect()
+    }
+}
+
+/// `starknet_getEvents` key filter accepting any value before `position` and a watched account at it
+pub fn key_filter(accounts: &[String], position: usize) -> Vec<Vec<String>> {
+    let mut keys = vec![Vec::new(); position];
+    keys.push(accounts.to_vec());
+    keys
+}
+
+/// Watched accounts found in the event's keys, with the position they were found at
+pub fn watched_mentions(event: &Value, accounts: &HashSet<String>, positions: &[usize]) -> Vec<(String, usize)> {
+    let keys = event.get("keys").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+    let mut mentions: Vec<(String, usize)> = Vec::new();
+
+    for &position in positions {
+        if let Some(key) = keys.get(position).and_then(|k| k.as_str()) {
+            let account = Database::normalize_address(key);
+            if accounts.contains(&account) && !mentions.iter().any(|(a, _)| a == &account) {
+                mentions.push((account, position));
+            }
+        }
+    }
+
+    mentions
+}
+
+fn account_cursor(account: &str) -> String {
+    format!("{}:{}", ACCOUNT_WATCH_CURSOR, account)
+}
+
+/// The next range to scan: from the earliest account's next block, for the accounts at that block, up to
+/// `chunk_size` blocks and not past the block where the next account resumes, so lagging accounts catch
+/// up on their own before being scanned together with the others
+fn next_scan(next_blocks: &HashMap<String, u64>, current_block: u64, chunk_size: u64) -> Option<(Vec<String>, u64, u64)> {
+    let from_block = *next_blocks.values().filter(|&&next| next <= current_block).min()?;
+    let mut accounts: Vec<String> = next_blocks.iter()
+        .filter(|(_, &next)| next == from_block)
+        .map(|(account, _)| account.clone())
+        .collect();
+    accounts.sort();
+
+    let mut to_block = std::cmp::min(from_block + chunk_size.max(1) - 1, current_block);
+    if let Some(&resume) = next_blocks.values().filter(|&&next| next > from_block).min() {
+        to_block = std::cmp::min(to_block, resume - 1);
+    }
+    Some((accounts, from_block, to_block))
+}
+
+/// Each event found by the key position scans once, with its index among the events of its transaction's
+/// receipt. `starknet_getEvents` has no event index, and the same event comes back once per position
+/// scanned, while identical events of one transaction (the same transfer made twice by a multicall) are
+/// distinct: the n-th of a scan is the n-th identical event of the receipt.
+pub fn index_events(scans: &[Vec<Value>], receipts: &HashMap<String, TransactionReceipt>) -> Result<Vec<(Value, usize)>, String> {
+    let mut indexed: Vec<(Value, usize)> = Vec::new();
+    let mut seen = HashSet::new();
+    for scan in scans {
+        let mut occurrences: HashMap<(String, EventContent), usize> = HashMap::new();
+        for event in scan {
+            let tx_hash = event.get("transaction_hash").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+            let content = EventContent::of_event(event);
+            let occurrence = occurrences.entry((tx_hash.clone(), content.clone())).or_insert(0);
+
+            let receipt = receipts.get(&tx_hash).ok_or_else(|| format!("No receipt for transaction {}", tx_hash))?;
+            let event_index = receipt.events.iter()
+                .enumerate()
+                .filter(|(_, receipt_event)| EventContent::of_receipt_event(receipt_event) == content)
+                .map(|(index, _)| index)
+                .nth(*occurrence)
+                .ok_or_else(|| format!("Event of {} not found in its receipt", tx_hash))?;
+            *occurrence += 1;
+
+            if seen.insert((tx_hash, event_index)) {
+                indexed.push((event.clone(), event_index));
+            }
+        }
+    }
+    Ok(indexed)
+}
+
+/// Emitter, keys and data of an event, with every felt normalized
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct EventContent(Vec<String>);
+
+impl EventContent {
+    fn of_event(event: &Value) -> Self {
+        let felts = |field: &str| event.get(field).and_then(|v| v.as_array()).cloned().unwrap_or_default();
+        let from_address = event.get("from_address").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        let keys = felts("keys").iter().filter_map(|k| k.as_str()).map(str::to_string).collect::<Vec<_>>();
+        let data = felts("data").iter().filter_map(|d| d.as_str()).map(str::to_string).collect::<Vec<_>>();
+        Self::new(&from_address, &keys, &data)
+    }
+
+    fn of_receipt_event(event: &crate::rpc_types::ReceiptEvent) -> Self {
+        Self::new(&event.from_address, &event.keys, &event.data)
+    }
+
+    fn new(from_address: &str, keys: &[String], data: &[String]) -> Self {
+        // Keys and data are told apart by their lengths
+        let lengths = [keys.len().to_string(), data.len().to_string()];
+        let felts = std::iter::once(from_address).chain(keys.iter().map(String::as_str)).chain(data.iter().map(String::as_str));
+        Self(lengths.into_iter().chain(felts.map(Database::normalize_address)).collect())
+    }
+}
+
+/// An event is its transaction and its index among the events of that transaction
+pub fn event_id(event: &Value, event_index: usize) -> String {
+    let tx_hash = event.get("transaction_hash").and_then(|v| v.as_str()).unwrap_or_default();
+    format!("{}:{}", tx_hash, event_index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VAULT: &str = "0x03f1a2b";
+
+    fn events() -> Vec<Value> {
+        let raw: Value = serde_json::from_str(include_str!("../tests/fixtures/accounts/events_page.json")).unwrap();
+        raw["result"]["events"].as_array().unwrap().clone()
+    }
+
+    #[test]
+    fn test_key_filter_targets_position() {
+        let filter = key_filter(&[VAULT.to_string()], 2);
+        assert_eq!(filter, vec![vec![], vec![], vec![VAULT.to_string()]]);
+    }
+
+    #[test]
+    fn test_mentions_in_any_contract() {
+        let accounts: HashSet<String> = [Database::normalize_address(VAULT)].into_iter().collect();
+        let events = events();
+
+        let mentions: Vec<Vec<(String, usize)>> = events.iter()
+            .map(|event| watched_mentions(event, &accounts, &[1, 2]))
+            .collect();
+
+        // Sent on one token, received on another, and only mentioned in data
+        assert_eq!(mentions[0], vec![(Database::normalize_address(VAULT), 1)]);
+        assert_eq!(mentions[1], vec![(Database::normalize_address(VAULT), 2)]);
+        assert!(mentions[2].is_empty());
+    }
+
+    fn receipt(tx_hash: &str, events: &[&Value]) -> TransactionReceipt {
+        serde_json::from_value(serde_json::json!({ "transaction_hash": tx_hash, "events": events })).unwrap()
+    }
+
+    #[test]
+    fn test_identical_events_of_a_transaction_get_distinct_ids() {
+        let transfer = events()[0].clone();
+        let tx_hash = transfer["transaction_hash"].as_str().unwrap().to_string();
+        let approval = events()[1].clone();
+        // A multicall making the same transfer twice, with another event in between; the receipt writes
+        // felts unpadded where getEvents padded one
+        let mut in_receipt = transfer.clone();
+        in_receipt["keys"][1] = Value::String(Database::normalize_address(VAULT));
+        let receipts = [(tx_hash.clone(), receipt(&tx_hash, &[&in_receipt, &approval, &in_receipt]))].into_iter().collect();
+
+        // The sender scan sees both transfers, the recipient scan the second event
+        let scans = vec![vec![transfer.clone(), transfer.clone()], vec![approval.clone()]];
+        let indexed = index_events(&scans, &receipts).unwrap();
+
+        let ids: Vec<String> = indexed.iter().map(|(event, index)| event_id(event, *index)).collect();
+        assert_eq!(ids, vec![format!("{}:0", tx_hash), format!("{}:2", tx_hash), format!("{}:1", tx_hash)]);
+    }
+
+    #[test]
+    fn test_event_seen_by_several_scans_is_indexed_once() {
+        let event = events()[0].clone();
+        let tx_hash = event["transaction_hash"].as_str().unwrap().to_string();
+        let receipts = [(tx_hash.clone(), receipt(&tx_hash, &[&event]))].into_iter().collect();
+
+        let indexed = index_events(&[vec![event.clone()], vec![event.clone()]], &receipts).unwrap();
+        assert_eq!(indexed.len(), 1);
+
+        // An event missing from its receipt fails the chunk so it is retried
+        let other = events()[1].clone();
+        assert!(index_events(&[vec![other]], &receipts).is_err());
+    }
+
+    #[test]
+    fn test_next_scan_backfills_new_account() {
+        let mut next_blocks: HashMap<String, u64> = [("0x1".to_string(), 1_000), ("0x2".to_string(), 10)].into_iter().collect();
+
+        // The new account catches up alone, chunk_size blocks at a time
+        assert_eq!(next_scan(&next_blocks, 1_200, 100), Some((vec!["0x2".to_string()], 10, 109)));
+
+        // and stops short of the block the other account resumes at, so both scan together from there
+        next_blocks.insert("0x2".to_string(), 950);
+        assert_eq!(next_scan(&next_blocks, 1_200, 100), Some((vec!["0x2".to_string()], 950, 999)));
+        next_blocks.insert("0x2".to_string(), 1_000);
+        assert_eq!(next_scan(&next_blocks, 1_200, 100), Some((vec!["0x1".to_string(), "0x2".to_string()], 1_000, 1_099)));
+
+        // Up to the head, and nothing left once past it
+        assert_eq!(next_scan(&next_blocks, 1_050, 100), Some((vec!["0x1".to_string(), "0x2".to_string()], 1_000, 1_050)));
+        next_blocks.values_mut().for_each(|next| *next = 1_051);
+        assert_eq!(next_scan(&next_blocks, 1_050, 100), None);
+    }
+}
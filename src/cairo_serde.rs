@@ -0,0 +1,366 @@
+use num_bigint::{BigInt, BigUint};
+use serde_json::Value;
+
+/// Felts of an event's keys or data (or of calldata), consumed front to back as values are decoded.
+/// Cairo Serde writes every value as a fixed number of felts, except arrays (length first) and enums
+/// (variant index first), so a value's size is only known once the values before it are decoded.
+pub struct FeltCursor<'a> {
+    felts: &'a [Value],
+    position: usize,
+}
+
+impl<'a> FeltCursor<'a> {
+    pub fn new(felts: &'a [Value]) -> Self {
+        Self::at(felts, 0)
+    }
+
+    /// Cursor that starts after `position` felts, e.g. after an event's selector keys
+    pub fn at(felts: &'a [Value], position: usize) -> Self {
+        Self { felts, position: position.min(felts.len()) }
+    }
+
+    /// Next felt as a hex string, for a value of `type_name`
+    pub fn next(&mut self, type_name: &str) -> Result<&'a str, String> {
+        let felt = self.felts.get(self.position)
+            .ok_or_else(|| format!("{} felts are too few for {}", self.felts.len(), type_name))?;
+        self.position += 1;
+        felt.as_str().ok_or_else(|| format!("felt {} of {} is not a string: {}", self.position - 1, type_name, felt))
+    }
+
+    /// Next felt, without consuming it
+    pub fn peek(&self) -> Option<&'a Value> {
+        self.felts.get(self.position)
+    }
+
+    /// Next felt as a length or variant index
+    pub fn next_usize(&mut self, type_name: &str) -> Result<usize, String> {
+        let felt = self.next(type_name)?;
+        felt_to_usize(felt).ok_or_else(|| format!("{} is not a valid length or index for {}", felt, type_name))
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.felts.len() - self.position
+    }
+}
+
+fn felt_to_usize(felt: &str) -> Option<usize> {
+    match felt.strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(if hex.is_empty() { "0" } else { hex }, 16).ok(),
+        None => felt.parse().ok(),
+    }
+}
+
+/// Value of a felt given as hex (`0x...`) or decimal
+pub fn parse_felt(felt: &str) -> Option<BigUint> {
+    match felt.strip_prefix("0x").or_else(|| felt.strip_prefix("0X")) {
+        Some("") => Some(BigUint::default()),
+        Some(hex) => BigUint::parse_bytes(hex.as_bytes(), 16),
+        None => BigUint::parse_bytes(felt.as_bytes(), 10),
+    }
+}
+
+/// Width of the unsigned integer types that fit in one felt
+pub fn unsigned_bits(type_name: &str) -> Option<u64> {
+    match type_name.strip_prefix("core::integer::").unwrap_or(type_name) {
+        "u8" => Some(8),
+        "u16" => Some(16),
+        "u32" => Some(32),
+        "u64" => Some(64),
+        "u128" => Some(128),
+        "usize" => Some(64),
+        _ => None,
+    }
+}
+
+/// Number of u128 limbs of the integer types that span several felts, least significant first
+pub fn wide_unsigned_limbs(type_name: &str) -> Option<usize> {
+    match type_name {
+        "core::integer::u256" | "u256" | "Uint256" => Some(2), // Cairo 0 ABIs declare Uint256 as a struct of the same limbs
+        "core::integer::u512" | "u512" => Some(4),
+        _ => None,
+    }
+}
+
+/// Next felt as an unsigned integer of `bits` bits
+pub fn read_unsigned(cursor: &mut FeltCursor, bits: u64, type_name: &str) -> Result<BigUint, String> {
+    let felt = cursor.next(type_name)?;
+    let value = parse_felt(felt).ok_or_else(|| format!("{} is not a number for {}", felt, type_name))?;
+    if value.bits() > bits {
+        return Err(format!("{} does not fit in {}", felt, type_name));
+    }
+    Ok(value)
+}
+
+/// u256 / u512 from their u128 limbs
+pub fn read_wide_unsigned(cursor: &mut FeltCursor, limbs: usize, type_name: &str) -> Result<BigUint, String> {
+    let mut value = BigUint::default();
+    for limb in 0..limbs {
+        value += read_unsigned(cursor, 128, type_name)? << (128 * limb);
+    }
+    Ok(value)
+}
+
+/// Integers up to 32 bits as JSON numbers; wider ones as exact decimal strings, since JSON clients
+/// commonly read numbers as doubles and would round anything above 2^53
+pub fn unsigned_json(value: &BigUint, bits: u64) -> Value {
+    match u32::try_from(value) {
+        Ok(small) if bits <= 32 => Value::from(small),
+        _ => Value::String(value.to_string()),
+    }
+}
+
+/// Width of the signed integer types
+pub fn signed_bits(type_name: &str) -> Option<u64> {
+    match type_name.strip_prefix("core::integer::").unwrap_or(type_name) {
+        "i8" => Some(8),
+        "i16" => Some(16),
+        "i32" => Some(32),
+        "i64" => Some(64),
+        "i128" => Some(128),
+        _ => None,
+    }
+}
+
+/// The Stark field prime, 2^251 + 17 * 2^192 + 1
+pub fn field_prime() -> BigUint {
+    (BigUint::from(1u8) << 251) + (BigUint::from(17u8) << 192) + 1u8
+}
+
+/// Next felt as a signed integer of `bits` bits. Negative values are encoded as `P - |x|`, so felts
+/// above P/2 are read back as negatives before the range check.
+pub fn read_signed(cursor: &mut FeltCursor, bits: u64, type_name: &str) -> Result<BigInt, String> {
+    let felt = cursor.next(type_name)?;
+    let value = parse_felt(felt).ok_or_else(|| format!("{} is not a number for {}", felt, type_name))?;
+    let prime = field_prime();
+    if value >= prime {
+        return Err(format!("{} is not a field element for {}", felt, type_name));
+    }
+    let value = if value > &prime >> 1 {
+        -BigInt::from(prime - value)
+    } else {
+        BigInt::from(value)
+    };
+    let limit = BigInt::from(1u8) << (bits - 1);
+    if value < -limit.clone() || value >= limit {
+        return Err(format!("{} does not fit in {}", felt, type_name));
+    }
+    Ok(value)
+}
+
+/// Like `unsigned_json`: numbers up to 32 bits, exact decimal strings beyond
+pub fn signed_json(value: &BigInt, bits: u64) -> Value {
+    match i32::try_from(value) {
+        Ok(small) if bits <= 32 => Value::from(small),
+        _ => Value::String(value.to_string()),
+    }
+}
+
+/// Bytes of a felt, big-endian and without leading zeros, when it holds at most `max_len` of them
+fn felt_bytes(felt: &str, max_len: usize, type_name: &str) -> Result<Vec<u8>, String> {
+    let value = parse_felt(felt).ok_or_else(|| format!("{} is not a number for {}", felt, type_name))?;
+    let bytes = if value.bits() == 0 { Vec::new() } else { value.to_bytes_be() };
+    if bytes.len() > max_len {
+        return Err(format!("{} is longer than {} bytes for {}", felt, max_len, type_name));
+    }
+    Ok(bytes)
+}
+
+/// Text of bytes that are valid UTF-8, otherwise the bytes as hex
+fn bytes_to_text(bytes: Vec<u8>) -> String {
+    String::from_utf8(bytes).unwrap_or_else(|e| format!("0x{}", hex::encode(e.into_bytes())))
+}
+
+/// Cairo short string: up to 31 bytes packed big-endian into one felt
+pub fn short_string(felt: &str) -> Result<String, String> {
+    felt_bytes(felt, 31, "short string").map(bytes_to_text)
+}
+
+/// `core::byte_array::ByteArray`: the number of full 31-byte words, the words, then a pending word
+/// holding the last `pending_word_len` (< 31) bytes. Text that is not UTF-8 is returned as hex.
+pub fn read_byte_array(cursor: &mut FeltCursor) -> Result<String, String> {
+    const TYPE: &str = "core::byte_array::ByteArray";
+    let words = cursor.next_usize(TYPE)?;
+    if words > cursor.remaining().saturating_sub(2) {
+        return Err(format!("{} of {} words with only {} felts left", TYPE, words, cursor.remaining()));
+    }
+    let mut bytes = Vec::with_capacity(words * 31 + 30);
+    for _ in 0..words {
+        let word = felt_bytes(cursor.next(TYPE)?, 31, TYPE)?;
+        bytes.resize(bytes.len() + 31 - word.len(), 0);
+        bytes.extend(word);
+    }
+    let pending_word = cursor.next(TYPE)?;
+    let pending_len = cursor.next_usize(TYPE)?;
+    if pending_len > 30 {
+        return Err(format!("pending word length {} of {} is over 30", pending_len, TYPE));
+    }
+    let pending = felt_bytes(pending_word, pending_len, TYPE)?;
+    bytes.resize(bytes.len() + pending_len - pending.len(), 0);
+    bytes.extend(pending);
+    Ok(bytes_to_text(bytes))
+}
+
+/// Readable text packed into a felt, if it looks like some: two or more printable ASCII characters.
+/// This is a guess (`0x4142` may as well be the number 16706), so it only applies when asked for.
+pub fn guess_short_string(felt: &str) -> Option<String> {
+    let text = String::from_utf8(felt_bytes(felt, 31, "short string").ok()?).ok()?;
+    let printable = text.chars().all(|c| c.is_ascii_alphanumeric() || c.is_ascii_punctuation() || c == ' ');
+    Some(text).filter(|text| text.len() > 1 && printable)
+}
+
+/// Element type of `core::array::Array::<T>` / `core::array::Span::<T>`
+pub fn array_element(type_name: &str) -> Option<&str> {
+    generic_arg(type_name, "core::array::Array::<").or_else(|| generic_arg(type_name, "core::array::Span::<"))
+}
+
+/// `T` of `<prefix>T>`, e.g. `generic_arg("core::option::Option::<u8>", "core::option::Option::<")`
+pub fn generic_arg<'t>(type_name: &'t str, prefix: &str) -> Option<&'t str> {
+    type_name.strip_prefix(prefix)?.strip_suffix('>')
+}
+
+/// Member types of a tuple type such as `(core::felt252, (core::bool, core::integer::u8))`
+pub fn tuple_members(type_name: &str) -> Option<Vec<&str>> {
+    let inner = type_name.strip_prefix('(')?.strip_suffix(')')?;
+    let mut members = Vec::new();
+    let (mut depth, mut start) = (0i32, 0);
+    for (index, c) in inner.char_indices() {
+        match c {
+            '(' | '<' => depth += 1,
+            ')' | '>' => depth -= 1,
+            ',' if depth == 0 => {
+                members.push(inner[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    if !inner[start..].trim().is_empty() {
+        members.push(inner[start..].trim());
+    }
+    Some(members)
+}
+
+/// Name and type of a Cairo 0 tuple member, which may be named: `x: felt`
+pub fn named_member(member: &str) -> (Option<&str>, &str) {
+    match member.split_once(':') {
+        Some((name, member_type)) if !member_type.starts_with(':') && !name.trim().is_empty()
+            && name.trim().chars().all(|c| c.is_ascii_alphanumeric() || c == '_') => (Some(name.trim()), member_type.trim()),
+        _ => (None, member),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_reports_missing_felts() {
+        let felts = vec![Value::from("0x2"), Value::from("0xa")];
+        let mut cursor = FeltCursor::new(&felts);
+        assert_eq!(cursor.next_usize("len").unwrap(), 2);
+        assert_eq!(cursor.next("core::felt252").unwrap(), "0xa");
+        assert_eq!(cursor.remaining(), 0);
+        assert!(cursor.next("core::felt252").is_err());
+    }
+
+    #[test]
+    fn test_wide_integers_are_exact() {
+        let max = vec![Value::from("0xffffffffffffffffffffffffffffffff"); 4];
+        let u256_max = read_wide_unsigned(&mut FeltCursor::new(&max), 2, "u256").unwrap();
+        assert_eq!(unsigned_json(&u256_max, 256), "115792089237316195423570985008687907853269984665640564039457584007913129639935");
+        let u512_max = read_wide_unsigned(&mut FeltCursor::new(&max), 4, "u512").unwrap();
+        assert_eq!(u512_max.bits(), 512);
+
+        // low = 1, high = 2: 2 * 2^128 + 1
+        let limbs = vec![Value::from("0x1"), Value::from("0x2")];
+        assert_eq!(read_wide_unsigned(&mut FeltCursor::new(&limbs), 2, "u256").unwrap().to_string(), "680564733841876926926749214863536422913");
+
+        // A limb wider than u128 is malformed
+        let wide = vec![Value::from("0x100000000000000000000000000000000"), Value::from("0x0")];
+        assert!(read_wide_unsigned(&mut FeltCursor::new(&wide), 2, "u256").is_err());
+
+        let u8_max = vec![Value::from("0xff"), Value::from("0x100")];
+        let mut cursor = FeltCursor::new(&u8_max);
+        assert_eq!(unsigned_json(&read_unsigned(&mut cursor, 8, "u8").unwrap(), 8), 255);
+        assert!(read_unsigned(&mut cursor, 8, "u8").is_err());
+        assert_eq!(unsigned_json(&BigUint::from(u64::MAX), 64), "18446744073709551615");
+    }
+
+    #[test]
+    fn test_signed_integers_wrap_around_the_field() {
+        let signed = |felt: &str, bits: u64| {
+            let felts = vec![Value::from(felt)];
+            read_signed(&mut FeltCursor::new(&felts), bits, "signed").map(|n| signed_json(&n, bits))
+        };
+        let minus = |x: &BigUint| format!("0x{}", (field_prime() - x).to_str_radix(16));
+
+        assert_eq!(signed("0x0", 8).unwrap(), 0);
+        assert_eq!(signed("0x7f", 8).unwrap(), 127);
+        assert_eq!(signed(&minus(&BigUint::from(1u8)), 8).unwrap(), -1);
+        assert_eq!(signed(&minus(&BigUint::from(128u8)), 8).unwrap(), -128);
+        assert!(signed("0x80", 8).is_err());
+        assert!(signed(&minus(&BigUint::from(129u8)), 8).is_err());
+
+        assert_eq!(signed(&minus(&BigUint::from(2147483648u32)), 32).unwrap(), i32::MIN);
+        assert_eq!(signed(&format!("{:#x}", i64::MAX), 64).unwrap(), i64::MAX.to_string());
+        assert_eq!(signed(&minus(&BigUint::from(1u64 << 63)), 64).unwrap(), i64::MIN.to_string());
+
+        let i128_min = BigUint::from(1u8) << 127;
+        assert_eq!(signed(&minus(&i128_min), 128).unwrap(), i128::MIN.to_string());
+        assert_eq!(signed(&format!("{:#x}", i128::MAX), 128).unwrap(), i128::MAX.to_string());
+        assert!(signed(&format!("0x{}", i128_min.to_str_radix(16)), 128).is_err());
+        assert!(signed(&minus(&(i128_min + 1u8)), 128).is_err());
+
+        // Decimal felts and values outside the field
+        assert_eq!(signed("3618502788666131213697322783095070105623107215331596699973092056135872020480", 16).unwrap(), -1);
+        assert!(signed(&format!("0x{}", field_prime().to_str_radix(16)), 128).is_err());
+    }
+
+    #[test]
+    fn test_byte_arrays_and_short_strings() {
+        let felts = |felts: &[&str]| felts.iter().map(|f| Value::from(*f)).collect::<Vec<_>>();
+
+        // "hello", all in the pending word
+        let hello = felts(&["0x0", "0x68656c6c6f", "0x5"]);
+        assert_eq!(read_byte_array(&mut FeltCursor::new(&hello)).unwrap(), "hello");
+
+        // 31 + 4 bytes: one full word, then the rest
+        let long = felts(&["0x1", "0x4c6f72656d20697073756d20646f6c6f722073697420616d65742c20636f6e", "0x73656374", "0x4", "0x9"]);
+        let mut cursor = FeltCursor::new(&long);
+        assert_eq!(read_byte_array(&mut cursor).unwrap(), "Lorem ipsum dolor sit amet, consect");
+        assert_eq!(cursor.remaining(), 1);
+
+        // Leading zero bytes are part of the text, not padding
+        let zeros = felts(&["0x0", "0x41", "0x3"]);
+        assert_eq!(read_byte_array(&mut FeltCursor::new(&zeros)).unwrap(), "\0\0A");
+        assert_eq!(read_byte_array(&mut FeltCursor::new(&felts(&["0x0", "0x0", "0x0"]))).unwrap(), "");
+        assert_eq!(read_byte_array(&mut FeltCursor::new(&felts(&["0x0", "0xff", "0x1"]))).unwrap(), "0xff");
+
+        assert!(read_byte_array(&mut FeltCursor::new(&felts(&["0x0", "0x4142", "0x1"]))).is_err());
+        assert!(read_byte_array(&mut FeltCursor::new(&felts(&["0x0", "0x41", "0x1f"]))).is_err());
+        assert!(read_byte_array(&mut FeltCursor::new(&felts(&["0x2", "0x41", "0x0"]))).is_err());
+        // A word count near usize::MAX must not overflow
+        assert!(read_byte_array(&mut FeltCursor::new(&felts(&["0xffffffffffffffff", "0x0", "0x0"]))).is_err());
+
+        assert_eq!(short_string("0x4142").unwrap(), "AB");
+        assert_eq!(short_string("0x0").unwrap(), "");
+        assert!(short_string("0x800000000000011000000000000000000000000000000000000000000000000").is_err());
+        assert_eq!(guess_short_string("0x746573745f66656c74323532").as_deref(), Some("test_felt252"));
+        assert_eq!(guess_short_string("0x41"), None);
+        assert_eq!(guess_short_string("0x1234"), None);
+    }
+
+    #[test]
+    fn test_type_names_are_split() {
+        assert_eq!(array_element("core::array::Span::<core::array::Array::<core::felt252>>"), Some("core::array::Array::<core::felt252>"));
+        assert_eq!(array_element("core::felt252"), None);
+        assert_eq!(
+            tuple_members("(core::felt252, (core::bool, core::integer::u8), core::array::Array::<(u8, u8)>)").unwrap(),
+            vec!["core::felt252", "(core::bool, core::integer::u8)", "core::array::Array::<(u8, u8)>"]
+        );
+        assert_eq!(tuple_members("()").unwrap(), Vec::<&str>::new());
+        assert_eq!(named_member("low : felt"), (Some("low"), "felt"));
+        assert_eq!(named_member("core::felt252"), (None, "core::felt252"));
+        assert_eq!(named_member("(x: felt, y: felt)"), (None, "(x: felt, y: felt)"));
+    }
+}
@@ -0,0 +1,227 @@
+use crate::database::Database;
+use crate::networks::{adopted_chain_key, chain_id_from_felt, is_development_network};
+use crate::starknet::{get_block_hash, get_chain_id, get_current_block_number, RpcContext};
+
+/// Metadata key holding the chain id the database was first indexed from
+const DATABASE_CHAIN_ID_KEY: &str = "chain_id";
+
+/// Metadata key prefix of the chain id a development network's node reported on first contact,
+/// `network_chain_id:<network>`
+const NETWORK_CHAIN_ID_KEY: &str = "network_chain_id";
+
+/// Distance behind the head at which checkpoints are taken, so normal reorgs don't look like restarts
+const CHECKPOINT_DEPTH: u64 = 64;
+
+/// Recent checkpoints kept per network, besides genesis
+const CHECKPOINTS_KEPT: i64 = 5;
+
+#[derive(Clone, Copy, Default)]
+pub struct ChainGuardOptions {
+    pub allow_chain_mismatch: bool, // Continue even if the node is on another chain than expected
+    pub reset_on_devnet_restart: bool, // Drop data of a devnet/local chain that was restarted from genesis
+}
+
+/// Check that the node behind `rpc` serves the chain this database (and network) expects.
+///
+/// With `database_chain` set the live chain id is also compared with, or recorded as, the chain the
+/// database was created for. Known block hashes are re-checked to detect a restarted devnet. A
+/// development network without a configured chain id takes the node's, and `rpc.chain_id` is set to
+/// the key its rows are stored under.
+pub async fn verify_chain(
+    database: &Database,
+    rpc: &mut RpcContext,
+    options: ChainGuardOptions,
+    database_chain: bool,
+) -> Result<(), String> {
+    let live_chain_id = chain_id_from_felt(&get_chain_id(rpc).await?);
+
+    let adopting = rpc.networks.get(&rpc.network).is_some_and(|network| network.chain_id.is_empty());
+    let expected_chain_id = if adopting {
+        adopt_chain_id(database, rpc, &live_chain_id, options).await?
+    } else {
+        rpc.chain_id.clone()
+    };
+
+    if live_chain_id != expected_chain_id {
+        let mut message = format!(
+            "RPC {} serves chain {} but network '{}' expects {}",
+            rpc.rpc_url, live_chain_id, rpc.network, expected_chain_id
+        );
+        if adopting {
+            message.push_str(" (recorded on first contact; use --reset-on-devnet-restart to reindex it from the new chain)");
+        } else if is_development_network(&rpc.network) {
+            message.push_str(&format!(
+                " (start the node with --chain-id {} or set {}_CHAIN_ID)",
+                expected_chain_id, rpc.network.to_uppercase()
+            ));
+        }
+        if !options.allow_chain_mismatch {
+            return Err(format!("{} (use --allow-chain-mismatch to continue anyway)", message));
+        }
+        eprintln!("⚠️  {}", message);
+    }
+
+    if database_chain {
+        let stored_chain_id = database.get_metadata(DATABASE_CHAIN_ID_KEY).await
+            .map_err(|e| format!("Failed to read database chain id: {}", e))?;
+
+        match stored_chain_id {
+            Some(stored) if stored != live_chain_id => {
+                let message = format!("Database was indexed from chain {} but the RPC serves {}", stored, live_chain_id);
+                if !options.allow_chain_mismatch {
+                    return Err(format!("{} (use --allow-chain-mismatch to continue anyway)", message));
+                }
+                eprintln!("⚠️  {} - continuing because of --allow-chain-mismatch", message);
+            }
+            Some(_) => {}
+            None => {
+                database.set_metadata(DATABASE_CHAIN_ID_KEY, &live_chain_id).await
+                    .map_err(|e| format!("Failed to store database chain id: {}", e))?;
+                println!("🔗 Recorded chain id {} for this database", live_chain_id);
+            }
+        }
+    }
+
+    verify_checkpoints(database, rpc, options).await
+}
+
+/// Chain id a development network without a configured one expects: the one its node reported on first
+/// contact, recorded per network. A node that reports another chain later was replaced; with
+/// `reset_on_devnet_restart` the network's data is dropped and the new chain id taken.
+async fn adopt_chain_id(
+    database: &Database,
+    rpc: &mut RpcContext,
+    live_chain_id: &str,
+    options: ChainGuardOptions,
+) -> Result<String, String> {
+    let key = format!("{}:{}", NETWORK_CHAIN_ID_KEY, rpc.network);
+    let stored = database.get_metadata(&key).await
+        .map_err(|e| format!("Failed to read chain id of {}: {}", rpc.network, e))?;
+
+    let chain_id = match stored {
+        Some(stored) if stored != live_chain_id && options.reset_on_devnet_restart => {
+            println!("♻️  {} now serves chain {} instead of {} - resetting its indexed data", rpc.network, live_chain_id, stored);
+            database.reset_network_data(&rpc.network, &adopted_chain_key(&rpc.network, &stored)).await
+                .map_err(|e| format!("Failed to reset data of {}: {}", rpc.network, e))?;
+            None
+        }
+        stored => stored,
+    };
+    let chain_id = match chain_id {
+        Some(chain_id) => chain_id,
+        None => {
+            database.set_metadata(&key, live_chain_id).await
+                .map_err(|e| format!("Failed to store chain id of {}: {}", rpc.network, e))?;
+            println!("🔗 Recorded chain id {} for network {}", live_chain_id, rpc.network);
+            live_chain_id.to_string()
+        }
+    };
+
+    let chain_key = adopted_chain_key(&rpc.network, &chain_id);
+    rpc.networks.adopt_chain_id(&rpc.network, &chain_key);
+    rpc.chain_id = chain_key;
+    Ok(chain_id)
+}
+
+/// Block hashes are checkpointed per network, and a reset drops the rows of the network's chain id
+async fn verify_checkpoints(
+    database: &Database,
+    rpc: &RpcContext,
+    options: ChainGuardOptions,
+) -> Result<(), String> {
+    let checkpoints = database.get_network_checkpoints(&rpc.network).await
+        .map_err(|e| format!("Failed to read chain checkpoints: {}", e))?;
+
+    for (block_number, known_hash) in &checkpoints {
+        let live_hash = get_block_hash(rpc, *block_number).await?;
+        if live_hash.as_deref().map(Database::normalize_address) == Some(Database::normalize_address(known_hash)) {
+            continue;
+        }
+
+        let message = format!(
+            "Block {} of {} changed from {} to {}",
+            block_number, rpc.network, known_hash, live_hash.as_deref().unwrap_or("<missing>")
+        );
+
+        if !is_development_network(&rpc.network) {
+            return Err(format!("{} - refusing to index on top of a different history", message));
+        }
+        if !options.reset_on_devnet_restart {
+            return Err(format!("{} - the {} was restarted; use --reset-on-devnet-restart to drop its data and reindex", message, rpc.network));
+        }
+
+        if let Some(other) = rpc.networks.networks().find(|n| n.name != rpc.network && n.chain_id == rpc.chain_id) {
+            return Err(format!(
+                "{} - not resetting because network '{}' shares chain id {}; give the {} a chain id of its own",
+                message, other.name, rpc.chain_id, rpc.network
+            ));
+        }
+
+        println!("♻️  {} - {} restarted, resetting its indexed data", message, rpc.network);
+        database.reset_network_data(&rpc.network, &rpc.chain_id).await
+            .map_err(|e| format!("Failed to reset data of {}: {}", rpc.network, e))?;
+        break;
+    }
+
+    record_checkpoints(database, rpc).await
+}
+
+async fn record_checkpoints(database: &Database, rpc: &RpcContext) -> Result<(), String> {
+    let head = get_current_block_number(rpc).await?;
+
+    for block_number in [0, head.saturating_sub(CHECKPOINT_DEPTH)] {
+        if let Some(hash) = get_block_hash(rpc, block_number).await? {
+            database.add_network_checkpoint(&rpc.network, block_number, &hash, CHECKPOINTS_KEPT).await
+                .map_err(|e| format!("Failed to record checkpoint: {}", e))?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::networks::NetworkRegistry;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_devnet_takes_the_chain_id_its_node_reports() {
+        let scenario = serde_yaml::from_str(include_str!("../tests/fixtures/mock_rpc/scenario.yaml")).unwrap();
+        let (addr, _chain) = crate::mock_rpc::serve(scenario, "127.0.0.1:0".parse().unwrap()).await.unwrap();
+        let config = format!(r#"{{"devnet": {{"rpcUrls": ["http://{}"]}}, "testnet": {{"chainId": "SN_SEPOLIA", "rpcUrls": ["http://{}"]}}}}"#, addr, addr);
+        let networks = Arc::new(NetworkRegistry::with_networks(NetworkRegistry::parse_config(&config).unwrap(), "devnet"));
+
+        let path = std::env::temp_dir().join(format!("chain_guard_{}.db", uuid::Uuid::new_v4()));
+        let database = Database::new(&format!("sqlite:{}", path.to_string_lossy())).await.unwrap();
+
+        // The mock reports SN_SEPOLIA like a stock starknet-devnet
+        let mut rpc = RpcContext::new(networks.clone()).unwrap();
+        assert_eq!(rpc.chain_id, "");
+        verify_chain(&database, &mut rpc, ChainGuardOptions::default(), true).await.unwrap();
+        assert_eq!(rpc.chain_id, "devnet:SN_SEPOLIA");
+        assert_eq!(rpc.for_network("devnet").unwrap().chain_id, "devnet:SN_SEPOLIA");
+        assert_eq!(database.get_metadata("network_chain_id:devnet").await.unwrap().as_deref(), Some("SN_SEPOLIA"));
+
+        // Testnet keeps its own rows
+        let mut testnet = rpc.for_network("testnet").unwrap();
+        verify_chain(&database, &mut testnet, ChainGuardOptions::default(), false).await.unwrap();
+        assert_eq!(testnet.chain_id, "SN_SEPOLIA");
+
+        // A node that now reports another chain is refused unless the devnet may be reset
+        database.set_metadata("network_chain_id:devnet", "KATANA").await.unwrap();
+        database.update_chain_indexer_state("devnet:KATANA", "0xabc", 10).await.unwrap();
+        database.update_chain_indexer_state("SN_SEPOLIA", "0xabc", 20).await.unwrap();
+        let mut rpc = RpcContext::new(networks.clone()).unwrap();
+        let error = verify_chain(&database, &mut rpc, ChainGuardOptions::default(), false).await.unwrap_err();
+        assert!(error.contains("expects KATANA"), "{}", error);
+
+        let options = ChainGuardOptions { reset_on_devnet_restart: true, ..Default::default() };
+        verify_chain(&database, &mut rpc, options, false).await.unwrap();
+        assert_eq!(rpc.chain_id, "devnet:SN_SEPOLIA");
+        assert!(database.get_chain_indexer_state("devnet:KATANA", "0xabc").await.unwrap().is_none());
+        assert_eq!(database.get_chain_indexer_state("SN_SEPOLIA", "0xabc").await.unwrap().unwrap().last_synced_block, 20);
+
+        let _ = std::fs::remove_file(path);
+    }
+}
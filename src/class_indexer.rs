@@ -0,0 +1,323 @@
+use crate::database::{Database, ClassDeclarationRecord, ContractDeploymentRecord, DeploymentContract, DeploymentsRevision};
+use crate::starknet::{get_block_with_receipts, get_current_block_number, get_state_update, sn_keccak, RpcContext};
+use chrono::{DateTime, TimeZone, Utc};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use tokio::time::{sleep, Duration};
+
+/// Cursor key used in `indexer_state` for this stream
+const CLASS_WATCH_CURSOR: &str = "class_watch";
+
+/// Deployment metadata key listing class hashes whose new instances are added to the deployment
+const AUTO_REGISTER_METADATA_KEY: &str = "autoRegisterClassHashes";
+
+#[derive(Clone, Default)]
+pub struct ClassWatchConfig {
+    pub class_hashes: Vec<String>, // Record deployments of these classes
+    pub declarers: Vec<String>,    // Record declarations sent by these accounts
+    pub start_block: Option<u64>,
+    pub sync_interval: u64,
+}
+
+impl ClassWatchConfig {
+    pub fn is_enabled(&self) -> bool {
+        !self.class_hashes.is_empty() || !self.declarers.is_empty()
+    }
+}
+
+/// Class hash -> deployment ids that asked to auto-register its instances
+type AutoRegisterRules = HashMap<String, Vec<String>>;
+
+/// Follows DEPLOY_ACCOUNT transactions, deploy syscalls and DECLARE transactions block by block
+pub struct ClassIndexer {
+    database: Arc<Database>,
+    rpc: RpcContext,
+    config: ClassWatchConfig,
+    auto_register: Mutex<Option<(DeploymentsRevision, Arc<AutoRegisterRules>)>>, // Reloaded when deployments change
+}
+
+impl ClassIndexer {
+    pub fn new(database: Arc<Database>, rpc: RpcContext, config: ClassWatchConfig) -> Self {
+        Self { database, rpc, config, auto_register: Mutex::new(None) }
+    }
+
+    pub async fn start_syncing(&self) {
+        println!("🚀 Starting class watcher for {} class hashes and {} declarers",
+                self.config.class_hashes.len(), self.config.declarers.len());
+
+        loop {
+            match self.sync_to_head().await {
+                Ok(blocks) if blocks > 0 => println!("   📦 Class watcher processed {} blocks", blocks),
+                Ok(_) => {}
+                Err(e) => eprintln!("❌ Error in class watcher: {}", e),
+            }
+
+            sleep(Duration::from_secs(self.config.sync_interval)).await;
+        }
+    }
+
+    async fn sync_to_head(&self) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        let current_block = get_current_block_number(&self.rpc).await
+            .map_err(|e| format!("Failed to get current block: {}", e))?;
+
+        let next_block = match self.database.get_chain_indexer_state(&self.rpc.chain_id, CLASS_WATCH_CURSOR).await? {
+            Some(state) => state.last_synced_block + 1,
+            // Without a configured start block only new deployments are followed
+            None => self.config.start_block.unwrap_or(current_block),
+        };
+
+        if next_block > current_block {
+            return Ok(0);
+        }
+
+        let auto_register = self.auto_register_rules().await?;
+        for block_number in next_block..=current_block {
+            self.sync_block(block_number, &auto_register).await?;
+            self.database.update_chain_indexer_state(&self.rpc.chain_id, CLASS_WATCH_CURSOR, block_number).await?;
+        }
+
+        Ok(current_block - next_block + 1)
+    }
+
+    async fn sync_block(&self, block_number: u64, auto_register: &AutoRegisterRules) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let block = get_block_with_receipts(&self.rpc, block_number).await
+            .map_err(|e| format!("Failed to get block {}: {}", block_number, e))?;
+
+        let declarers: HashSet<String> = self.config.declarers.iter().map(|a| Database::normalize_address(a)).collect();
+        let declarations: Vec<ClassDeclarationRecord> = extract_declarations(&block, &self.rpc.chain_id)
+            .into_iter()
+            .filter(|d| declarers.contains(&d.sender_address))
+            .collect();
+
+        let mut class_hashes: HashSet<String> = self.config.class_hashes.iter().map(|h| Database::normalize_address(h)).collect();
+        class_hashes.extend(auto_register.keys().cloned());
+
+        let state_update = get_state_update(&self.rpc, block_number).await
+            .map_err(|e| format!("Failed to get state update {}: {}", block_number, e))?;
+        let deployments: Vec<ContractDeploymentRecord> = extract_deployments(&state_update, &block, &self.rpc.chain_id)
+            .into_iter()
+            .filter(|d| class_hashes.contains(&d.class_hash))
+            .collect();
+
+        for declaration in &declarations {
+            println!("📜 Class {} declared by {} in block {}", declaration.class_hash, declaration.sender_address, block_number);
+        }
+        for deployment in &deployments {
+            println!("🏗️  Contract {} deployed from class {} in block {}", deployment.contract_address, deployment.class_hash, block_number);
+        }
+
+        self.database.insert_class_declarations(&declarations).await?;
+        self.database.insert_contract_deployments(&deployments).await?;
+
+        for deployment in &deployments {
+            if let Some(deployment_ids) = auto_register.get(&deployment.class_hash) {
+                for deployment_id in deployment_ids {
+                    self.register_into_deployment(deployment_id, deployment).await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The auto-register rules of the active deployments on this network, read again only once deployments have changed
+    async fn auto_register_rules(&self) -> Result<Arc<AutoRegisterRules>, sqlx::Error> {
+        let revision = self.database.get_deployments_revision().await?;
+        if let Some((cached_revision, rules)) = self.auto_register.lock().unwrap().as_ref() {
+            if *cached_revision == revision {
+                return Ok(rules.clone());
+            }
+        }
+
+        let rules = Arc::new(self.load_auto_register_rules().await?);
+        *self.auto_register.lock().unwrap() = Some((revision, rules.clone()));
+        Ok(rules)
+    }
+
+    async fn load_auto_register_rules(&self) -> Result<AutoRegisterRules, sqlx::Error> {
+        let mut rules = AutoRegisterRules::new();
+
+        for deployment in self.database.get_deployments(Some("active"), Some(&self.rpc.network), 1000, 0).await? {
+            let metadata: Value = deployment.metadata.as_deref()
+                .and_then(|m| serde_json::from_str(m).ok())
+                .unwrap_or_default();
+            if let Some(hashes) = metadata.get(AUTO_REGISTER_METADATA_KEY).and_then(|v| v.as_array()) {
+                for hash in hashes.iter().filter_map(|h| h.as_str()) {
+                    rules.entry(Database::normalize_address(hash)).or_default().push(deployment.id.clone());
+                }
+            }
+        }
+
+        Ok(rules)
+    }
+
+    async fn register_into_deployment(&self, deployment_id: &str, deployment: &ContractDeploymentRecord) -> Result<(), sqlx::Error> {
+        let existing = self.database.get_deployment_contracts(deployment_id).await?;
+        if existing.iter().any(|c| Database::normalize_address(&c.contract_address) == deployment.contract_address) {
+            return Ok(());
+        }
+
+        let now = Utc::now();
+        self.database.create_deployment_contract(&DeploymentContract {
+            id: uuid::Uuid::new_v4().to_string(),
+            deployment_id: deployment_id.to_string(),
+            contract_address: deployment.contract_address.clone(),
+            name: None,
+            description: Some(format!("Auto-registered instance of class {}", deployment.class_hash)),
+            start_block: Some(deployment.block_number),
+            status: "active".to_string(),
+            created_at: now,
+            updated_at: now,
+            metadata: None,
+        }).await?;
+
+        println!("🔗 Auto-registered {} into deployment {}", deployment.contract_address, deployment_id);
+        Ok(())
+    }
+}
+
+pub fn block_timestamp(block: &Value) -> DateTime<Utc> {
+    block.get("timestamp")
+        .and_then(|t| t.as_i64())
+        .and_then(|t| Utc.timestamp_opt(t, 0).single())
+        .unwrap_or_else(Utc::now)
+}
+
+fn str_field(value: &Value, name: &str) -> Option<String> {
+    value.get(name).and_then(|v| v.as_str()).map(|s| s.to_string())
+}
+
+/// DECLARE transactions of a `starknet_getBlockWithReceipts` result of chain `chain_id`
+pub fn extract_declarations(block: &Value, chain_id: &str) -> Vec<ClassDeclarationRecord> {
+    let block_number = block.get("block_number").and_then(|v| v.as_u64()).unwrap_or_default();
+    let timestamp = block_timestamp(block);
+
+    block.get("transactions")
+        .and_then(|v| v.as_array())
+        .map(|txs| txs.iter().filter_map(|entry| {
+            let tx = entry.get("transaction")?;
+            if tx.get("type")?.as_str()? != "DECLARE" {
+                return None;
+            }
+            Some(ClassDeclarationRecord {
+                class_hash: Database::normalize_address(&str_field(tx, "class_hash")?),
+                compiled_class_hash: str_field(tx, "compiled_class_hash").map(|h| Database::normalize_address(&h)),
+                sender_address: Database::normalize_address(&str_field(tx, "sender_address")?),
+                transaction_hash: str_field(entry.get("receipt")?, "transaction_hash")
+                    .or_else(|| str_field(tx, "transaction_hash"))?,
+                block_number,
+                timestamp,
+                chain_id: chain_id.to_string(),
+            })
+        }).collect())
+        .unwrap_or_default()
+}
+
+/// New contracts from a state diff, attributed to DEPLOY_ACCOUNT transactions or UDC
+/// `ContractDeployed` events of the same block where possible.
+pub fn extract_deployments(state_update: &Value, block: &Value, chain_id: &str) -> Vec<ContractDeploymentRecord> {
+    let block_number = block.get("block_number").and_then(|v| v.as_u64()).unwrap_or_default();
+    let timestamp = block_timestamp(block);
+    let contract_deployed_selector = sn_keccak("ContractDeployed");
+
+    // contract address -> (deployment type, deployer, transaction hash)
+    let mut origins: HashMap<String, (&str, Option<String>, String)> = HashMap::new();
+    for entry in block.get("transactions").and_then(|v| v.as_array()).into_iter().flatten() {
+        let Some(receipt) = entry.get("receipt") else { continue };
+        let tx_hash = str_field(receipt, "transaction_hash").unwrap_or_default();
+
+        if receipt.get("type").and_then(|t| t.as_str()) == Some("DEPLOY_ACCOUNT") {
+            if let Some(address) = str_field(receipt, "contract_address") {
+                let address = Database::normalize_address(&address);
+                origins.insert(address.clone(), ("DEPLOY_ACCOUNT", Some(address), tx_hash.clone()));
+            }
+        }
+
+        for event in receipt.get("events").and_then(|v| v.as_array()).into_iter().flatten() {
+            let is_contract_deployed = event.get("keys")
+                .and_then(|k| k.get(0))
+                .and_then(|k| k.as_str())
+                .map(|k| Database::normalize_address(k) == contract_deployed_selector)
+                .unwrap_or(false);
+            let data = event.get("data").and_then(|d| d.as_array());
+            if let (true, Some(data)) = (is_contract_deployed, data) {
+                if let (Some(address), Some(deployer)) = (data.first().and_then(|v| v.as_str()), data.get(1).and_then(|v| v.as_str())) {
+                    origins.insert(
+                        Database::normalize_address(address),
+                        ("DEPLOY_SYSCALL", Some(Database::normalize_address(deployer)), tx_hash.clone()),
+                    );
+                }
+            }
+        }
+    }
+
+    state_update.get("state_diff")
+        .and_then(|d| d.get("deployed_contracts"))
+        .and_then(|v| v.as_array())
+        .map(|deployed| deployed.iter().filter_map(|contract| {
+            let contract_address = Database::normalize_address(&str_field(contract, "address")?);
+            let class_hash = Database::normalize_address(&str_field(contract, "class_hash")?);
+            let (deployment_type, deployer_address, transaction_hash) = match origins.get(&contract_address) {
+                Some((kind, deployer, tx_hash)) => (kind.to_string(), deployer.clone(), Some(tx_hash.clone())),
+                None => ("DEPLOY_SYSCALL".to_string(), None, None),
+            };
+            Some(ContractDeploymentRecord {
+                contract_address,
+                class_hash,
+                deployer_address,
+                transaction_hash,
+                deployment_type,
+                block_number,
+                timestamp,
+                chain_id: chain_id.to_string(),
+            })
+        }).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> Value {
+        let raw = match name {
+            "block" => include_str!("../tests/fixtures/classes/block_with_receipts.json"),
+            _ => include_str!("../tests/fixtures/classes/state_update.json"),
+        };
+        serde_json::from_str::<Value>(raw).unwrap()["result"].clone()
+    }
+
+    #[test]
+    fn test_declarations_are_extracted() {
+        let declarations = extract_declarations(&fixture("block"), "SN_MAIN");
+
+        assert_eq!(declarations.len(), 1);
+        assert_eq!(declarations[0].sender_address, Database::normalize_address("0x6b1f2c"));
+        assert_eq!(declarations[0].block_number, 812345);
+        assert!(declarations[0].compiled_class_hash.is_some());
+    }
+
+    #[test]
+    fn test_deployments_are_attributed() {
+        let deployments = extract_deployments(&fixture("state_update"), &fixture("block"), "SN_MAIN");
+        let by_address = |address: &str| deployments.iter()
+            .find(|d| d.contract_address == Database::normalize_address(address))
+            .unwrap();
+
+        assert_eq!(deployments.len(), 3);
+
+        let account = by_address("0x51a7c0");
+        assert_eq!(account.deployment_type, "DEPLOY_ACCOUNT");
+        assert_eq!(account.deployer_address, Some(account.contract_address.clone()));
+
+        let udc = by_address("0x7e11d0");
+        assert_eq!(udc.deployment_type, "DEPLOY_SYSCALL");
+        assert_eq!(udc.deployer_address, Some(Database::normalize_address("0x6b1f2c")));
+        assert_eq!(udc.class_hash, Database::normalize_address("0x2e8a3b"));
+
+        let factory = by_address("0x3c90f1");
+        assert_eq!(factory.deployer_address, None);
+        assert_eq!(factory.transaction_hash, None);
+    }
+}
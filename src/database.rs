@@ -14,6 +14,92 @@ pub struct EventRecord {
     pub decoded_data: Option<String>,
     pub raw_data: String,
     pub raw_keys: String,
+    pub chain_id: Option<String>, // None for rows indexed before chain ids were recorded
+}
+
+#[derive(Debug, Clone)]
+pub struct InternalCallRecord {
+    pub id: String,
+    pub contract_address: String,
+    pub caller_address: String,
+    pub block_number: u64,
+    pub transaction_hash: String,
+    pub call_index: i32,
+    pub depth: i32,
+    pub phase: String, // "validate", "execute", "constructor", "l1_handler", "fee_transfer"
+    pub call_type: String,
+    pub entry_point_type: String,
+    pub entry_point_selector: String,
+    pub function_name: Option<String>,
+    pub calldata: String,
+    pub result: String,
+    pub decoded_inputs: Option<String>,
+    pub decoded_outputs: Option<String>,
+    pub chain_id: Option<String>, // None for rows indexed before chain ids were recorded
+}
+
+#[derive(Debug, Clone)]
+pub struct AccountEventRecord {
+    pub id: String, // "<tx_hash>:<content hash>", shared by every watched account the event mentions
+    pub account_address: String,
+    pub key_position: i32, // Index in `keys` where the account appeared
+    pub contract_address: String,
+    pub event_type: String,
+    pub event_selector: String,
+    pub block_number: u64,
+    pub transaction_hash: String,
+    pub timestamp: DateTime<Utc>,
+    pub decoded_data: Option<String>,
+    pub raw_data: String,
+    pub raw_keys: String,
+    pub chain_id: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct MessageRecord {
+    pub id: String,
+    pub contract_address: String,
+    pub direction: String, // "L2_TO_L1" (messages_sent), "L1_TO_L2" (L1 handler transaction)
+    pub from_address: String,
+    pub to_address: String,
+    pub payload: String, // JSON array of felts
+    pub message_hash: Option<String>,
+    pub nonce: Option<String>, // L1_TO_L2 only
+    pub entry_point_selector: Option<String>, // L1_TO_L2 only
+    pub transaction_hash: String,
+    pub block_number: u64,
+    pub timestamp: DateTime<Utc>,
+    pub chain_id: Option<String>, // None for rows indexed before chain ids were recorded
+}
+
+#[derive(Debug, Clone)]
+pub struct ContractDeploymentRecord {
+    pub contract_address: String,
+    pub class_hash: String,
+    pub deployer_address: Option<String>, // Unknown for syscall deployments outside the UDC
+    pub transaction_hash: Option<String>,
+    pub deployment_type: String, // "DEPLOY_ACCOUNT", "DEPLOY_SYSCALL"
+    pub block_number: u64,
+    pub timestamp: DateTime<Utc>,
+    pub chain_id: String, // Empty for rows indexed before chain ids were recorded
+}
+
+/// Deployment count and latest update, compared to tell whether cached deployment data is stale
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeploymentsRevision {
+    pub count: i64,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ClassDeclarationRecord {
+    pub class_hash: String,
+    pub compiled_class_hash: Option<String>, // None for Cairo 0 classes
+    pub sender_address: String,
+    pub transaction_hash: String,
+    pub block_number: u64,
+    pub timestamp: DateTime<Utc>,
+    pub chain_id: String, // Empty for rows indexed before chain ids were recorded
 }
 
 #[derive(Debug, Clone)]
@@ -21,6 +107,8 @@ pub struct IndexerState {
     #[allow(dead_code)]
     pub id: i32,
     #[allow(dead_code)] 
+    pub chain_id: String, // Empty for cursors written before chain ids were recorded
+    #[allow(dead_code)] 
     pub contract_address: String,
     pub last_synced_block: u64,
     pub updated_at: DateTime<Utc>,
@@ -54,6 +142,19 @@ pub struct DeploymentContract {
     pub metadata: Option<String>, // JSON metadata for contract-specific config
 }
 
+/// ABI to decode a contract with instead of its class ABI, given as JSON or as a class hash
+#[derive(Debug, Clone, PartialEq)]
+pub struct AbiOverrideRecord {
+    pub id: String,
+    pub deployment_id: Option<String>, // None applies to every indexer, e.g. overrides given on the command line
+    pub contract_address: String,
+    pub abi: Option<String>,
+    pub class_hash: Option<String>,
+    pub from_block: Option<u64>,
+    pub to_block: Option<u64>, // Inclusive
+    pub created_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone)]
 pub struct ApiCallRecord {
     pub id: String,
@@ -131,6 +232,104 @@ impl Database {
         format!("0x{}", padded)
     }
 
+    /// Same addresses and class hashes exist on several networks, so rows are keyed by chain id too
+    const CONTRACT_DEPLOYMENTS_TABLE: &'static str = r#"
+        CREATE TABLE IF NOT EXISTS contract_deployments (
+            chain_id TEXT NOT NULL DEFAULT '',
+            contract_address TEXT NOT NULL,
+            class_hash TEXT NOT NULL,
+            deployer_address TEXT,
+            transaction_hash TEXT,
+            deployment_type TEXT NOT NULL,
+            block_number INTEGER NOT NULL,
+            timestamp TEXT NOT NULL,
+            PRIMARY KEY (chain_id, contract_address)
+        )
+    "#;
+
+    const CLASS_DECLARATIONS_TABLE: &'static str = r#"
+        CREATE TABLE IF NOT EXISTS class_declarations (
+            chain_id TEXT NOT NULL DEFAULT '',
+            class_hash TEXT NOT NULL,
+            compiled_class_hash TEXT,
+            sender_address TEXT NOT NULL,
+            transaction_hash TEXT NOT NULL,
+            block_number INTEGER NOT NULL,
+            timestamp TEXT NOT NULL,
+            PRIMARY KEY (chain_id, class_hash)
+        )
+    "#;
+
+    async fn has_column(pool: &SqlitePool, table: &str, column: &str) -> Result<bool, sqlx::Error> {
+        let rows = sqlx::query(&format!("PRAGMA table_info({})", table))
+            .fetch_all(pool)
+            .await?;
+        Ok(rows.iter().any(|row| row.get::<String, _>("name") == column))
+    }
+
+    /// Databases created before multi-network support have no chain id on indexed rows and a cursor per
+    /// contract address only. Existing cursors keep an empty chain id until an indexer claims them.
+    async fn migrate_chain_ids(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+        for table in ["events", "internal_calls", "messages"] {
+            if !Self::has_column(pool, table, "chain_id").await? {
+                sqlx::query(&format!("ALTER TABLE {} ADD COLUMN chain_id TEXT", table))
+                    .execute(pool).await?;
+            }
+        }
+
+        for (table, create, columns) in [
+            ("contract_deployments", Self::CONTRACT_DEPLOYMENTS_TABLE,
+             "contract_address, class_hash, deployer_address, transaction_hash, deployment_type, block_number, timestamp"),
+            ("class_declarations", Self::CLASS_DECLARATIONS_TABLE,
+             "class_hash, compiled_class_hash, sender_address, transaction_hash, block_number, timestamp"),
+        ] {
+            if Self::has_column(pool, table, "chain_id").await? {
+                continue;
+            }
+            let mut tx = pool.begin().await?;
+            sqlx::query(&format!("ALTER TABLE {0} RENAME TO {0}_legacy", table))
+                .execute(&mut *tx).await?;
+            sqlx::query(create).execute(&mut *tx).await?;
+            sqlx::query(&format!("INSERT INTO {0} (chain_id, {1}) SELECT '', {1} FROM {0}_legacy", table, columns))
+                .execute(&mut *tx).await?;
+            sqlx::query(&format!("DROP TABLE {}_legacy", table))
+                .execute(&mut *tx).await?;
+            tx.commit().await?;
+        }
+
+        if !Self::has_column(pool, "indexer_state", "chain_id").await? {
+            let mut tx = pool.begin().await?;
+            sqlx::query("ALTER TABLE indexer_state RENAME TO indexer_state_legacy")
+                .execute(&mut *tx).await?;
+            sqlx::query(
+                r#"
+                CREATE TABLE indexer_state (
+                    id INTEGER PRIMARY KEY,
+                    chain_id TEXT NOT NULL DEFAULT '',
+                    contract_address TEXT NOT NULL,
+                    last_synced_block INTEGER NOT NULL,
+                    updated_at TEXT NOT NULL,
+                    UNIQUE(chain_id, contract_address)
+                )
+                "#
+            ).execute(&mut *tx).await?;
+            sqlx::query(
+                "INSERT INTO indexer_state (id, chain_id, contract_address, last_synced_block, updated_at) \
+                 SELECT id, '', contract_address, last_synced_block, updated_at FROM indexer_state_legacy"
+            ).execute(&mut *tx).await?;
+            sqlx::query("DROP TABLE indexer_state_legacy")
+                .execute(&mut *tx).await?;
+            tx.commit().await?;
+        }
+
+        // Checkpoints used to be kept per chain id, which devnet and testnet shared; they are recorded
+        // again per network on the next start
+        sqlx::query("DROP TABLE IF EXISTS chain_checkpoints")
+            .execute(pool).await?;
+
+        Ok(())
+    }
+
     pub async fn new(database_url: &str) -> Result<Self, sqlx::Error> {
         // Parse the database URL and create connection options that will create the file if it doesn't exist
         let options = SqliteConnectOptions::from_str(database_url)?
@@ -151,7 +350,8 @@ impl Database {
                 timestamp TEXT NOT NULL,
                 decoded_data TEXT,
                 raw_data TEXT NOT NULL,
-                raw_keys TEXT NOT NULL
+                raw_keys TEXT NOT NULL,
+                chain_id TEXT
             )
             "#
         ).execute(&pool).await?;
@@ -160,13 +360,108 @@ impl Database {
             r#"
             CREATE TABLE IF NOT EXISTS indexer_state (
                 id INTEGER PRIMARY KEY,
-                contract_address TEXT UNIQUE NOT NULL,
+                chain_id TEXT NOT NULL DEFAULT '',
+                contract_address TEXT NOT NULL,
                 last_synced_block INTEGER NOT NULL,
+                updated_at TEXT NOT NULL,
+                UNIQUE(chain_id, contract_address)
+            )
+            "#
+        ).execute(&pool).await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS indexer_metadata (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL,
                 updated_at TEXT NOT NULL
             )
             "#
         ).execute(&pool).await?;
 
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS network_checkpoints (
+                network TEXT NOT NULL,
+                block_number INTEGER NOT NULL,
+                block_hash TEXT NOT NULL,
+                recorded_at TEXT NOT NULL,
+                PRIMARY KEY (network, block_number)
+            )
+            "#
+        ).execute(&pool).await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS internal_calls (
+                id TEXT PRIMARY KEY,
+                contract_address TEXT NOT NULL,
+                caller_address TEXT NOT NULL,
+                block_number INTEGER NOT NULL,
+                transaction_hash TEXT NOT NULL,
+                call_index INTEGER NOT NULL,
+                depth INTEGER NOT NULL,
+                phase TEXT NOT NULL,
+                call_type TEXT NOT NULL,
+                entry_point_type TEXT NOT NULL,
+                entry_point_selector TEXT NOT NULL,
+                function_name TEXT,
+                calldata TEXT NOT NULL,
+                result TEXT NOT NULL,
+                decoded_inputs TEXT,
+                decoded_outputs TEXT,
+                chain_id TEXT
+            )
+            "#
+        ).execute(&pool).await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS account_events (
+                id TEXT NOT NULL,
+                account_address TEXT NOT NULL,
+                key_position INTEGER NOT NULL,
+                contract_address TEXT NOT NULL,
+                event_type TEXT NOT NULL,
+                event_selector TEXT NOT NULL,
+                block_number INTEGER NOT NULL,
+                transaction_hash TEXT NOT NULL,
+                timestamp TEXT NOT NULL,
+                decoded_data TEXT,
+                raw_data TEXT NOT NULL,
+                raw_keys TEXT NOT NULL,
+                chain_id TEXT NOT NULL,
+                PRIMARY KEY (id, account_address)
+            )
+            "#
+        ).execute(&pool).await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS messages (
+                id TEXT PRIMARY KEY,
+                contract_address TEXT NOT NULL,
+                direction TEXT NOT NULL,
+                from_address TEXT NOT NULL,
+                to_address TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                message_hash TEXT,
+                nonce TEXT,
+                entry_point_selector TEXT,
+                transaction_hash TEXT NOT NULL,
+                block_number INTEGER NOT NULL,
+                timestamp TEXT NOT NULL,
+                chain_id TEXT
+            )
+            "#
+        ).execute(&pool).await?;
+
+        sqlx::query(Self::CONTRACT_DEPLOYMENTS_TABLE).execute(&pool).await?;
+
+        sqlx::query(Self::CLASS_DECLARATIONS_TABLE).execute(&pool).await?;
+
+        Self::migrate_chain_ids(&pool).await?;
+
         sqlx::query(
             r#"
             CREATE TABLE IF NOT EXISTS deployments (
@@ -202,6 +497,24 @@ impl Database {
             "#
         ).execute(&pool).await?;
 
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS abi_overrides (
+                id TEXT PRIMARY KEY,
+                deployment_id TEXT,
+                contract_address TEXT NOT NULL,
+                abi TEXT,
+                class_hash TEXT,
+                from_block INTEGER,
+                to_block INTEGER,
+                created_at TEXT NOT NULL
+            )
+            "#
+        ).execute(&pool).await?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_abi_overrides_contract ON abi_overrides(contract_address)")
+            .execute(&pool).await?;
+
         // API usage tracking tables
         sqlx::query(
             r#"
@@ -261,6 +574,33 @@ impl Database {
         
         sqlx::query("CREATE INDEX IF NOT EXISTS idx_events_timestamp ON events(timestamp)")
             .execute(&pool).await?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_events_chain_contract ON events(chain_id, contract_address)")
+            .execute(&pool).await?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_internal_calls_contract_block ON internal_calls(contract_address, block_number)")
+            .execute(&pool).await?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_internal_calls_caller ON internal_calls(caller_address)")
+            .execute(&pool).await?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_account_events_account_block ON account_events(chain_id, account_address, block_number)")
+            .execute(&pool).await?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_messages_contract_block ON messages(contract_address, block_number)")
+            .execute(&pool).await?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_messages_hash ON messages(message_hash)")
+            .execute(&pool).await?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_contract_deployments_class_hash ON contract_deployments(class_hash)")
+            .execute(&pool).await?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_contract_deployments_deployer ON contract_deployments(deployer_address)")
+            .execute(&pool).await?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_class_declarations_sender ON class_declarations(sender_address)")
+            .execute(&pool).await?;
             
         // Create indexes for deployments table
         sqlx::query("CREATE INDEX IF NOT EXISTS idx_deployments_status ON deployments(status)")
@@ -308,86 +648,675 @@ impl Database {
         sqlx::query("CREATE INDEX IF NOT EXISTS idx_contract_queries_timestamp ON contract_queries(timestamp)")
             .execute(&pool).await?;
 
-        // CPU pricing configuration tables
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS cpu_pricing_tiers (
-                id TEXT PRIMARY KEY,
-                name TEXT NOT NULL,
-                cpu_units_per_request INTEGER NOT NULL,
-                price_per_cpu_unit_usdc REAL NOT NULL,
-                minimum_charge_usdc REAL NOT NULL DEFAULT 0.001,
-                is_active BOOLEAN NOT NULL DEFAULT 1,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL
-            )
-            "#
-        ).execute(&pool).await?;
+        // CPU pricing configuration tables
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS cpu_pricing_tiers (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                cpu_units_per_request INTEGER NOT NULL,
+                price_per_cpu_unit_usdc REAL NOT NULL,
+                minimum_charge_usdc REAL NOT NULL DEFAULT 0.001,
+                is_active BOOLEAN NOT NULL DEFAULT 1,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )
+            "#
+        ).execute(&pool).await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS cpu_usage_records (
+                id TEXT PRIMARY KEY,
+                api_call_id TEXT NOT NULL,
+                cpu_units_used INTEGER NOT NULL,
+                cpu_time_ms INTEGER NOT NULL,
+                memory_usage_mb REAL NOT NULL,
+                cost_usdc REAL NOT NULL,
+                timestamp TEXT NOT NULL,
+                FOREIGN KEY (api_call_id) REFERENCES api_calls(id)
+            )
+            "#
+        ).execute(&pool).await?;
+
+        // Create indexes for CPU pricing
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_cpu_pricing_tiers_active ON cpu_pricing_tiers(is_active)")
+            .execute(&pool).await?;
+        
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_cpu_usage_records_api_call_id ON cpu_usage_records(api_call_id)")
+            .execute(&pool).await?;
+        
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_cpu_usage_records_timestamp ON cpu_usage_records(timestamp)")
+            .execute(&pool).await?;
+
+        Ok(Database { pool })
+    }
+
+    pub async fn insert_events(&self, events: &[EventRecord]) -> Result<(), sqlx::Error> {
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = self.pool.begin().await?;
+        
+        for event in events {
+            sqlx::query(
+                r#"
+                INSERT OR REPLACE INTO events 
+                (id, contract_address, event_type, block_number, transaction_hash, log_index, timestamp, decoded_data, raw_data, raw_keys, chain_id)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#
+            )
+            .bind(&event.id)
+            .bind(&event.contract_address)
+            .bind(&event.event_type)
+            .bind(event.block_number as i64)
+            .bind(&event.transaction_hash)
+            .bind(event.log_index)
+            .bind(event.timestamp.to_rfc3339())
+            .bind(&event.decoded_data)
+            .bind(&event.raw_data)
+            .bind(&event.raw_keys)
+            .bind(&event.chain_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+        
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn insert_internal_calls(&self, calls: &[InternalCallRecord]) -> Result<(), sqlx::Error> {
+        if calls.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        for call in calls {
+            sqlx::query(
+                r#"
+                INSERT OR REPLACE INTO internal_calls
+                (id, contract_address, caller_address, block_number, transaction_hash, call_index, depth, phase, call_type,
+                 entry_point_type, entry_point_selector, function_name, calldata, result, decoded_inputs, decoded_outputs, chain_id)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#
+            )
+            .bind(&call.id)
+            .bind(&call.contract_address)
+            .bind(&call.caller_address)
+            .bind(call.block_number as i64)
+            .bind(&call.transaction_hash)
+            .bind(call.call_index)
+            .bind(call.depth)
+            .bind(&call.phase)
+            .bind(&call.call_type)
+            .bind(&call.entry_point_type)
+            .bind(&call.entry_point_selector)
+            .bind(&call.function_name)
+            .bind(&call.calldata)
+            .bind(&call.result)
+            .bind(&call.decoded_inputs)
+            .bind(&call.decoded_outputs)
+            .bind(&call.chain_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn get_internal_calls(
+        &self,
+        contract_address: &str,
+        chain_id: Option<&str>,
+        caller_address: Option<&str>,
+        function_name: Option<&str>,
+        from_block: Option<u64>,
+        to_block: Option<u64>,
+        limit: i32,
+        offset: i32,
+    ) -> Result<Vec<InternalCallRecord>, sqlx::Error> {
+        let normalized_address = Self::normalize_address(contract_address);
+        let normalized_caller = caller_address.map(Self::normalize_address);
+
+        let mut query = "SELECT id, contract_address, caller_address, block_number, transaction_hash, call_index, depth, phase, call_type, \
+                         entry_point_type, entry_point_selector, function_name, calldata, result, decoded_inputs, decoded_outputs, chain_id \
+                         FROM internal_calls WHERE contract_address = ?".to_string();
+
+        if chain_id.is_some() {
+            query.push_str(" AND chain_id = ?");
+        }
+        if normalized_caller.is_some() {
+            query.push_str(" AND caller_address = ?");
+        }
+        if function_name.is_some() {
+            query.push_str(" AND function_name = ?");
+        }
+        if from_block.is_some() {
+            query.push_str(" AND block_number >= ?");
+        }
+        if to_block.is_some() {
+            query.push_str(" AND block_number <= ?");
+        }
+
+        query.push_str(" ORDER BY block_number DESC, transaction_hash, call_index LIMIT ? OFFSET ?");
+
+        let mut sql_query = sqlx::query(&query).bind(&normalized_address);
+
+        if let Some(chain_id) = chain_id {
+            sql_query = sql_query.bind(chain_id);
+        }
+        if let Some(caller) = &normalized_caller {
+            sql_query = sql_query.bind(caller);
+        }
+        if let Some(name) = function_name {
+            sql_query = sql_query.bind(name);
+        }
+        if let Some(from) = from_block {
+            sql_query = sql_query.bind(from as i64);
+        }
+        if let Some(to) = to_block {
+            sql_query = sql_query.bind(to as i64);
+        }
+
+        let rows = sql_query
+            .bind(limit as i64)
+            .bind(offset as i64)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut calls = Vec::new();
+        for row in rows {
+            calls.push(InternalCallRecord {
+                id: row.get("id"),
+                contract_address: row.get("contract_address"),
+                caller_address: row.get("caller_address"),
+                block_number: row.get::<i64, _>("block_number") as u64,
+                transaction_hash: row.get("transaction_hash"),
+                call_index: row.get("call_index"),
+                depth: row.get("depth"),
+                phase: row.get("phase"),
+                call_type: row.get("call_type"),
+                entry_point_type: row.get("entry_point_type"),
+                entry_point_selector: row.get("entry_point_selector"),
+                function_name: row.get("function_name"),
+                calldata: row.get("calldata"),
+                result: row.get("result"),
+                decoded_inputs: row.get("decoded_inputs"),
+                decoded_outputs: row.get("decoded_outputs"),
+                chain_id: row.get("chain_id"),
+            });
+        }
+
+        Ok(calls)
+    }
+
+    pub async fn insert_account_events(&self, events: &[AccountEventRecord]) -> Result<(), sqlx::Error> {
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        for event in events {
+            sqlx::query(
+                r#"
+                INSERT OR REPLACE INTO account_events
+                (id, account_address, key_position, contract_address, event_type, event_selector, block_number,
+                 transaction_hash, timestamp, decoded_data, raw_data, raw_keys, chain_id)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#
+            )
+            .bind(&event.id)
+            .bind(&event.account_address)
+            .bind(event.key_position)
+            .bind(&event.contract_address)
+            .bind(&event.event_type)
+            .bind(&event.event_selector)
+            .bind(event.block_number as i64)
+            .bind(&event.transaction_hash)
+            .bind(event.timestamp.to_rfc3339())
+            .bind(&event.decoded_data)
+            .bind(&event.raw_data)
+            .bind(&event.raw_keys)
+            .bind(&event.chain_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn get_account_events(
+        &self,
+        account_address: &str,
+        contract_address: Option<&str>,
+        event_type: Option<&str>,
+        from_block: Option<u64>,
+        to_block: Option<u64>,
+        limit: i32,
+        offset: i32,
+    ) -> Result<Vec<AccountEventRecord>, sqlx::Error> {
+        let normalized_account = Self::normalize_address(account_address);
+        let normalized_contract = contract_address.map(Self::normalize_address);
+
+        let mut query = "SELECT id, account_address, key_position, contract_address, event_type, event_selector, block_number, \
+                         transaction_hash, timestamp, decoded_data, raw_data, raw_keys, chain_id \
+                         FROM account_events WHERE account_address = ?".to_string();
+
+        if normalized_contract.is_some() {
+            query.push_str(" AND contract_address = ?");
+        }
+        if event_type.is_some() {
+            query.push_str(" AND (event_type = ? OR event_type LIKE '%::' || ?)"); // Short names match component events
+        }
+        if from_block.is_some() {
+            query.push_str(" AND block_number >= ?");
+        }
+        if to_block.is_some() {
+            query.push_str(" AND block_number <= ?");
+        }
+
+        query.push_str(" ORDER BY block_number DESC, transaction_hash, id LIMIT ? OFFSET ?");
+
+        let mut sql_query = sqlx::query(&query).bind(&normalized_account);
+
+        if let Some(contract) = &normalized_contract {
+            sql_query = sql_query.bind(contract);
+        }
+        if let Some(event_type) = event_type {
+            sql_query = sql_query.bind(event_type).bind(event_type);
+        }
+        if let Some(from) = from_block {
+            sql_query = sql_query.bind(from as i64);
+        }
+        if let Some(to) = to_block {
+            sql_query = sql_query.bind(to as i64);
+        }
+
+        let rows = sql_query
+            .bind(limit as i64)
+            .bind(offset as i64)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut events = Vec::new();
+        for row in rows {
+            events.push(AccountEventRecord {
+                id: row.get("id"),
+                account_address: row.get("account_address"),
+                key_position: row.get("key_position"),
+                contract_address: row.get("contract_address"),
+                event_type: row.get("event_type"),
+                event_selector: row.get("event_selector"),
+                block_number: row.get::<i64, _>("block_number") as u64,
+                transaction_hash: row.get("transaction_hash"),
+                timestamp: DateTime::parse_from_rfc3339(&row.get::<String, _>("timestamp"))
+                    .unwrap()
+                    .with_timezone(&Utc),
+                decoded_data: row.get("decoded_data"),
+                raw_data: row.get("raw_data"),
+                raw_keys: row.get("raw_keys"),
+                chain_id: row.get("chain_id"),
+            });
+        }
+
+        Ok(events)
+    }
+
+    pub async fn insert_messages(&self, messages: &[MessageRecord]) -> Result<(), sqlx::Error> {
+        if messages.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        for message in messages {
+            sqlx::query(
+                r#"
+                INSERT OR REPLACE INTO messages
+                (id, contract_address, direction, from_address, to_address, payload, message_hash, nonce,
+                 entry_point_selector, transaction_hash, block_number, timestamp, chain_id)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#
+            )
+            .bind(&message.id)
+            .bind(&message.contract_address)
+            .bind(&message.direction)
+            .bind(&message.from_address)
+            .bind(&message.to_address)
+            .bind(&message.payload)
+            .bind(&message.message_hash)
+            .bind(&message.nonce)
+            .bind(&message.entry_point_selector)
+            .bind(&message.transaction_hash)
+            .bind(message.block_number as i64)
+            .bind(message.timestamp.to_rfc3339())
+            .bind(&message.chain_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn get_messages(
+        &self,
+        contract_address: Option<&str>,
+        chain_id: Option<&str>,
+        direction: Option<&str>,
+        message_hash: Option<&str>,
+        from_block: Option<u64>,
+        to_block: Option<u64>,
+        limit: i32,
+        offset: i32,
+    ) -> Result<Vec<MessageRecord>, sqlx::Error> {
+        let normalized_address = contract_address.map(Self::normalize_address);
+        let normalized_hash = message_hash.map(Self::normalize_address);
+
+        let mut query = "SELECT id, contract_address, direction, from_address, to_address, payload, message_hash, nonce, \
+                         entry_point_selector, transaction_hash, block_number, timestamp, chain_id FROM messages".to_string();
+        let mut conditions = Vec::new();
+
+        if normalized_address.is_some() {
+            conditions.push("contract_address = ?");
+        }
+        if chain_id.is_some() {
+            conditions.push("chain_id = ?");
+        }
+        if direction.is_some() {
+            conditions.push("direction = ?");
+        }
+        if normalized_hash.is_some() {
+            conditions.push("message_hash = ?");
+        }
+        if from_block.is_some() {
+            conditions.push("block_number >= ?");
+        }
+        if to_block.is_some() {
+            conditions.push("block_number <= ?");
+        }
+
+        if !conditions.is_empty() {
+            query.push_str(" WHERE ");
+            query.push_str(&conditions.join(" AND "));
+        }
+
+        query.push_str(" ORDER BY block_number DESC, id LIMIT ? OFFSET ?");
+
+        let mut sql_query = sqlx::query(&query);
+
+        if let Some(address) = &normalized_address {
+            sql_query = sql_query.bind(address);
+        }
+        if let Some(chain_id) = chain_id {
+            sql_query = sql_query.bind(chain_id);
+        }
+        if let Some(direction) = direction {
+            sql_query = sql_query.bind(direction);
+        }
+        if let Some(hash) = &normalized_hash {
+            sql_query = sql_query.bind(hash);
+        }
+        if let Some(from) = from_block {
+            sql_query = sql_query.bind(from as i64);
+        }
+        if let Some(to) = to_block {
+            sql_query = sql_query.bind(to as i64);
+        }
+
+        let rows = sql_query
+            .bind(limit as i64)
+            .bind(offset as i64)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut messages = Vec::new();
+        for row in rows {
+            messages.push(MessageRecord {
+                id: row.get("id"),
+                contract_address: row.get("contract_address"),
+                direction: row.get("direction"),
+                from_address: row.get("from_address"),
+                to_address: row.get("to_address"),
+                payload: row.get("payload"),
+                message_hash: row.get("message_hash"),
+                nonce: row.get("nonce"),
+                entry_point_selector: row.get("entry_point_selector"),
+                transaction_hash: row.get("transaction_hash"),
+                block_number: row.get::<i64, _>("block_number") as u64,
+                timestamp: DateTime::parse_from_rfc3339(&row.get::<String, _>("timestamp"))
+                    .unwrap()
+                    .with_timezone(&Utc),
+                chain_id: row.get("chain_id"),
+            });
+        }
+
+        Ok(messages)
+    }
+
+    pub async fn insert_contract_deployments(&self, deployments: &[ContractDeploymentRecord]) -> Result<(), sqlx::Error> {
+        if deployments.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        for deployment in deployments {
+            sqlx::query(
+                r#"
+                INSERT OR REPLACE INTO contract_deployments
+                (chain_id, contract_address, class_hash, deployer_address, transaction_hash, deployment_type, block_number, timestamp)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                "#
+            )
+            .bind(&deployment.chain_id)
+            .bind(&deployment.contract_address)
+            .bind(&deployment.class_hash)
+            .bind(&deployment.deployer_address)
+            .bind(&deployment.transaction_hash)
+            .bind(&deployment.deployment_type)
+            .bind(deployment.block_number as i64)
+            .bind(deployment.timestamp.to_rfc3339())
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn get_contract_deployments(
+        &self,
+        chain_id: Option<&str>,
+        class_hash: Option<&str>,
+        deployer_address: Option<&str>,
+        from_block: Option<u64>,
+        to_block: Option<u64>,
+        limit: i32,
+        offset: i32,
+    ) -> Result<Vec<ContractDeploymentRecord>, sqlx::Error> {
+        let normalized_class_hash = class_hash.map(Self::normalize_address);
+        let normalized_deployer = deployer_address.map(Self::normalize_address);
+
+        let mut query = "SELECT contract_address, class_hash, deployer_address, transaction_hash, deployment_type, block_number, timestamp, chain_id FROM contract_deployments".to_string();
+        let mut conditions = Vec::new();
+
+        if chain_id.is_some() {
+            conditions.push("chain_id = ?");
+        }
+        if normalized_class_hash.is_some() {
+            conditions.push("class_hash = ?");
+        }
+        if normalized_deployer.is_some() {
+            conditions.push("deployer_address = ?");
+        }
+        if from_block.is_some() {
+            conditions.push("block_number >= ?");
+        }
+        if to_block.is_some() {
+            conditions.push("block_number <= ?");
+        }
+
+        if !conditions.is_empty() {
+            query.push_str(" WHERE ");
+            query.push_str(&conditions.join(" AND "));
+        }
 
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS cpu_usage_records (
-                id TEXT PRIMARY KEY,
-                api_call_id TEXT NOT NULL,
-                cpu_units_used INTEGER NOT NULL,
-                cpu_time_ms INTEGER NOT NULL,
-                memory_usage_mb REAL NOT NULL,
-                cost_usdc REAL NOT NULL,
-                timestamp TEXT NOT NULL,
-                FOREIGN KEY (api_call_id) REFERENCES api_calls(id)
-            )
-            "#
-        ).execute(&pool).await?;
+        query.push_str(" ORDER BY block_number DESC, contract_address LIMIT ? OFFSET ?");
 
-        // Create indexes for CPU pricing
-        sqlx::query("CREATE INDEX IF NOT EXISTS idx_cpu_pricing_tiers_active ON cpu_pricing_tiers(is_active)")
-            .execute(&pool).await?;
-        
-        sqlx::query("CREATE INDEX IF NOT EXISTS idx_cpu_usage_records_api_call_id ON cpu_usage_records(api_call_id)")
-            .execute(&pool).await?;
-        
-        sqlx::query("CREATE INDEX IF NOT EXISTS idx_cpu_usage_records_timestamp ON cpu_usage_records(timestamp)")
-            .execute(&pool).await?;
+        let mut sql_query = sqlx::query(&query);
 
-        Ok(Database { pool })
+        if let Some(chain_id) = chain_id {
+            sql_query = sql_query.bind(chain_id);
+        }
+        if let Some(hash) = &normalized_class_hash {
+            sql_query = sql_query.bind(hash);
+        }
+        if let Some(deployer) = &normalized_deployer {
+            sql_query = sql_query.bind(deployer);
+        }
+        if let Some(from) = from_block {
+            sql_query = sql_query.bind(from as i64);
+        }
+        if let Some(to) = to_block {
+            sql_query = sql_query.bind(to as i64);
+        }
+
+        let rows = sql_query
+            .bind(limit as i64)
+            .bind(offset as i64)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut deployments = Vec::new();
+        for row in rows {
+            deployments.push(ContractDeploymentRecord {
+                contract_address: row.get("contract_address"),
+                class_hash: row.get("class_hash"),
+                deployer_address: row.get("deployer_address"),
+                transaction_hash: row.get("transaction_hash"),
+                deployment_type: row.get("deployment_type"),
+                block_number: row.get::<i64, _>("block_number") as u64,
+                timestamp: DateTime::parse_from_rfc3339(&row.get::<String, _>("timestamp"))
+                    .unwrap()
+                    .with_timezone(&Utc),
+                chain_id: row.get("chain_id"),
+            });
+        }
+
+        Ok(deployments)
     }
 
-    pub async fn insert_events(&self, events: &[EventRecord]) -> Result<(), sqlx::Error> {
-        if events.is_empty() {
+    pub async fn insert_class_declarations(&self, declarations: &[ClassDeclarationRecord]) -> Result<(), sqlx::Error> {
+        if declarations.is_empty() {
             return Ok(());
         }
 
         let mut tx = self.pool.begin().await?;
-        
-        for event in events {
+
+        for declaration in declarations {
             sqlx::query(
                 r#"
-                INSERT OR REPLACE INTO events 
-                (id, contract_address, event_type, block_number, transaction_hash, log_index, timestamp, decoded_data, raw_data, raw_keys)
-                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                INSERT OR REPLACE INTO class_declarations
+                (chain_id, class_hash, compiled_class_hash, sender_address, transaction_hash, block_number, timestamp)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
                 "#
             )
-            .bind(&event.id)
-            .bind(&event.contract_address)
-            .bind(&event.event_type)
-            .bind(event.block_number as i64)
-            .bind(&event.transaction_hash)
-            .bind(event.log_index)
-            .bind(event.timestamp.to_rfc3339())
-            .bind(&event.decoded_data)
-            .bind(&event.raw_data)
-            .bind(&event.raw_keys)
+            .bind(&declaration.chain_id)
+            .bind(&declaration.class_hash)
+            .bind(&declaration.compiled_class_hash)
+            .bind(&declaration.sender_address)
+            .bind(&declaration.transaction_hash)
+            .bind(declaration.block_number as i64)
+            .bind(declaration.timestamp.to_rfc3339())
             .execute(&mut *tx)
             .await?;
         }
-        
+
         tx.commit().await?;
         Ok(())
     }
 
+    pub async fn get_class_declarations(
+        &self,
+        chain_id: Option<&str>,
+        sender_address: Option<&str>,
+        class_hash: Option<&str>,
+        limit: i32,
+        offset: i32,
+    ) -> Result<Vec<ClassDeclarationRecord>, sqlx::Error> {
+        let normalized_sender = sender_address.map(Self::normalize_address);
+        let normalized_class_hash = class_hash.map(Self::normalize_address);
+
+        let mut query = "SELECT class_hash, compiled_class_hash, sender_address, transaction_hash, block_number, timestamp, chain_id FROM class_declarations".to_string();
+        let mut conditions = Vec::new();
+
+        if chain_id.is_some() {
+            conditions.push("chain_id = ?");
+        }
+        if normalized_sender.is_some() {
+            conditions.push("sender_address = ?");
+        }
+        if normalized_class_hash.is_some() {
+            conditions.push("class_hash = ?");
+        }
+
+        if !conditions.is_empty() {
+            query.push_str(" WHERE ");
+            query.push_str(&conditions.join(" AND "));
+        }
+
+        query.push_str(" ORDER BY block_number DESC LIMIT ? OFFSET ?");
+
+        let mut sql_query = sqlx::query(&query);
+
+        if let Some(chain_id) = chain_id {
+            sql_query = sql_query.bind(chain_id);
+        }
+        if let Some(sender) = &normalized_sender {
+            sql_query = sql_query.bind(sender);
+        }
+        if let Some(hash) = &normalized_class_hash {
+            sql_query = sql_query.bind(hash);
+        }
+
+        let rows = sql_query
+            .bind(limit as i64)
+            .bind(offset as i64)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut declarations = Vec::new();
+        for row in rows {
+            declarations.push(ClassDeclarationRecord {
+                class_hash: row.get("class_hash"),
+                compiled_class_hash: row.get("compiled_class_hash"),
+                sender_address: row.get("sender_address"),
+                transaction_hash: row.get("transaction_hash"),
+                block_number: row.get::<i64, _>("block_number") as u64,
+                timestamp: DateTime::parse_from_rfc3339(&row.get::<String, _>("timestamp"))
+                    .unwrap()
+                    .with_timezone(&Utc),
+                chain_id: row.get("chain_id"),
+            });
+        }
+
+        Ok(declarations)
+    }
+
     pub async fn get_events(
         &self,
         contract_address: &str,
+        chain_id: Option<&str>,
         event_types: Option<&[String]>,
         from_block: Option<u64>,
         to_block: Option<u64>,
@@ -400,11 +1329,13 @@ impl Database {
             // No filters except contract address
             (None, None, None) => {
                 sqlx::query(
-                    "SELECT id, contract_address, event_type, block_number, transaction_hash, log_index, timestamp, decoded_data, raw_data, raw_keys 
-                     FROM events WHERE contract_address = ? 
+                    "SELECT id, contract_address, event_type, block_number, transaction_hash, log_index, timestamp, decoded_data, raw_data, raw_keys, chain_id 
+                     FROM events WHERE contract_address = ? AND (? IS NULL OR chain_id = ?) 
                      ORDER BY block_number DESC, log_index DESC LIMIT ? OFFSET ?"
                 )
                 .bind(&normalized_address)
+                .bind(chain_id)
+                .bind(chain_id)
                 .bind(limit as i64)
                 .bind(offset as i64)
                 .fetch_all(&self.pool)
@@ -413,11 +1344,13 @@ impl Database {
             // Only block range filter
             (None, Some(from), Some(to)) => {
                 sqlx::query(
-                    "SELECT id, contract_address, event_type, block_number, transaction_hash, log_index, timestamp, decoded_data, raw_data, raw_keys 
-                     FROM events WHERE contract_address = ? AND block_number >= ? AND block_number <= ? 
+                    "SELECT id, contract_address, event_type, block_number, transaction_hash, log_index, timestamp, decoded_data, raw_data, raw_keys, chain_id 
+                     FROM events WHERE contract_address = ? AND (? IS NULL OR chain_id = ?) AND block_number >= ? AND block_number <= ? 
                      ORDER BY block_number DESC, log_index DESC LIMIT ? OFFSET ?"
                 )
                 .bind(&normalized_address)
+                .bind(chain_id)
+                .bind(chain_id)
                 .bind(from as i64)
                 .bind(to as i64)
                 .bind(limit as i64)
@@ -428,11 +1361,13 @@ impl Database {
             // Only from block
             (None, Some(from), None) => {
                 sqlx::query(
-                    "SELECT id, contract_address, event_type, block_number, transaction_hash, log_index, timestamp, decoded_data, raw_data, raw_keys 
-                     FROM events WHERE contract_address = ? AND block_number >= ? 
+                    "SELECT id, contract_address, event_type, block_number, transaction_hash, log_index, timestamp, decoded_data, raw_data, raw_keys, chain_id 
+                     FROM events WHERE contract_address = ? AND (? IS NULL OR chain_id = ?) AND block_number >= ? 
                      ORDER BY block_number DESC, log_index DESC LIMIT ? OFFSET ?"
                 )
                 .bind(&normalized_address)
+                .bind(chain_id)
+                .bind(chain_id)
                 .bind(from as i64)
                 .bind(limit as i64)
                 .bind(offset as i64)
@@ -442,11 +1377,13 @@ impl Database {
             // Only to block
             (None, None, Some(to)) => {
                 sqlx::query(
-                    "SELECT id, contract_address, event_type, block_number, transaction_hash, log_index, timestamp, decoded_data, raw_data, raw_keys 
-                     FROM events WHERE contract_address = ? AND block_number <= ? 
+                    "SELECT id, contract_address, event_type, block_number, transaction_hash, log_index, timestamp, decoded_data, raw_data, raw_keys, chain_id 
+                     FROM events WHERE contract_address = ? AND (? IS NULL OR chain_id = ?) AND block_number <= ? 
                      ORDER BY block_number DESC, log_index DESC LIMIT ? OFFSET ?"
                 )
                 .bind(&normalized_address)
+                .bind(chain_id)
+                .bind(chain_id)
                 .bind(to as i64)
                 .bind(limit as i64)
                 .bind(offset as i64)
@@ -456,11 +1393,13 @@ impl Database {
             // For now, handle event type filtering in memory - we can optimize this later
             _ => {
                 sqlx::query(
-                    "SELECT id, contract_address, event_type, block_number, transaction_hash, log_index, timestamp, decoded_data, raw_data, raw_keys 
-                     FROM events WHERE contract_address = ? 
+                    "SELECT id, contract_address, event_type, block_number, transaction_hash, log_index, timestamp, decoded_data, raw_data, raw_keys, chain_id 
+                     FROM events WHERE contract_address = ? AND (? IS NULL OR chain_id = ?) 
                      ORDER BY block_number DESC, log_index DESC"
                 )
                 .bind(&normalized_address)
+                .bind(chain_id)
+                .bind(chain_id)
                 .fetch_all(&self.pool)
                 .await?
             }
@@ -472,7 +1411,7 @@ impl Database {
             
             // Filter by event types if specified
             if let Some(filter_types) = event_types {
-                if !filter_types.contains(&event_type) {
+                if !crate::starknet::event_type_matches(filter_types, &event_type) {
                     continue;
                 }
             }
@@ -490,6 +1429,7 @@ impl Database {
                 decoded_data: row.get("decoded_data"),
                 raw_data: row.get("raw_data"),
                 raw_keys: row.get("raw_keys"),
+                chain_id: row.get("chain_id"),
             });
         }
         
@@ -499,6 +1439,7 @@ impl Database {
     pub async fn get_events_with_ordering(
         &self,
         contract_address: &str,
+        chain_id: Option<&str>,
         event_types: Option<&[String]>,
         from_block: Option<u64>,
         to_block: Option<u64>,
@@ -521,12 +1462,14 @@ impl Database {
             // No filters except contract address
             (None, None, None) => {
                 let query = format!(
-                    "SELECT id, contract_address, event_type, block_number, transaction_hash, log_index, timestamp, decoded_data, raw_data, raw_keys 
-                     FROM events WHERE contract_address = ? 
+                    "SELECT id, contract_address, event_type, block_number, transaction_hash, log_index, timestamp, decoded_data, raw_data, raw_keys, chain_id 
+                     FROM events WHERE contract_address = ? AND (? IS NULL OR chain_id = ?) 
                      {} LIMIT ? OFFSET ?", order_clause
                 );
                 sqlx::query(&query)
                     .bind(&normalized_address)
+                    .bind(chain_id)
+                    .bind(chain_id)
                     .bind(limit as i64)
                     .bind(offset as i64)
                     .fetch_all(&self.pool)
@@ -535,12 +1478,14 @@ impl Database {
             // Only block range filter
             (None, Some(from), Some(to)) => {
                 let query = format!(
-                    "SELECT id, contract_address, event_type, block_number, transaction_hash, log_index, timestamp, decoded_data, raw_data, raw_keys 
-                     FROM events WHERE contract_address = ? AND block_number >= ? AND block_number <= ? 
+                    "SELECT id, contract_address, event_type, block_number, transaction_hash, log_index, timestamp, decoded_data, raw_data, raw_keys, chain_id 
+                     FROM events WHERE contract_address = ? AND (? IS NULL OR chain_id = ?) AND block_number >= ? AND block_number <= ? 
                      {} LIMIT ? OFFSET ?", order_clause
                 );
                 sqlx::query(&query)
                     .bind(&normalized_address)
+                    .bind(chain_id)
+                    .bind(chain_id)
                     .bind(from as i64)
                     .bind(to as i64)
                     .bind(limit as i64)
@@ -551,12 +1496,14 @@ impl Database {
             // Only from block
             (None, Some(from), None) => {
                 let query = format!(
-                    "SELECT id, contract_address, event_type, block_number, transaction_hash, log_index, timestamp, decoded_data, raw_data, raw_keys 
-                     FROM events WHERE contract_address = ? AND block_number >= ? 
+                    "SELECT id, contract_address, event_type, block_number, transaction_hash, log_index, timestamp, decoded_data, raw_data, raw_keys, chain_id 
+                     FROM events WHERE contract_address = ? AND (? IS NULL OR chain_id = ?) AND block_number >= ? 
                      {} LIMIT ? OFFSET ?", order_clause
                 );
                 sqlx::query(&query)
                     .bind(&normalized_address)
+                    .bind(chain_id)
+                    .bind(chain_id)
                     .bind(from as i64)
                     .bind(limit as i64)
                     .bind(offset as i64)
@@ -566,12 +1513,14 @@ impl Database {
             // Only to block
             (None, None, Some(to)) => {
                 let query = format!(
-                    "SELECT id, contract_address, event_type, block_number, transaction_hash, log_index, timestamp, decoded_data, raw_data, raw_keys 
-                     FROM events WHERE contract_address = ? AND block_number <= ? 
+                    "SELECT id, contract_address, event_type, block_number, transaction_hash, log_index, timestamp, decoded_data, raw_data, raw_keys, chain_id 
+                     FROM events WHERE contract_address = ? AND (? IS NULL OR chain_id = ?) AND block_number <= ? 
                      {} LIMIT ? OFFSET ?", order_clause
                 );
                 sqlx::query(&query)
                     .bind(&normalized_address)
+                    .bind(chain_id)
+                    .bind(chain_id)
                     .bind(to as i64)
                     .bind(limit as i64)
                     .bind(offset as i64)
@@ -581,12 +1530,14 @@ impl Database {
             // For now, handle event type filtering in memory - we can optimize this later
             _ => {
                 let query = format!(
-                    "SELECT id, contract_address, event_type, block_number, transaction_hash, log_index, timestamp, decoded_data, raw_data, raw_keys 
-                     FROM events WHERE contract_address = ? 
+                    "SELECT id, contract_address, event_type, block_number, transaction_hash, log_index, timestamp, decoded_data, raw_data, raw_keys, chain_id 
+                     FROM events WHERE contract_address = ? AND (? IS NULL OR chain_id = ?) 
                      {}", order_clause
                 );
                 sqlx::query(&query)
                     .bind(&normalized_address)
+                    .bind(chain_id)
+                    .bind(chain_id)
                     .fetch_all(&self.pool)
                     .await?
             }
@@ -598,7 +1549,7 @@ impl Database {
             
             // Filter by event types if specified
             if let Some(filter_types) = event_types {
-                if !filter_types.contains(&event_type) {
+                if !crate::starknet::event_type_matches(filter_types, &event_type) {
                     continue;
                 }
             }
@@ -616,44 +1567,86 @@ impl Database {
                 decoded_data: row.get("decoded_data"),
                 raw_data: row.get("raw_data"),
                 raw_keys: row.get("raw_keys"),
+                chain_id: row.get("chain_id"),
             });
         }
         
         Ok(events)
     }
 
+    /// Latest cursor for a contract on any chain
     pub async fn get_indexer_state(&self, contract_address: &str) -> Result<Option<IndexerState>, sqlx::Error> {
         let normalized_address = Self::normalize_address(contract_address);
         let row = sqlx::query(
-            "SELECT id, contract_address, last_synced_block, updated_at FROM indexer_state WHERE contract_address = ?"
+            "SELECT id, chain_id, contract_address, last_synced_block, updated_at FROM indexer_state \
+             WHERE contract_address = ? ORDER BY updated_at DESC LIMIT 1"
         )
         .bind(&normalized_address)
         .fetch_optional(&self.pool)
         .await?;
 
-        if let Some(row) = row {
-            Ok(Some(IndexerState {
-                id: row.get("id"),
-                contract_address: row.get("contract_address"),
-                last_synced_block: row.get::<i64, _>("last_synced_block") as u64,
-                updated_at: DateTime::parse_from_rfc3339(&row.get::<String, _>("updated_at"))
-                    .unwrap()
-                    .with_timezone(&Utc),
-            }))
-        } else {
-            Ok(None)
+        Ok(row.map(|row| Self::indexer_state_from_row(&row)))
+    }
+
+    /// Cursor of a contract on a specific chain, falling back to a cursor written before chain ids were recorded
+    pub async fn get_chain_indexer_state(&self, chain_id: &str, contract_address: &str) -> Result<Option<IndexerState>, sqlx::Error> {
+        let normalized_address = Self::normalize_address(contract_address);
+        let row = sqlx::query(
+            "SELECT id, chain_id, contract_address, last_synced_block, updated_at FROM indexer_state \
+             WHERE contract_address = ? AND chain_id IN (?, '') ORDER BY chain_id DESC LIMIT 1"
+        )
+        .bind(&normalized_address)
+        .bind(chain_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|row| Self::indexer_state_from_row(&row)))
+    }
+
+    fn indexer_state_from_row(row: &sqlx::sqlite::SqliteRow) -> IndexerState {
+        IndexerState {
+            id: row.get("id"),
+            chain_id: row.get("chain_id"),
+            contract_address: row.get("contract_address"),
+            last_synced_block: row.get::<i64, _>("last_synced_block") as u64,
+            updated_at: DateTime::parse_from_rfc3339(&row.get::<String, _>("updated_at"))
+                .unwrap()
+                .with_timezone(&Utc),
         }
     }
 
     pub async fn update_indexer_state(&self, contract_address: &str, last_synced_block: u64) -> Result<(), sqlx::Error> {
+        self.update_chain_indexer_state("", contract_address, last_synced_block).await
+    }
+
+    pub async fn update_chain_indexer_state(&self, chain_id: &str, contract_address: &str, last_synced_block: u64) -> Result<(), sqlx::Error> {
         let normalized_address = Self::normalize_address(contract_address);
         let now = Utc::now();
+
+        // The first chain to write a cursor takes over the legacy one
+        if !chain_id.is_empty() {
+            sqlx::query(
+                "UPDATE indexer_state SET chain_id = ? WHERE chain_id = '' AND contract_address = ? \
+                 AND NOT EXISTS (SELECT 1 FROM indexer_state WHERE chain_id = ? AND contract_address = ?)"
+            )
+            .bind(chain_id)
+            .bind(&normalized_address)
+            .bind(chain_id)
+            .bind(&normalized_address)
+            .execute(&self.pool)
+            .await?;
+        }
+
         sqlx::query(
             r#"
-            INSERT OR REPLACE INTO indexer_state (contract_address, last_synced_block, updated_at)
-            VALUES (?, ?, ?)
+            INSERT INTO indexer_state (chain_id, contract_address, last_synced_block, updated_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(chain_id, contract_address) DO UPDATE SET
+                last_synced_block = excluded.last_synced_block,
+                updated_at = excluded.updated_at
             "#
         )
+        .bind(chain_id)
         .bind(&normalized_address)
         .bind(last_synced_block as i64)
         .bind(now.to_rfc3339())
@@ -663,19 +1656,138 @@ impl Database {
         Ok(())
     }
 
-    pub async fn count_events(&self, contract_address: &str, event_types: Option<&[String]>) -> Result<i64, sqlx::Error> {
+    pub async fn delete_chain_indexer_state(&self, chain_id: &str, contract_address: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM indexer_state WHERE chain_id = ? AND contract_address = ?")
+            .bind(chain_id)
+            .bind(Self::normalize_address(contract_address))
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_metadata(&self, key: &str) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar("SELECT value FROM indexer_metadata WHERE key = ?")
+            .bind(key)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    pub async fn set_metadata(&self, key: &str, value: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("INSERT OR REPLACE INTO indexer_metadata (key, value, updated_at) VALUES (?, ?, ?)")
+            .bind(key)
+            .bind(value)
+            .bind(Utc::now().to_rfc3339())
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Known `(block_number, block_hash)` pairs of a network, oldest first
+    pub async fn get_network_checkpoints(&self, network: &str) -> Result<Vec<(u64, String)>, sqlx::Error> {
+        let rows = sqlx::query("SELECT block_number, block_hash FROM network_checkpoints WHERE network = ? ORDER BY block_number")
+            .bind(network)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.iter()
+            .map(|row| (row.get::<i64, _>("block_number") as u64, row.get("block_hash")))
+            .collect())
+    }
+
+    /// Record a checkpoint, keeping genesis and the `keep` most recent ones
+    pub async fn add_network_checkpoint(&self, network: &str, block_number: u64, block_hash: &str, keep: i64) -> Result<(), sqlx::Error> {
+        sqlx::query("INSERT OR REPLACE INTO network_checkpoints (network, block_number, block_hash, recorded_at) VALUES (?, ?, ?, ?)")
+            .bind(network)
+            .bind(block_number as i64)
+            .bind(block_hash)
+            .bind(Utc::now().to_rfc3339())
+            .execute(&self.pool)
+            .await?;
+
+        sqlx::query(
+            "DELETE FROM network_checkpoints WHERE network = ? AND block_number > 0 AND block_number NOT IN \
+             (SELECT block_number FROM network_checkpoints WHERE network = ? AND block_number > 0 ORDER BY block_number DESC LIMIT ?)"
+        )
+        .bind(network)
+        .bind(network)
+        .bind(keep)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Drop everything indexed from a network, e.g. after a devnet was restarted from genesis. Rows are
+    /// found by the key the network stores them under: a development network that took its chain id
+    /// from the node has one of its own (`<network>:<chain id>`), otherwise the caller has to make sure
+    /// no other network shares the chain id.
+    pub async fn reset_network_data(&self, network: &str, chain_id: &str) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        for table in [
+            "events",
+            "account_events",
+            "internal_calls",
+            "messages",
+            "contract_deployments",
+            "class_declarations",
+            "indexer_state",
+        ] {
+            sqlx::query(&format!("DELETE FROM {} WHERE chain_id = ?", table))
+                .bind(chain_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        sqlx::query("DELETE FROM network_checkpoints WHERE network = ?")
+            .bind(network)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Drop what a contract indexer wrote for blocks `from_block` to `to_block` of a chain, so blocks a
+    /// reorg replaced can be indexed again without leaving events of orphaned transactions behind
+    pub async fn delete_contract_blocks(&self, chain_id: &str, contract_address: &str, from_block: u64, to_block: u64) -> Result<(), sqlx::Error> {
+        let normalized_address = Self::normalize_address(contract_address);
+        let mut tx = self.pool.begin().await?;
+
+        for table in ["events", "internal_calls", "messages"] {
+            sqlx::query(&format!(
+                "DELETE FROM {} WHERE chain_id = ? AND contract_address = ? AND block_number BETWEEN ? AND ?",
+                table
+            ))
+            .bind(chain_id)
+            .bind(&normalized_address)
+            .bind(from_block as i64)
+            .bind(to_block as i64)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn count_events(&self, contract_address: &str, chain_id: Option<&str>, event_types: Option<&[String]>) -> Result<i64, sqlx::Error> {
         let normalized_address = Self::normalize_address(contract_address);
         match event_types {
             None => {
-                let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM events WHERE contract_address = ?")
+                let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM events WHERE contract_address = ? AND (? IS NULL OR chain_id = ?)")
                     .bind(&normalized_address)
+                    .bind(chain_id)
+                    .bind(chain_id)
                     .fetch_one(&self.pool)
                     .await?;
                 Ok(count)
             }
             Some(types) if types.is_empty() => {
-                let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM events WHERE contract_address = ?")
+                let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM events WHERE contract_address = ? AND (? IS NULL OR chain_id = ?)")
                     .bind(&normalized_address)
+                    .bind(chain_id)
+                    .bind(chain_id)
                     .fetch_one(&self.pool)
                     .await?;
                 Ok(count)
@@ -683,7 +1795,7 @@ impl Database {
             Some(types) => {
                 // For now, use a simple approach - get all events and count in memory
                 // In production, you'd want to optimize this with proper SQL IN clauses
-                let events = self.get_events(&normalized_address, Some(types), None, None, i32::MAX, 0).await?;
+                let events = self.get_events(&normalized_address, chain_id, Some(types), None, None, i32::MAX, 0).await?;
                 Ok(events.len() as i64)
             }
         }
@@ -692,6 +1804,7 @@ impl Database {
     pub async fn get_events_with_advanced_filters(
         &self,
         contract_address: &str,
+        chain_id: Option<&str>,
         event_types: Option<&[String]>,
         event_keys: Option<&[String]>,
         from_block: Option<u64>,
@@ -706,7 +1819,7 @@ impl Database {
         let normalized_address = Self::normalize_address(contract_address);
         // For now, use the existing get_events method and filter in memory
         // This can be optimized later with proper dynamic SQL queries
-        let mut events = self.get_events_with_ordering(&normalized_address, event_types, from_block, to_block, limit * 2, offset, order_by).await?;
+        let mut events = self.get_events_with_ordering(&normalized_address, chain_id, event_types, from_block, to_block, limit * 2, offset, order_by).await?;
         
         // Apply additional filters in memory
         events.retain(|event| {
@@ -827,6 +1940,7 @@ impl Database {
     pub async fn get_events_from_multiple_contracts(
         &self,
         contract_addresses: &[String],
+        chain_id: Option<&str>,
         event_types: Option<&[String]>,
         event_keys: Option<&[String]>,
         from_block: Option<u64>,
@@ -842,6 +1956,7 @@ impl Database {
         for contract_address in contract_addresses {
             let events = self.get_events_with_advanced_filters(
                 contract_address,
+                chain_id,
                 event_types,
                 event_keys,
                 from_block,
@@ -1037,6 +2152,17 @@ impl Database {
         Ok(())
     }
 
+    /// Changes whenever a deployment is created, updated or deleted
+    pub async fn get_deployments_revision(&self) -> Result<DeploymentsRevision, sqlx::Error> {
+        let row = sqlx::query("SELECT COUNT(*) AS count, MAX(updated_at) AS updated_at FROM deployments")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(DeploymentsRevision {
+            count: row.get("count"),
+            updated_at: row.get("updated_at"),
+        })
+    }
+
     pub async fn count_deployments(&self, status: Option<&str>, network: Option<&str>) -> Result<i64, sqlx::Error> {
         let mut query = "SELECT COUNT(*) FROM deployments".to_string();
         let mut conditions = Vec::new();
@@ -1461,6 +2587,69 @@ impl Database {
         Ok(())
     }
 
+    pub async fn upsert_abi_override(&self, record: &AbiOverrideRecord) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO abi_overrides
+            (id, deployment_id, contract_address, abi, class_hash, from_block, to_block, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#
+        )
+        .bind(&record.id)
+        .bind(&record.deployment_id)
+        .bind(Self::normalize_address(&record.contract_address))
+        .bind(&record.abi)
+        .bind(record.class_hash.as_deref().map(Self::normalize_address))
+        .bind(record.from_block.map(|b| b as i64))
+        .bind(record.to_block.map(|b| b as i64))
+        .bind(record.created_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Overrides that apply to an indexer of `deployment_id` (or to the command line indexer when None):
+    /// those of the deployment plus those without one. Oldest first, so later ones take precedence.
+    pub async fn get_abi_overrides(&self, deployment_id: Option<&str>, contract_address: Option<&str>) -> Result<Vec<AbiOverrideRecord>, sqlx::Error> {
+        let normalized_contract = contract_address.map(Self::normalize_address);
+
+        let mut query = "SELECT id, deployment_id, contract_address, abi, class_hash, from_block, to_block, created_at \
+                         FROM abi_overrides WHERE (deployment_id IS NULL OR deployment_id = ?)".to_string();
+        if normalized_contract.is_some() {
+            query.push_str(" AND contract_address = ?");
+        }
+        query.push_str(" ORDER BY created_at, id");
+
+        let mut sql_query = sqlx::query(&query).bind(deployment_id);
+        if let Some(contract) = &normalized_contract {
+            sql_query = sql_query.bind(contract);
+        }
+
+        let rows = sql_query.fetch_all(&self.pool).await?;
+        Ok(rows.into_iter().map(|row| AbiOverrideRecord {
+            id: row.get("id"),
+            deployment_id: row.get("deployment_id"),
+            contract_address: row.get("contract_address"),
+            abi: row.get("abi"),
+            class_hash: row.get("class_hash"),
+            from_block: row.get::<Option<i64>, _>("from_block").map(|b| b as u64),
+            to_block: row.get::<Option<i64>, _>("to_block").map(|b| b as u64),
+            created_at: DateTime::parse_from_rfc3339(&row.get::<String, _>("created_at"))
+                .unwrap()
+                .with_timezone(&Utc),
+        }).collect())
+    }
+
+    /// Whether an override of the deployment was removed
+    pub async fn delete_abi_override(&self, id: &str, deployment_id: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM abi_overrides WHERE id = ? AND deployment_id = ?")
+            .bind(id)
+            .bind(deployment_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
     pub async fn get_contracts_by_deployment(&self, deployment_id: &str) -> Result<Vec<DeploymentContract>, sqlx::Error> {
         self.get_deployment_contracts(deployment_id).await
     }
@@ -1770,3 +2959,183 @@ impl Database {
         Ok(stats)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_legacy_cursor_is_claimed_per_chain() {
+        let path = std::env::temp_dir().join(format!("indexer_state_{}.db", uuid::Uuid::new_v4()));
+        let database_url = format!("sqlite:{}", path.to_string_lossy());
+
+        // A cursor table from before chain ids were recorded
+        {
+            let options = SqliteConnectOptions::from_str(&database_url).unwrap().create_if_missing(true);
+            let pool = SqlitePool::connect_with(options).await.unwrap();
+            sqlx::query("CREATE TABLE indexer_state (id INTEGER PRIMARY KEY, contract_address TEXT UNIQUE NOT NULL, last_synced_block INTEGER NOT NULL, updated_at TEXT NOT NULL)")
+                .execute(&pool).await.unwrap();
+            sqlx::query("INSERT INTO indexer_state (contract_address, last_synced_block, updated_at) VALUES ('0x0000000000000000000000000000000000000000000000000000000000000abc', 42, ?)")
+                .bind(Utc::now().to_rfc3339())
+                .execute(&pool).await.unwrap();
+            pool.close().await;
+        }
+
+        let database = Database::new(&database_url).await.unwrap();
+
+        let legacy = database.get_chain_indexer_state("SN_MAIN", "0xabc").await.unwrap().unwrap();
+        assert_eq!(legacy.chain_id, "");
+        assert_eq!(legacy.last_synced_block, 42);
+
+        database.update_chain_indexer_state("SN_MAIN", "0xabc", 50).await.unwrap();
+        let mainnet = database.get_chain_indexer_state("SN_MAIN", "0xabc").await.unwrap().unwrap();
+        assert_eq!(mainnet.chain_id, "SN_MAIN");
+        assert_eq!(mainnet.last_synced_block, 50);
+
+        // The same address on another chain starts from scratch
+        assert!(database.get_chain_indexer_state("SN_SEPOLIA", "0xabc").await.unwrap().is_none());
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn test_reset_network_data_keeps_other_networks() {
+        let path = std::env::temp_dir().join(format!("reset_network_{}.db", uuid::Uuid::new_v4()));
+        let database = Database::new(&format!("sqlite:{}", path.to_string_lossy())).await.unwrap();
+
+        let deployment = |chain_id: &str| ContractDeploymentRecord {
+            contract_address: Database::normalize_address("0xabc"),
+            class_hash: Database::normalize_address("0x1"),
+            deployer_address: None,
+            transaction_hash: None,
+            deployment_type: "DEPLOY_SYSCALL".to_string(),
+            block_number: 5,
+            timestamp: Utc::now(),
+            chain_id: chain_id.to_string(),
+        };
+        // starknet-devnet reports Sepolia's chain id; the devnet's rows are stored under its own key
+        for (network, chain_id) in [("devnet", "devnet:SN_SEPOLIA"), ("testnet", "SN_SEPOLIA")] {
+            database.update_chain_indexer_state(chain_id, "0xabc", 10).await.unwrap();
+            database.insert_contract_deployments(&[deployment(chain_id)]).await.unwrap();
+            for block_number in [0, 100, 200, 300] {
+                database.add_network_checkpoint(network, block_number, "0x1", 2).await.unwrap();
+            }
+        }
+
+        let checkpoints: Vec<u64> = database.get_network_checkpoints("devnet").await.unwrap()
+            .into_iter().map(|(block_number, _)| block_number).collect();
+        assert_eq!(checkpoints, vec![0, 200, 300]);
+
+        database.reset_network_data("devnet", "devnet:SN_SEPOLIA").await.unwrap();
+        assert!(database.get_network_checkpoints("devnet").await.unwrap().is_empty());
+        assert!(database.get_chain_indexer_state("devnet:SN_SEPOLIA", "0xabc").await.unwrap().is_none());
+        assert!(database.get_contract_deployments(Some("devnet:SN_SEPOLIA"), None, None, None, None, 10, 0).await.unwrap().is_empty());
+
+        // Sepolia keeps its data and checkpoints
+        assert_eq!(database.get_network_checkpoints("testnet").await.unwrap().len(), 3);
+        assert_eq!(database.get_chain_indexer_state("SN_SEPOLIA", "0xabc").await.unwrap().unwrap().last_synced_block, 10);
+        assert_eq!(database.get_contract_deployments(Some("SN_SEPOLIA"), None, None, None, None, 10, 0).await.unwrap().len(), 1);
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn test_deployments_revision_follows_changes() {
+        let path = std::env::temp_dir().join(format!("deployments_revision_{}.db", uuid::Uuid::new_v4()));
+        let database = Database::new(&format!("sqlite:{}", path.to_string_lossy())).await.unwrap();
+
+        let empty = database.get_deployments_revision().await.unwrap();
+        let created_at = Utc::now() - chrono::Duration::seconds(60);
+        database.create_deployment(&DeploymentRecord {
+            id: "dep-1".to_string(),
+            name: "Factory".to_string(),
+            description: None,
+            database_url: "sqlite:dep-1.db".to_string(),
+            contract_address: None,
+            network: "mainnet".to_string(),
+            status: "active".to_string(),
+            created_at,
+            updated_at: created_at,
+            metadata: None,
+        }).await.unwrap();
+        let created = database.get_deployments_revision().await.unwrap();
+        assert_ne!(created, empty);
+        assert_eq!(database.get_deployments_revision().await.unwrap(), created);
+
+        database.update_deployment("dep-1", None, None, None, None, Some(r#"{"autoRegisterClassHashes":["0x1"]}"#)).await.unwrap();
+        let updated = database.get_deployments_revision().await.unwrap();
+        assert_ne!(updated, created);
+
+        database.delete_deployment("dep-1").await.unwrap();
+        assert_eq!(database.get_deployments_revision().await.unwrap(), empty);
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn test_rows_are_kept_apart_per_chain() {
+        let path = std::env::temp_dir().join(format!("per_chain_{}.db", uuid::Uuid::new_v4()));
+        let database_url = format!("sqlite:{}", path.to_string_lossy());
+
+        // A deployments table from before chain ids were recorded
+        {
+            let options = SqliteConnectOptions::from_str(&database_url).unwrap().create_if_missing(true);
+            let pool = SqlitePool::connect_with(options).await.unwrap();
+            sqlx::query("CREATE TABLE contract_deployments (contract_address TEXT PRIMARY KEY, class_hash TEXT NOT NULL, deployer_address TEXT, \
+                         transaction_hash TEXT, deployment_type TEXT NOT NULL, block_number INTEGER NOT NULL, timestamp TEXT NOT NULL)")
+                .execute(&pool).await.unwrap();
+            sqlx::query("INSERT INTO contract_deployments VALUES ('0x0abc', '0x01', NULL, NULL, 'DEPLOY_SYSCALL', 5, ?)")
+                .bind(Utc::now().to_rfc3339())
+                .execute(&pool).await.unwrap();
+            pool.close().await;
+        }
+
+        let database = Database::new(&database_url).await.unwrap();
+        let legacy = database.get_contract_deployments(None, None, None, None, None, 10, 0).await.unwrap();
+        assert_eq!(legacy.len(), 1);
+        assert_eq!(legacy[0].chain_id, "");
+
+        // The same address deployed on two networks
+        let deployment = |chain_id: &str, block_number| ContractDeploymentRecord {
+            contract_address: Database::normalize_address("0xdef"),
+            class_hash: Database::normalize_address("0x02"),
+            deployer_address: None,
+            transaction_hash: None,
+            deployment_type: "DEPLOY_SYSCALL".to_string(),
+            block_number,
+            timestamp: Utc::now(),
+            chain_id: chain_id.to_string(),
+        };
+        database.insert_contract_deployments(&[deployment("SN_MAIN", 10), deployment("SN_SEPOLIA", 20)]).await.unwrap();
+        let sepolia = database.get_contract_deployments(Some("SN_SEPOLIA"), None, None, None, None, 10, 0).await.unwrap();
+        assert_eq!(sepolia.len(), 1);
+        assert_eq!(sepolia[0].block_number, 20);
+        assert_eq!(database.get_contract_deployments(Some("SN_MAIN"), None, None, None, None, 10, 0).await.unwrap()[0].block_number, 10);
+
+        let event = |chain_id: &str, id: &str| EventRecord {
+            id: id.to_string(),
+            contract_address: Database::normalize_address("0xdef"),
+            event_type: "Transfer".to_string(),
+            block_number: 10,
+            transaction_hash: "0x1".to_string(),
+            log_index: 0,
+            timestamp: Utc::now(),
+            decoded_data: None,
+            raw_data: "[]".to_string(),
+            raw_keys: "[]".to_string(),
+            chain_id: Some(chain_id.to_string()),
+        };
+        database.insert_events(&[event("SN_MAIN", "main"), event("SN_SEPOLIA", "sepolia")]).await.unwrap();
+        let sepolia_events = database.get_events("0xdef", Some("SN_SEPOLIA"), None, None, None, 10, 0).await.unwrap();
+        assert_eq!(sepolia_events.iter().map(|e| e.id.as_str()).collect::<Vec<_>>(), vec!["sepolia"]);
+        assert_eq!(database.count_events("0xdef", Some("SN_MAIN"), None).await.unwrap(), 1);
+        assert_eq!(database.count_events("0xdef", None, None).await.unwrap(), 2);
+
+        // Reorged blocks are dropped for one chain only
+        database.delete_contract_blocks("SN_MAIN", "0xdef", 10, 12).await.unwrap();
+        assert_eq!(database.count_events("0xdef", Some("SN_MAIN"), None).await.unwrap(), 0);
+        assert_eq!(database.count_events("0xdef", Some("SN_SEPOLIA"), None).await.unwrap(), 1);
+
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,331 @@
+use num_bigint::{BigInt, BigUint};
+use serde_json::Value;
+
+use crate::cairo_serde;
+use crate::graphql::types::ValueFormat;
+
+/// A value decoded from felts, along with what it was read as, so that it can be written in any
+/// `ValueFormat` without guessing from its magnitude. Events are stored in this form (see
+/// `to_stored`) and written out in the format a query asks for.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodedValue {
+    /// `Option::None` and `()`
+    Null,
+    Bool(bool),
+    Unsigned { bits: u64, value: BigUint },
+    Signed { bits: u64, value: BigInt },
+    /// felt252 and other single-felt values that are not addresses, such as `bytes31`
+    Felt(BigUint),
+    /// ContractAddress, ClassHash, EthAddress and StorageAddress, written in every format as hex padded
+    /// to 64 digits, like addresses everywhere else in the API
+    Address(BigUint),
+    Text(String),
+    List(Vec<DecodedValue>),
+    Struct(Vec<(String, DecodedValue)>),
+    /// An enum variant; `payload` is `None` for unit variants
+    Variant { name: String, payload: Option<Box<DecodedValue>> },
+}
+
+impl DecodedValue {
+    /// A felt given as hex or decimal
+    pub fn felt(felt: &str) -> Option<Self> {
+        cairo_serde::parse_felt(felt).map(Self::Felt)
+    }
+
+    pub fn address(felt: &str) -> Option<Self> {
+        cairo_serde::parse_felt(felt).map(Self::Address)
+    }
+
+    /// The value as JSON. `Raw` only applies to whole events, see `render_event_data`; values
+    /// themselves are written as with `Hex`.
+    pub fn render(&self, format: ValueFormat) -> Value {
+        match self {
+            Self::Null => Value::Null,
+            Self::Bool(b) => Value::Bool(*b),
+            Self::Unsigned { bits, value } => match format {
+                ValueFormat::Typed => cairo_serde::unsigned_json(value, *bits),
+                ValueFormat::Hex | ValueFormat::Raw => Value::String(format!("{:#x}", value)),
+                ValueFormat::DecimalString => Value::String(value.to_string()),
+            },
+            Self::Signed { bits, value } => match format {
+                ValueFormat::Typed => cairo_serde::signed_json(value, *bits),
+                ValueFormat::Hex | ValueFormat::Raw => Value::String(signed_hex(value)),
+                ValueFormat::DecimalString => Value::String(value.to_string()),
+            },
+            Self::Felt(value) => match format {
+                ValueFormat::DecimalString => Value::String(value.to_string()),
+                _ => Value::String(format!("{:#x}", value)),
+            },
+            Self::Address(value) => Value::String(format!("{:#066x}", value)),
+            Self::Text(text) => Value::String(text.clone()),
+            Self::List(items) => Value::Array(items.iter().map(|item| item.render(format)).collect()),
+            Self::Struct(members) => Value::Object(members.iter().map(|(name, value)| (name.clone(), value.render(format))).collect()),
+            Self::Variant { name, payload: None } => Value::String(name.clone()),
+            Self::Variant { name, payload: Some(payload) } => serde_json::json!({ name.clone(): payload.render(format) }),
+        }
+    }
+
+    /// The value as stored in the database: scalars tagged with their type, `{"$t": "u64", "$v": "42"}`,
+    /// variants as `{"$variant": "Name", "$v": payload}`, and lists and structs as JSON arrays and objects.
+    /// `$` cannot start a Cairo member name, so tags never clash with struct members.
+    pub fn to_stored(&self) -> Value {
+        let tagged = |tag: String, value: Value| serde_json::json!({ "$t": tag, "$v": value });
+        match self {
+            Self::Null => Value::Null,
+            Self::Bool(b) => tagged("bool".to_string(), Value::Bool(*b)),
+            Self::Unsigned { bits, value } => tagged(format!("u{}", bits), Value::String(value.to_string())),
+            Self::Signed { bits, value } => tagged(format!("i{}", bits), Value::String(value.to_string())),
+            Self::Felt(value) => tagged("felt".to_string(), Value::String(format!("{:#x}", value))),
+            Self::Address(value) => tagged("address".to_string(), Value::String(format!("{:#x}", value))),
+            Self::Text(text) => tagged("text".to_string(), Value::String(text.clone())),
+            Self::List(items) => Value::Array(items.iter().map(Self::to_stored).collect()),
+            Self::Struct(members) => Value::Object(members.iter().map(|(name, value)| (name.clone(), value.to_stored())).collect()),
+            Self::Variant { name, payload } => {
+                let mut variant = serde_json::Map::new();
+                variant.insert("$variant".to_string(), Value::String(name.clone()));
+                if let Some(payload) = payload {
+                    variant.insert("$v".to_string(), payload.to_stored());
+                }
+                Value::Object(variant)
+            }
+        }
+    }
+
+    /// Read back a stored value. Rows indexed before values were tagged hold plain JSON; their types
+    /// are recovered as far as the JSON allows: 0x strings are felts, integers are unsigned or signed.
+    pub fn from_stored(stored: &Value) -> Self {
+        match stored {
+            Value::Null => Self::Null,
+            Value::Bool(b) => Self::Bool(*b),
+            Value::Number(n) => match (n.as_u64(), n.as_i64()) {
+                (Some(value), _) => Self::Unsigned { bits: if value <= u32::MAX as u64 { 32 } else { 64 }, value: value.into() },
+                (None, Some(value)) => Self::Signed { bits: if i32::try_from(value).is_ok() { 32 } else { 64 }, value: value.into() },
+                _ => Self::Text(n.to_string()),
+            },
+            Value::String(s) if s.starts_with("0x") => Self::felt(s).unwrap_or_else(|| Self::Text(s.clone())),
+            Value::String(s) => Self::Text(s.clone()),
+            Value::Array(items) => Self::List(items.iter().map(Self::from_stored).collect()),
+            Value::Object(object) => {
+                if let Some(tag) = object.get("$t").and_then(|t| t.as_str()) {
+                    if let Some(value) = object.get("$v").and_then(|v| Self::from_tagged(tag, v)) {
+                        return value;
+                    }
+                }
+                if let Some(name) = object.get("$variant").and_then(|n| n.as_str()) {
+                    return Self::Variant {
+                        name: name.to_string(),
+                        payload: object.get("$v").map(|payload| Box::new(Self::from_stored(payload))),
+                    };
+                }
+                // Older rows wrapped values with their type, e.g. {"hex": "0x2a", "decimal": "42", "type": "felt252"}
+                if object.contains_key("type") {
+                    if let Some(value) = ["value", "decimal", "address", "hex", "raw"].iter().find_map(|key| object.get(*key)) {
+                        return Self::from_stored(value);
+                    }
+                }
+                Self::Struct(object.iter().map(|(name, value)| (name.clone(), Self::from_stored(value))).collect())
+            }
+        }
+    }
+
+    fn from_tagged(tag: &str, value: &Value) -> Option<Self> {
+        match (tag, value) {
+            ("bool", Value::Bool(b)) => Some(Self::Bool(*b)),
+            ("felt", Value::String(s)) => Self::felt(s),
+            ("address", Value::String(s)) => Self::address(s),
+            ("text", Value::String(s)) => Some(Self::Text(s.clone())),
+            (_, Value::String(s)) => {
+                if let Some(bits) = tag.strip_prefix('u').and_then(|bits| bits.parse().ok()) {
+                    return Some(Self::Unsigned { bits, value: s.parse().ok()? });
+                }
+                let bits = tag.strip_prefix('i')?.parse().ok()?;
+                Some(Self::Signed { bits, value: s.parse().ok()? })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// `-0x2a` for -42
+fn signed_hex(value: &BigInt) -> String {
+    let (sign, magnitude) = (value.sign(), value.magnitude());
+    if sign == num_bigint::Sign::Minus {
+        format!("-{:#x}", magnitude)
+    } else {
+        format!("{:#x}", magnitude)
+    }
+}
+
+/// An event decoded with its ABI: its type, its members in declaration order and, when the payload
+/// does not fit the ABI, what went wrong after the members decoded so far
+#[derive(Debug, Clone)]
+pub struct DecodedEvent {
+    pub event_type: String,
+    pub members: Vec<(String, DecodedValue)>,
+    pub error: Option<String>,
+    pub keys: Vec<Value>,
+    pub data: Vec<Value>,
+}
+
+impl DecodedEvent {
+    /// Members in `format`, with the problem (if any) as `_error` and the raw felts as `_keys` and `_raw_data`
+    pub fn render(&self, format: ValueFormat) -> Value {
+        self.to_object(|value| value.render(format))
+    }
+
+    /// Like `render`, with the members in their stored form
+    pub fn to_stored(&self) -> Value {
+        self.to_object(DecodedValue::to_stored)
+    }
+
+    fn to_object(&self, member: impl Fn(&DecodedValue) -> Value) -> Value {
+        let mut object: serde_json::Map<String, Value> = self.members.iter()
+            .map(|(name, value)| (name.clone(), member(value)))
+            .collect();
+        if let Some(error) = &self.error {
+            object.insert("_error".to_string(), Value::String(error.clone()));
+        }
+        object.insert("_keys".to_string(), Value::Array(self.keys.clone()));
+        object.insert("_raw_data".to_string(), Value::Array(self.data.clone()));
+        Value::Object(object)
+    }
+}
+
+/// Fields a stored event carries next to its members: the felts it was decoded from and, when decoding
+/// stopped early, why
+const RESERVED_FIELDS: [&str; 3] = ["_keys", "_raw_data", "_error"];
+
+pub fn is_reserved_field(name: &str) -> bool {
+    RESERVED_FIELDS.contains(&name)
+}
+
+/// Why a stored event could not be decoded completely; its members are those decoded before the failure
+pub fn decode_error(decoded: &Value) -> Option<String> {
+    decoded.get("_error").and_then(|e| e.as_str()).map(str::to_string)
+}
+
+/// Integer member of a stored event at a dot-separated path of member names, list indexes and variant
+/// names (e.g. "value", "payload.amount", "amounts.0" or "fee.Some"), read from its decoded type, so
+/// the answer does not depend on a `ValueFormat`. None when the member is missing or not an integer
+/// type; felts, addresses and text are not integers even when their digits would parse as one.
+pub fn integer_member(decoded: &Value, path: &str) -> Option<BigInt> {
+    let mut value = DecodedValue::from_stored(decoded);
+    for name in path.split('.') {
+        value = match value {
+            DecodedValue::Struct(members) => members.into_iter().find(|(member, _)| member == name)?.1,
+            DecodedValue::List(items) => items.into_iter().nth(name.parse().ok()?)?,
+            DecodedValue::Variant { name: variant, payload: Some(payload) } if variant == name => *payload,
+            _ => return None,
+        };
+    }
+    match value {
+        DecodedValue::Unsigned { value, .. } => Some(value.into()),
+        DecodedValue::Signed { value, .. } => Some(value),
+        _ => None,
+    }
+}
+
+/// The `data` of an event as returned by the API: the stored members written in `format`, without
+/// the reserved fields (see `decode_error`). `Raw` returns the felts the event was emitted with.
+pub fn render_event_data(decoded: &Value, raw_keys: &[String], raw_data: &[String], format: ValueFormat) -> Value {
+    if format == ValueFormat::Raw {
+        return serde_json::json!({ "keys": raw_keys, "data": raw_data });
+    }
+    let Value::Object(stored) = decoded else {
+        return Value::Object(serde_json::Map::new());
+    };
+
+    let data = stored.iter()
+        .filter(|(name, _)| !is_reserved_field(name))
+        .map(|(name, value)| (name.clone(), DecodedValue::from_stored(value).render(format)))
+        .collect();
+    Value::Object(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transfer() -> DecodedEvent {
+        DecodedEvent {
+            event_type: "Transfer".to_string(),
+            members: vec![
+                ("from".to_string(), DecodedValue::address("0x0049d3").unwrap()),
+                ("amount".to_string(), DecodedValue::Unsigned { bits: 256, value: BigUint::from(1000u32) }),
+                ("decimals".to_string(), DecodedValue::Unsigned { bits: 8, value: BigUint::from(18u8) }),
+                ("delta".to_string(), DecodedValue::Signed { bits: 64, value: BigInt::from(-42) }),
+                ("memo".to_string(), DecodedValue::felt("0x4142").unwrap()),
+                ("name".to_string(), DecodedValue::Text("0x10".to_string())),
+                ("status".to_string(), DecodedValue::Variant { name: "Done".to_string(), payload: None }),
+                ("fee".to_string(), DecodedValue::Variant {
+                    name: "Some".to_string(),
+                    payload: Some(Box::new(DecodedValue::List(vec![DecodedValue::Unsigned { bits: 32, value: BigUint::from(7u8) }]))),
+                }),
+            ],
+            error: None,
+            keys: vec![Value::from("0x99")],
+            data: vec![Value::from("0x3e8"), Value::from("0x0")],
+        }
+    }
+
+    #[test]
+    fn test_values_are_written_by_type_in_every_format() {
+        let stored = transfer().to_stored();
+        let keys = vec!["0x99".to_string()];
+        let data = vec!["0x3e8".to_string(), "0x0".to_string()];
+        let render = |format| render_event_data(&stored, &keys, &data, format);
+
+        let typed = render(ValueFormat::Typed);
+        let mut rendered = transfer().render(ValueFormat::Typed);
+        rendered.as_object_mut().unwrap().retain(|name, _| !is_reserved_field(name));
+        assert_eq!(typed, rendered);
+        assert_eq!(typed["from"], crate::database::Database::normalize_address("0x49d3"));
+        assert_eq!(typed["amount"], "1000");
+        assert_eq!(typed["decimals"], 18);
+        assert_eq!(typed["delta"], "-42");
+        assert_eq!(typed["memo"], "0x4142");
+        assert_eq!(typed["name"], "0x10"); // Text stays text, whatever it looks like
+        assert_eq!(typed["status"], "Done");
+        assert_eq!(typed["fee"], serde_json::json!({"Some": [7]}));
+
+        let hex = render(ValueFormat::Hex);
+        assert_eq!((&hex["amount"], &hex["decimals"], &hex["delta"]), (&"0x3e8".into(), &"0x12".into(), &"-0x2a".into()));
+        assert_eq!(hex["name"], "0x10");
+
+        let decimal = render(ValueFormat::DecimalString);
+        assert_eq!((&decimal["amount"], &decimal["decimals"], &decimal["memo"]), (&"1000".into(), &"18".into(), &"16706".into()));
+        assert_eq!(decimal["from"], typed["from"]);
+        assert_eq!(decimal["fee"], serde_json::json!({"Some": ["7"]}));
+
+        assert_eq!(render(ValueFormat::Raw), serde_json::json!({"keys": ["0x99"], "data": ["0x3e8", "0x0"]}));
+    }
+
+    #[test]
+    fn test_integer_members_are_read_by_type() {
+        let stored = transfer().to_stored();
+        assert_eq!(integer_member(&stored, "amount"), Some(BigInt::from(1000)));
+        assert_eq!(integer_member(&stored, "delta"), Some(BigInt::from(-42)));
+        assert_eq!(integer_member(&stored, "fee.Some.0"), Some(BigInt::from(7)));
+        // Felts, addresses and text are not integers, whatever their digits look like
+        assert_eq!(integer_member(&stored, "memo"), None);
+        assert_eq!(integer_member(&stored, "from"), None);
+        assert_eq!(integer_member(&stored, "name"), None);
+        assert_eq!(integer_member(&stored, "amount.low"), None);
+        assert_eq!(integer_member(&stored, "missing"), None);
+    }
+
+    #[test]
+    fn test_untagged_rows_are_read_back() {
+        let legacy = serde_json::json!({"value": 300, "big": 5000000000u64, "owner": "0x01", "label": "abc", "_keys": ["0x1"]});
+        let typed = render_event_data(&legacy, &[], &[], ValueFormat::Typed);
+        assert_eq!(typed, serde_json::json!({"value": 300, "big": "5000000000", "owner": "0x1", "label": "abc"}));
+        assert_eq!(render_event_data(&legacy, &[], &[], ValueFormat::Hex)["value"], "0x12c");
+
+        // Cairo allows members with a leading underscore; only the reserved fields are left out
+        let partial = serde_json::json!({"_owner": "0x01", "_keys": ["0x1"], "_raw_data": [], "_error": "data ended at member amount"});
+        assert_eq!(render_event_data(&partial, &[], &[], ValueFormat::Typed), serde_json::json!({"_owner": "0x1"}));
+        assert_eq!(decode_error(&partial).as_deref(), Some("data ended at member amount"));
+        assert_eq!(decode_error(&legacy), None);
+        assert_eq!(render_event_data(&Value::Null, &[], &[], ValueFormat::Typed), serde_json::json!({}));
+    }
+}
@@ -234,6 +234,7 @@ impl DeploymentService {
 pub fn validate_deployment_params(
     name: &str,
     network: &str,
+    valid_networks: &[&str],
 ) -> Result<(), String> {
     if name.trim().is_empty() {
         return Err("Deployment name cannot be empty".to_string());
@@ -243,7 +244,6 @@ pub fn validate_deployment_params(
         return Err("Deployment name cannot exceed 100 characters".to_string());
     }
     
-    let valid_networks = ["mainnet", "testnet", "devnet", "local"];
     if !valid_networks.contains(&network.to_lowercase().as_str()) {
         return Err(format!("Invalid network '{}'. Must be one of: {}", network, valid_networks.join(", ")));
     }
@@ -50,8 +50,14 @@ pub async fn get_deployment_schema(
         Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
     };
 
+    // Resolve contracts against the deployment's own network
+    let rpc = rpc.for_network(&deployment.network).map_err(|e| {
+        eprintln!("❌ Deployment {}: {}", deployment.id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
     // Create deployment context
-    let deployment_context = DeploymentContext::new(deployment, database);
+    let deployment_context = DeploymentContext::new(deployment, database, rpc.chain_id.clone());
     
     // Build deployment-specific schema
     let schema = build_deployment_schema(deployment_context, rpc, realtime_manager, billing_service);
@@ -6,11 +6,12 @@ use crate::database::{Database, DeploymentRecord};
 pub struct DeploymentContext {
     pub deployment: DeploymentRecord,
     pub database: Arc<Database>,
+    pub chain_id: String, // Chain of the deployment's network; rows of other chains are not its data
 }
 
 impl DeploymentContext {
-    pub fn new(deployment: DeploymentRecord, database: Arc<Database>) -> Self {
-        Self { deployment, database }
+    pub fn new(deployment: DeploymentRecord, database: Arc<Database>, chain_id: String) -> Self {
+        Self { deployment, database, chain_id }
     }
 
     /// Get all contract addresses associated with this deployment
@@ -0,0 +1,117 @@
+use async_graphql::{Context, Object, Result as GqlResult};
+use std::sync::Arc;
+
+use crate::abi_registry::{override_abi, parse_abi, proxy_implementation};
+use crate::database::{AbiOverrideRecord, Database};
+use crate::graphql::types::{AbiOverride, SetAbiOverrideInput};
+use crate::rpc_types::BlockId;
+use crate::starknet::RpcContext;
+
+#[derive(Default)]
+pub struct AbiOverrideQueryRoot;
+
+#[Object]
+impl AbiOverrideQueryRoot {
+    /// ABI overrides that apply to a deployment, oldest first
+    async fn abi_overrides(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(name = "deploymentId")] deployment_id: String,
+        #[graphql(name = "contractAddress")] contract_address: Option<String>,
+    ) -> GqlResult<Vec<AbiOverride>> {
+        let database = ctx.data::<Arc<Database>>()?;
+        let records = database.get_abi_overrides(Some(&deployment_id), contract_address.as_deref()).await
+            .map_err(|e| format!("Failed to fetch ABI overrides: {}", e))?;
+        Ok(records.into_iter().map(convert_abi_override_record_to_graphql).collect())
+    }
+
+    /// Class hash of the implementation behind a proxy contract, read from its latest storage on
+    /// `network` (the server's default network when omitted)
+    async fn proxy_implementation(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(name = "contractAddress")] contract_address: String,
+        network: Option<String>,
+    ) -> GqlResult<Option<String>> {
+        let rpc = ctx.data::<RpcContext>()?;
+        let rpc = match network {
+            Some(network) => rpc.for_network(&network)?,
+            None => rpc.clone(),
+        };
+        Ok(proxy_implementation(&rpc, &contract_address, BlockId::Latest).await?)
+    }
+}
+
+#[derive(Default)]
+pub struct AbiOverrideMutationRoot;
+
+#[Object]
+impl AbiOverrideMutationRoot {
+    /// Decode a contract of a deployment with an uploaded ABI or the ABI of a class. Applies to the
+    /// blocks indexed from now on; events already stored keep their decoding.
+    async fn set_abi_override(&self, ctx: &Context<'_>, input: SetAbiOverrideInput) -> GqlResult<AbiOverride> {
+        let database = ctx.data::<Arc<Database>>()?;
+        let rpc = ctx.data::<RpcContext>()?;
+
+        let deployment = database.get_deployment(&input.deployment_id).await
+            .map_err(|e| format!("Failed to verify deployment: {}", e))?
+            .ok_or_else(|| "Deployment not found".to_string())?;
+
+        let abi = match (input.abi, &input.class_hash) {
+            (Some(abi), None) => Some(parse_abi(&abi.to_string())?.to_string()),
+            (None, Some(_)) => None,
+            _ => return Err("Give either abi or classHash".into()),
+        };
+        let block = |block: &Option<String>| -> Result<Option<u64>, String> {
+            block.as_deref().map(|b| b.parse::<u64>().map_err(|_| format!("Invalid block number '{}'", b))).transpose()
+        };
+        let (from_block, to_block) = (block(&input.from_block)?, block(&input.to_block)?);
+        if let (Some(from), Some(to)) = (from_block, to_block) {
+            if from > to {
+                return Err("toBlock is before fromBlock".into());
+            }
+        }
+
+        let record = AbiOverrideRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            deployment_id: Some(input.deployment_id),
+            contract_address: Database::normalize_address(&input.contract_address),
+            abi,
+            class_hash: input.class_hash.as_deref().map(Database::normalize_address),
+            from_block,
+            to_block,
+            created_at: chrono::Utc::now(),
+        };
+        // A class hash must resolve to a class with an ABI on the deployment's network before it is stored
+        override_abi(&rpc.for_network(&deployment.network)?, &record).await?;
+
+        database.upsert_abi_override(&record).await
+            .map_err(|e| format!("Failed to store ABI override: {}", e))?;
+        Ok(convert_abi_override_record_to_graphql(record))
+    }
+
+    /// Remove an ABI override of a deployment; false if the deployment has none with this id
+    async fn remove_abi_override(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(name = "deploymentId")] deployment_id: String,
+        id: String,
+    ) -> GqlResult<bool> {
+        let database = ctx.data::<Arc<Database>>()?;
+        Ok(database.delete_abi_override(&id, &deployment_id).await
+            .map_err(|e| format!("Failed to delete ABI override: {}", e))?)
+    }
+}
+
+fn convert_abi_override_record_to_graphql(record: AbiOverrideRecord) -> AbiOverride {
+    AbiOverride {
+        id: record.id,
+        deployment_id: record.deployment_id,
+        contract_address: record.contract_address,
+        abi: record.abi.and_then(|abi| serde_json::from_str(&abi).ok()),
+        class_hash: record.class_hash,
+        from_block: record.from_block.map(|b| b.to_string()),
+        to_block: record.to_block.map(|b| b.to_string()),
+        created_at: record.created_at.to_rfc3339(),
+    }
+}
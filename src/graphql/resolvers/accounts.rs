@@ -0,0 +1,72 @@
+use async_graphql::{Context, Object, Result as GqlResult};
+use std::sync::Arc;
+
+use crate::database::{AccountEventRecord, Database};
+use crate::decoded_value::{decode_error, render_event_data};
+use crate::graphql::types::{AccountEvent, ValueFormat};
+
+#[derive(Default)]
+pub struct AccountQueryRoot;
+
+#[Object]
+impl AccountQueryRoot {
+    /// Events from any contract that mention a watched account (requires --watch-accounts):
+    /// - Filter by emitting contract, e.g. a single token
+    /// - Filter by event type, e.g. "Transfer"
+    /// - Value format: valueFormat, as for the events query
+    #[allow(clippy::too_many_arguments)]
+    async fn account_events(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(name = "accountAddress")] account_address: String,
+        #[graphql(name = "contractAddress")] contract_address: Option<String>,
+        #[graphql(name = "eventType")] event_type: Option<String>,
+        #[graphql(name = "fromBlock")] from_block: Option<String>,
+        #[graphql(name = "toBlock")] to_block: Option<String>,
+        first: Option<i32>,
+        after: Option<String>,
+        #[graphql(name = "valueFormat")] value_format: Option<ValueFormat>,
+    ) -> GqlResult<Vec<AccountEvent>> {
+        let database = ctx.data::<Arc<Database>>()?.clone();
+        let limit = first.unwrap_or(20).clamp(1, 100);
+        let offset = after.as_ref()
+            .and_then(|cursor| cursor.parse::<i32>().ok())
+            .unwrap_or(0);
+
+        let events = database.get_account_events(
+            &account_address,
+            contract_address.as_deref(),
+            event_type.as_deref(),
+            from_block.as_ref().and_then(|s| s.parse::<u64>().ok()),
+            to_block.as_ref().and_then(|s| s.parse::<u64>().ok()),
+            limit,
+            offset,
+        ).await.map_err(|e| async_graphql::Error::new(format!("Database error: {}", e)))?;
+
+        let value_format = value_format.unwrap_or_default();
+        Ok(events.into_iter().map(|record| convert_account_event_record_to_graphql(record, value_format)).collect())
+    }
+}
+
+fn convert_account_event_record_to_graphql(record: AccountEventRecord, value_format: ValueFormat) -> AccountEvent {
+    let raw_data: Vec<String> = serde_json::from_str(&record.raw_data).unwrap_or_default();
+    let raw_keys: Vec<String> = serde_json::from_str(&record.raw_keys).unwrap_or_default();
+    let decoded: Option<serde_json::Value> = record.decoded_data.map(|d| serde_json::from_str(&d).unwrap_or_default());
+    let data = decoded.as_ref().map(|decoded| render_event_data(decoded, &raw_keys, &raw_data, value_format));
+    AccountEvent {
+        id: record.id,
+        account_address: record.account_address,
+        key_position: record.key_position,
+        contract_address: record.contract_address,
+        event_type: record.event_type,
+        event_selector: record.event_selector,
+        block_number: record.block_number.to_string(),
+        transaction_hash: record.transaction_hash,
+        timestamp: record.timestamp.to_rfc3339(),
+        chain_id: record.chain_id,
+        data,
+        raw_data,
+        raw_keys,
+        decode_error: decoded.as_ref().and_then(decode_error),
+    }
+}
@@ -0,0 +1,71 @@
+use async_graphql::{Context, Object, Result as GqlResult};
+use std::sync::Arc;
+
+use crate::database::{Database, InternalCallRecord};
+use crate::graphql::types::InternalCall;
+
+#[derive(Default)]
+pub struct CallQueryRoot;
+
+#[Object]
+impl CallQueryRoot {
+    /// Calls into a contract recorded from transaction traces (requires --trace-calls):
+    /// - Filter by caller to see which contracts interact with it
+    /// - Filter by function name, e.g. "transfer_from"
+    /// - Filter by chain, e.g. "SN_MAIN"
+    #[allow(clippy::too_many_arguments)]
+    async fn internal_calls(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(name = "contractAddress")] contract_address: String,
+        #[graphql(name = "chainId")] chain_id: Option<String>,
+        #[graphql(name = "callerAddress")] caller_address: Option<String>,
+        #[graphql(name = "functionName")] function_name: Option<String>,
+        #[graphql(name = "fromBlock")] from_block: Option<String>,
+        #[graphql(name = "toBlock")] to_block: Option<String>,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> GqlResult<Vec<InternalCall>> {
+        let database = ctx.data::<Arc<Database>>()?.clone();
+        let limit = first.unwrap_or(20).clamp(1, 100);
+        let offset = after.as_ref()
+            .and_then(|cursor| cursor.parse::<i32>().ok())
+            .unwrap_or(0);
+
+        let calls = database.get_internal_calls(
+            &contract_address,
+            chain_id.as_deref(),
+            caller_address.as_deref(),
+            function_name.as_deref(),
+            from_block.as_ref().and_then(|s| s.parse::<u64>().ok()),
+            to_block.as_ref().and_then(|s| s.parse::<u64>().ok()),
+            limit,
+            offset,
+        ).await.map_err(|e| async_graphql::Error::new(format!("Database error: {}", e)))?;
+
+        Ok(calls.into_iter().map(convert_internal_call_record_to_graphql).collect())
+    }
+}
+
+/// Helper function to convert database record to GraphQL type
+fn convert_internal_call_record_to_graphql(record: InternalCallRecord) -> InternalCall {
+    InternalCall {
+        id: record.id,
+        contract_address: record.contract_address,
+        caller_address: record.caller_address,
+        block_number: record.block_number.to_string(),
+        transaction_hash: record.transaction_hash,
+        call_index: record.call_index,
+        depth: record.depth,
+        phase: record.phase,
+        call_type: record.call_type,
+        entry_point_type: record.entry_point_type,
+        entry_point_selector: record.entry_point_selector,
+        function_name: record.function_name,
+        inputs: record.decoded_inputs.and_then(|d| serde_json::from_str(&d).ok()),
+        outputs: record.decoded_outputs.and_then(|d| serde_json::from_str(&d).ok()),
+        calldata: serde_json::from_str(&record.calldata).unwrap_or_default(),
+        result: serde_json::from_str(&record.result).unwrap_or_default(),
+        chain_id: record.chain_id,
+    }
+}
@@ -0,0 +1,95 @@
+use async_graphql::{Context, Object, Result as GqlResult};
+use std::sync::Arc;
+
+use crate::database::{ClassDeclarationRecord, ContractDeploymentRecord, Database};
+use crate::graphql::types::{DeclaredClass, DeployedContract};
+
+#[derive(Default)]
+pub struct ClassQueryRoot;
+
+#[Object]
+impl ClassQueryRoot {
+    /// Contracts deployed from watched class hashes (requires --watch-class-hashes)
+    #[allow(clippy::too_many_arguments)]
+    async fn deployed_contracts(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(name = "chainId")] chain_id: Option<String>,
+        #[graphql(name = "classHash")] class_hash: Option<String>,
+        #[graphql(name = "deployerAddress")] deployer_address: Option<String>,
+        #[graphql(name = "fromBlock")] from_block: Option<String>,
+        #[graphql(name = "toBlock")] to_block: Option<String>,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> GqlResult<Vec<DeployedContract>> {
+        let database = ctx.data::<Arc<Database>>()?.clone();
+        let limit = first.unwrap_or(20).clamp(1, 100);
+        let offset = after.as_ref()
+            .and_then(|cursor| cursor.parse::<i32>().ok())
+            .unwrap_or(0);
+
+        let deployments = database.get_contract_deployments(
+            chain_id.as_deref(),
+            class_hash.as_deref(),
+            deployer_address.as_deref(),
+            from_block.as_ref().and_then(|s| s.parse::<u64>().ok()),
+            to_block.as_ref().and_then(|s| s.parse::<u64>().ok()),
+            limit,
+            offset,
+        ).await.map_err(|e| async_graphql::Error::new(format!("Database error: {}", e)))?;
+
+        Ok(deployments.into_iter().map(convert_deployment_record_to_graphql).collect())
+    }
+
+    /// Classes declared by watched accounts (requires --watch-declarers)
+    async fn declared_classes(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(name = "chainId")] chain_id: Option<String>,
+        #[graphql(name = "senderAddress")] sender_address: Option<String>,
+        #[graphql(name = "classHash")] class_hash: Option<String>,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> GqlResult<Vec<DeclaredClass>> {
+        let database = ctx.data::<Arc<Database>>()?.clone();
+        let limit = first.unwrap_or(20).clamp(1, 100);
+        let offset = after.as_ref()
+            .and_then(|cursor| cursor.parse::<i32>().ok())
+            .unwrap_or(0);
+
+        let declarations = database.get_class_declarations(
+            chain_id.as_deref(),
+            sender_address.as_deref(),
+            class_hash.as_deref(),
+            limit,
+            offset,
+        ).await.map_err(|e| async_graphql::Error::new(format!("Database error: {}", e)))?;
+
+        Ok(declarations.into_iter().map(convert_declaration_record_to_graphql).collect())
+    }
+}
+
+fn convert_deployment_record_to_graphql(record: ContractDeploymentRecord) -> DeployedContract {
+    DeployedContract {
+        contract_address: record.contract_address,
+        class_hash: record.class_hash,
+        deployer_address: record.deployer_address,
+        transaction_hash: record.transaction_hash,
+        deployment_type: record.deployment_type,
+        block_number: record.block_number.to_string(),
+        timestamp: record.timestamp.to_rfc3339(),
+        chain_id: record.chain_id,
+    }
+}
+
+fn convert_declaration_record_to_graphql(record: ClassDeclarationRecord) -> DeclaredClass {
+    DeclaredClass {
+        class_hash: record.class_hash,
+        compiled_class_hash: record.compiled_class_hash,
+        sender_address: record.sender_address,
+        transaction_hash: record.transaction_hash,
+        block_number: record.block_number.to_string(),
+        timestamp: record.timestamp.to_rfc3339(),
+        chain_id: record.chain_id,
+    }
+}
@@ -94,7 +94,7 @@ impl DeploymentContractQueryRoot {
             
         let mut total_events = 0i64;
         for addr in &contract_addresses {
-            let count = database.count_events(addr, None).await
+            let count = database.count_events(addr, Some(&deployment_context.chain_id), None).await
                 .map_err(|e| format!("Failed to count events for {}: {}", addr, e))?;
             total_events += count;
         }
@@ -1,7 +1,8 @@
 use async_graphql::{Context, Object, Result as GqlResult, InputObject};
 
 use crate::database::EventRecord;
-use crate::graphql::types::{Event, EventConnection, EventEdge, PageInfo, EventOrderBy};
+use crate::decoded_value::{decode_error, render_event_data};
+use crate::graphql::types::{Event, EventConnection, EventEdge, PageInfo, EventOrderBy, ValueFormat};
 use crate::graphql::deployment_context::DeploymentContext;
 
 /// Input type for deployment-specific event queries
@@ -23,7 +24,7 @@ pub struct DeploymentEventQueryRoot;
 
 #[Object]
 impl DeploymentEventQueryRoot {
-    /// Get events for this specific deployment
+    /// Get events for this specific deployment, decoded values written in `valueFormat` (default TYPED)
     async fn events(
         &self,
         ctx: &Context<'_>,
@@ -31,6 +32,7 @@ impl DeploymentEventQueryRoot {
         first: Option<i32>,
         after: Option<String>,
         order_by: Option<EventOrderBy>,
+        value_format: Option<ValueFormat>,
     ) -> GqlResult<EventConnection> {
         let deployment_context = ctx.data::<DeploymentContext>()?;
         let database = deployment_context.get_database();
@@ -80,6 +82,7 @@ impl DeploymentEventQueryRoot {
         for contract_address in &contract_addresses {
             let events = database.get_events_with_advanced_filters(
                 contract_address,
+                Some(&deployment_context.chain_id),
                 event_types,
                 event_keys,
                 from_block,
@@ -92,7 +95,7 @@ impl DeploymentEventQueryRoot {
                 order_by,
             ).await.map_err(|e| format!("Failed to fetch events: {}", e))?;
 
-            let count = database.count_events(contract_address, event_types).await
+            let count = database.count_events(contract_address, Some(&deployment_context.chain_id), event_types).await
                 .map_err(|e| format!("Failed to count events: {}", e))?;
 
             all_events.extend(events);
@@ -120,7 +123,7 @@ impl DeploymentEventQueryRoot {
             .map(|(index, record)| {
                 let cursor = (offset + index as i32).to_string();
                 EventEdge {
-                    node: convert_event_record_to_graphql(record),
+                    node: convert_event_record_to_graphql(record, value_format.unwrap_or_default()),
                     cursor: cursor.clone(),
                 }
             })
@@ -141,7 +144,7 @@ impl DeploymentEventQueryRoot {
     }
 
     /// Get a single event by ID (only if it belongs to this deployment)
-    async fn event(&self, ctx: &Context<'_>, id: String) -> GqlResult<Option<Event>> {
+    async fn event(&self, ctx: &Context<'_>, id: String, value_format: Option<ValueFormat>) -> GqlResult<Option<Event>> {
         let deployment_context = ctx.data::<DeploymentContext>()?;
         let database = deployment_context.get_database();
         
@@ -161,12 +164,12 @@ impl DeploymentEventQueryRoot {
         }
 
         // Get all events for this contract and find the one with matching ID
-        let events = database.get_events(contract_address, None, None, None, 1000, 0).await
+        let events = database.get_events(contract_address, Some(&deployment_context.chain_id), None, None, None, 1000, 0).await
             .map_err(|e| format!("Failed to fetch events: {}", e))?;
             
         for event in events {
             if event.id == id {
-                return Ok(Some(convert_event_record_to_graphql(event)));
+                return Ok(Some(convert_event_record_to_graphql(event, value_format.unwrap_or_default())));
             }
         }
         
@@ -175,10 +178,11 @@ impl DeploymentEventQueryRoot {
 }
 
 /// Helper function to convert database record to GraphQL type
-fn convert_event_record_to_graphql(record: EventRecord) -> Event {
-    let data = record.decoded_data.and_then(|d| serde_json::from_str(&d).ok());
+fn convert_event_record_to_graphql(record: EventRecord, value_format: ValueFormat) -> Event {
     let raw_data: Vec<String> = serde_json::from_str(&record.raw_data).unwrap_or_default();
     let raw_keys: Vec<String> = serde_json::from_str(&record.raw_keys).unwrap_or_default();
+    let decoded: Option<serde_json::Value> = record.decoded_data.map(|d| serde_json::from_str(&d).unwrap_or_default());
+    let data = decoded.as_ref().map(|decoded| render_event_data(decoded, &raw_keys, &raw_data, value_format));
 
     Event {
         id: record.id,
@@ -191,5 +195,8 @@ fn convert_event_record_to_graphql(record: EventRecord) -> Event {
         data,
         raw_data,
         raw_keys,
+        chain_id: record.chain_id,
+        decode_error: decoded.as_ref().and_then(decode_error),
+        decoded,
     }
 }
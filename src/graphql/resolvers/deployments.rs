@@ -3,6 +3,7 @@ use std::sync::Arc;
 
 use crate::database::{Database, DeploymentRecord};
 use crate::deployment_service::{DeploymentService, validate_deployment_params};
+use crate::starknet::RpcContext;
 use crate::graphql::types::{
     Deployment, DeploymentConnection, DeploymentEdge, PageInfo, 
     CreateDeploymentInput, UpdateDeploymentInput, DeploymentFilter, DeploymentStatus,
@@ -96,9 +97,10 @@ impl DeploymentMutationRoot {
         input: CreateDeploymentInput,
     ) -> FieldResult<Deployment> {
         let database = ctx.data::<Arc<Database>>()?;
+        let rpc = ctx.data::<RpcContext>()?;
         
         // Validate input parameters
-        validate_deployment_params(&input.name, &input.network)
+        validate_deployment_params(&input.name, &input.network, &rpc.networks.names())
             .map_err(|e| format!("Invalid deployment parameters: {}", e))?;
         
         // Create deployment service
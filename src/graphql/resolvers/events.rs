@@ -4,131 +4,12 @@ use std::sync::Arc;
 use crate::database::Database;
 use crate::billing::BillingService;
 use crate::billing_context::BillingContext;
-use crate::graphql::types::{Event, EventConnection, EventEdge, PageInfo};
-
-fn convert_felt_to_string(felt_hex: &str) -> serde_json::Value {
-    // Remove 0x prefix if present
-    let hex_str = felt_hex.trim_start_matches("0x");
-    
-    // Handle special cases first - all F's means max value
-    if hex_str == "ffffffffffffffffffffffffffffffff" || hex_str.chars().all(|c| c == 'f' || c == 'F') {
-        // This is likely a max value, convert to decimal
-        if let Ok(num) = u128::from_str_radix(hex_str, 16) {
-            if num <= u64::MAX as u128 {
-                return serde_json::Value::Number((num as u64).into());
-            } else {
-                return serde_json::Value::String(num.to_string());
-            }
-        }
-    }
-    
-    // Try to decode as UTF-8 string first
-    if hex_str.len() % 2 == 0 && hex_str.len() <= 64 { // Reasonable length for string
-        if let Ok(bytes) = hex::decode(hex_str) {
-            // Remove trailing zeros
-            let trimmed_bytes: Vec<u8> = bytes.into_iter()
-                .rev()
-                .skip_while(|&b| b == 0)
-                .collect::<Vec<_>>()
-                .into_iter()
-                .rev()
-                .collect();
-            
-            // Try to convert to UTF-8 string
-            if let Ok(utf8_string) = String::from_utf8(trimmed_bytes.clone()) {
-                // Check if it's a readable string (printable ASCII or valid UTF-8)
-                // Allow alphanumeric, spaces, and common punctuation
-                if !utf8_string.is_empty() && 
-                   utf8_string.chars().all(|c| c.is_ascii_alphanumeric() || 
-                                          c.is_ascii_punctuation() || 
-                                          c.is_whitespace()) &&
-                   utf8_string.len() > 1 { // Avoid single character strings from random hex
-                    return serde_json::Value::String(utf8_string);
-                }
-            }
-        }
-    }
-    
-    // Try to parse as number
-    if let Ok(num) = u128::from_str_radix(hex_str, 16) {
-        // If it's a reasonable number, return as decimal
-        if num <= u64::MAX as u128 {
-            serde_json::Value::Number((num as u64).into())
-        } else {
-            // For very large numbers, return as decimal string
-            serde_json::Value::String(num.to_string())
-        }
-    } else {
-        // Fallback to original hex value
-        serde_json::Value::String(felt_hex.to_string())
-    }
-}
+use crate::decoded_value::{decode_error, render_event_data};
+use crate::graphql::types::{Event, EventConnection, EventEdge, PageInfo, ValueFormat};
 
 #[derive(Default)]
 pub struct EventQueryRoot;
 
-fn convert_decoded_data_to_clean_format(decoded_json: &str) -> serde_json::Value {
-    if let Ok(decoded) = serde_json::from_str::<serde_json::Value>(decoded_json) {
-        if let Some(obj) = decoded.as_object() {
-            let mut clean_data = serde_json::Map::new();
-            
-            // Check if this is the old format with only _keys (backward compatibility)
-            if obj.len() == 1 && obj.contains_key("_keys") {
-                if let Some(keys_array) = obj.get("_keys").and_then(|k| k.as_array()) {
-                    // For events like U8Event, the structure is typically:
-                    // [event_selector, variant_selector, actual_value]
-                    if keys_array.len() >= 3 {
-                        // Extract the actual value (last element in most cases)
-                        if let Some(value_key) = keys_array.last() {
-                            if let Some(value_str) = value_key.as_str() {
-                                // Convert felt252 hex values to readable strings
-                                let clean_value = convert_felt_to_string(value_str);
-                                clean_data.insert("value".to_string(), clean_value);
-                            }
-                        }
-                    }
-                }
-            } else {
-                // New format - process normally, extracting clean values
-                for (key, value) in obj {
-                    // Skip internal fields that start with underscore
-                    if key.starts_with('_') {
-                        continue;
-                    }
-                    
-                    let clean_value = match value {
-                        serde_json::Value::Object(nested) => {
-                            // Extract the most relevant value from structured decoded values
-                            if let Some(decoded_val) = nested.get("value") {
-                                decoded_val.clone()
-                            } else if let Some(decimal_val) = nested.get("decimal") {
-                                decimal_val.clone()
-                            } else if let Some(address_val) = nested.get("address") {
-                                address_val.clone()
-                            } else {
-                                value.clone()
-                            }
-                        },
-                        serde_json::Value::String(hex_str) => {
-                            // Convert felt252 hex values to readable strings
-                            convert_felt_to_string(hex_str)
-                        },
-                        _ => value.clone()
-                    };
-                    
-                    clean_data.insert(key.clone(), clean_value);
-                }
-            }
-            
-            serde_json::Value::Object(clean_data)
-        } else {
-            serde_json::Value::Object(serde_json::Map::new())
-        }
-    } else {
-        serde_json::Value::Object(serde_json::Map::new())
-    }
-}
-
 #[Object]
 impl EventQueryRoot {
     /// Universal events query that handles all use cases:
@@ -137,6 +18,9 @@ impl EventQueryRoot {
     /// - Advanced filtering: eventTypes, eventKeys, blocks, timestamps
     /// - Custom ordering: orderBy parameter
     /// - Pagination: first, after parameters
+    /// - Value format: valueFormat (TYPED by default, or HEX, DECIMAL_STRING, RAW)
+    /// - Chain: chainId, e.g. "SN_MAIN", when one database holds several networks
+    #[allow(clippy::too_many_arguments)]
     async fn events(
         &self,
         ctx: &Context<'_>,
@@ -144,6 +28,7 @@ impl EventQueryRoot {
         // Contract filtering - supports single contract or multiple contracts
         #[graphql(name = "contractAddress")] contract_address: Option<String>,
         #[graphql(name = "contractAddresses")] contract_addresses: Option<Vec<String>>,
+        #[graphql(name = "chainId")] chain_id: Option<String>,
         
         // Block filtering
         #[graphql(name = "fromBlock")] from_block: Option<String>,
@@ -164,6 +49,7 @@ impl EventQueryRoot {
         first: Option<i32>,
         after: Option<String>,
         #[graphql(name = "orderBy")] order_by: Option<crate::graphql::types::EventOrderBy>,
+        #[graphql(name = "valueFormat")] value_format: Option<ValueFormat>,
     ) -> GqlResult<EventConnection> {
         let database = ctx.data::<Arc<Database>>()?.clone();
         let billing_service = ctx.data::<Arc<BillingService>>()?.clone();
@@ -211,6 +97,7 @@ impl EventQueryRoot {
         for contract_addr in &target_contracts {
             let db_events = database.get_events_with_advanced_filters(
                 contract_addr,
+                chain_id.as_deref(),
                 event_types.as_ref().map(|v| v.as_slice()),
                 event_keys.as_ref().map(|v| v.as_slice()),
                 from_block_num,
@@ -226,6 +113,7 @@ impl EventQueryRoot {
             // Get total count for this contract
             let contract_total_count = database.count_events(
                 contract_addr,
+                chain_id.as_deref(),
                 event_types.as_ref().map(|v| v.as_slice()),
             ).await.map_err(|e| async_graphql::Error::new(format!("Database error for contract {}: {}", contract_addr, e)))? as i32;
             
@@ -272,6 +160,8 @@ impl EventQueryRoot {
                 .unwrap_or_default();
             let raw_keys: Vec<String> = serde_json::from_str(&db_event.raw_keys)
                 .unwrap_or_default();
+            let decoded: Option<serde_json::Value> = db_event.decoded_data.as_ref()
+                .map(|json| serde_json::from_str(json).unwrap_or_default());
 
             let event = Event {
                 id: db_event.id.clone(),
@@ -281,9 +171,12 @@ impl EventQueryRoot {
                 transaction_hash: db_event.transaction_hash.clone(),
                 log_index: db_event.log_index,
                 timestamp: db_event.timestamp.to_rfc3339(),
-                data: db_event.decoded_data.as_ref().map(|json| convert_decoded_data_to_clean_format(json)),
+                data: decoded.as_ref().map(|decoded| render_event_data(decoded, &raw_keys, &raw_data, value_format.unwrap_or_default())),
                 raw_data,
                 raw_keys,
+                chain_id: db_event.chain_id.clone(),
+                decode_error: decoded.as_ref().and_then(decode_error),
+                decoded,
             };
             
             let cursor = (offset + idx as i32 + 1).to_string();
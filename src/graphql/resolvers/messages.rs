@@ -0,0 +1,72 @@
+use async_graphql::{Context, Object, Result as GqlResult};
+use std::sync::Arc;
+
+use crate::database::{Database, MessageRecord};
+use crate::graphql::types::Message;
+
+#[derive(Default)]
+pub struct MessageQueryRoot;
+
+#[Object]
+impl MessageQueryRoot {
+    /// L1<->L2 messages of indexed contracts (requires --index-messages):
+    /// - direction "L2_TO_L1" for messages_sent, "L1_TO_L2" for L1 handler transactions
+    /// - Look up the L1 handler of a deposit by its L1 message hash
+    /// - Filter by chain, e.g. "SN_MAIN"
+    #[allow(clippy::too_many_arguments)]
+    async fn messages(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(name = "contractAddress")] contract_address: Option<String>,
+        #[graphql(name = "chainId")] chain_id: Option<String>,
+        direction: Option<String>,
+        #[graphql(name = "messageHash")] message_hash: Option<String>,
+        #[graphql(name = "fromBlock")] from_block: Option<String>,
+        #[graphql(name = "toBlock")] to_block: Option<String>,
+        first: Option<i32>,
+        after: Option<String>,
+    ) -> GqlResult<Vec<Message>> {
+        let database = ctx.data::<Arc<Database>>()?.clone();
+        let limit = first.unwrap_or(20).clamp(1, 100);
+        let offset = after.as_ref()
+            .and_then(|cursor| cursor.parse::<i32>().ok())
+            .unwrap_or(0);
+
+        if let Some(direction) = &direction {
+            if direction != "L2_TO_L1" && direction != "L1_TO_L2" {
+                return Err(async_graphql::Error::new("direction must be L2_TO_L1 or L1_TO_L2"));
+            }
+        }
+
+        let messages = database.get_messages(
+            contract_address.as_deref(),
+            chain_id.as_deref(),
+            direction.as_deref(),
+            message_hash.as_deref(),
+            from_block.as_ref().and_then(|s| s.parse::<u64>().ok()),
+            to_block.as_ref().and_then(|s| s.parse::<u64>().ok()),
+            limit,
+            offset,
+        ).await.map_err(|e| async_graphql::Error::new(format!("Database error: {}", e)))?;
+
+        Ok(messages.into_iter().map(convert_message_record_to_graphql).collect())
+    }
+}
+
+fn convert_message_record_to_graphql(record: MessageRecord) -> Message {
+    Message {
+        id: record.id,
+        contract_address: record.contract_address,
+        direction: record.direction,
+        from_address: record.from_address,
+        to_address: record.to_address,
+        payload: serde_json::from_str(&record.payload).unwrap_or_default(),
+        message_hash: record.message_hash,
+        nonce: record.nonce,
+        entry_point_selector: record.entry_point_selector,
+        transaction_hash: record.transaction_hash,
+        block_number: record.block_number.to_string(),
+        timestamp: record.timestamp.to_rfc3339(),
+        chain_id: record.chain_id,
+    }
+}
@@ -1,9 +1,14 @@
 pub mod billing;
 pub mod events;
+pub mod accounts;
+pub mod calls;
+pub mod classes;
+pub mod messages;
 pub mod contracts;
 pub mod subscriptions;
 pub mod deployments;
 pub mod deployment_events;
 pub mod deployment_contracts;
 pub mod api_keys;
+pub mod abi_overrides;
 
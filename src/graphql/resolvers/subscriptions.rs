@@ -4,7 +4,8 @@ use futures::stream::BoxStream;
 use tokio_stream::wrappers::BroadcastStream;
 use std::sync::Arc;
 
-use crate::graphql::types::Event;
+use crate::decoded_value::render_event_data;
+use crate::graphql::types::{Event, ValueFormat};
 use crate::realtime::{RealtimeEventManager, SubscriptionFilter};
 pub struct SubscriptionRoot;
 
@@ -15,6 +16,7 @@ impl SubscriptionRoot {
     /// - Multiple contracts: provide contractAddresses  
     /// - Event filtering: eventTypes, eventKeys
     /// - Real-time updates: automatically streams new events
+    /// - Value format: valueFormat, as for the events query
     async fn events(
         &self,
         ctx: &Context<'_>,
@@ -25,6 +27,7 @@ impl SubscriptionRoot {
         // Event filtering
         #[graphql(name = "eventTypes")] event_types: Option<Vec<String>>,
         #[graphql(name = "eventKeys")] event_keys: Option<Vec<String>>,
+        #[graphql(name = "valueFormat")] value_format: Option<ValueFormat>,
     ) -> Result<BoxStream<'static, Event>, async_graphql::Error> {
         let realtime_manager = ctx.data_unchecked::<Arc<RealtimeEventManager>>();
         
@@ -50,10 +53,15 @@ impl SubscriptionRoot {
         let (_subscription_id, receiver) = realtime_manager.subscribe(filter).await;
         
         // Create a stream from the broadcast receiver
+        let value_format = value_format.unwrap_or_default();
         let stream = BroadcastStream::new(receiver)
-            .filter_map(|result| async move {
+            .filter_map(move |result| async move {
                 match result {
-                    Ok(event) => Some(event),
+                    // Events are broadcast with their stored data, written here in the subscriber's format
+                    Ok(mut event) => {
+                        event.data = event.data.map(|decoded| render_event_data(&decoded, &event.raw_keys, &event.raw_data, value_format));
+                        Some(event)
+                    }
                     Err(_) => None, // Ignore broadcast errors
                 }
             })
@@ -6,17 +6,22 @@ use crate::billing::BillingService;
 use crate::graphql::resolvers::billing::BillingQueryRoot;
 use crate::graphql::resolvers::contracts::ContractQueryRoot;
 use crate::graphql::resolvers::events::EventQueryRoot;
+use crate::graphql::resolvers::accounts::AccountQueryRoot;
+use crate::graphql::resolvers::calls::CallQueryRoot;
+use crate::graphql::resolvers::classes::ClassQueryRoot;
+use crate::graphql::resolvers::messages::MessageQueryRoot;
 use crate::graphql::resolvers::deployments::{DeploymentQueryRoot, DeploymentMutationRoot, DeploymentContractQueryRoot, DeploymentContractMutationRoot};
 use crate::graphql::resolvers::api_keys::{ApiKeyQueryRoot, ApiKeyMutationRoot};
+use crate::graphql::resolvers::abi_overrides::{AbiOverrideQueryRoot, AbiOverrideMutationRoot};
 use crate::graphql::resolvers::subscriptions::SubscriptionRoot;
 use crate::starknet::RpcContext;
 use crate::realtime::RealtimeEventManager;
 
 #[derive(MergedObject, Default)]
-pub struct QueryRoot(EventQueryRoot, ContractQueryRoot, DeploymentQueryRoot, DeploymentContractQueryRoot, BillingQueryRoot, ApiKeyQueryRoot);
+pub struct QueryRoot(EventQueryRoot, AccountQueryRoot, CallQueryRoot, MessageQueryRoot, ClassQueryRoot, ContractQueryRoot, DeploymentQueryRoot, DeploymentContractQueryRoot, BillingQueryRoot, ApiKeyQueryRoot, AbiOverrideQueryRoot);
 
 #[derive(MergedObject, Default)]
-pub struct MutationRoot(DeploymentMutationRoot, DeploymentContractMutationRoot, ApiKeyMutationRoot);
+pub struct MutationRoot(DeploymentMutationRoot, DeploymentContractMutationRoot, ApiKeyMutationRoot, AbiOverrideMutationRoot);
 
 pub type AppSchema = Schema<QueryRoot, MutationRoot, SubscriptionRoot>;
 
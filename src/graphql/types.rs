@@ -1,8 +1,46 @@
-use async_graphql::{SimpleObject, InputObject, Enum};
+use async_graphql::{ComplexObject, Enum, InputObject, InputValueError, InputValueResult, Scalar, ScalarType, SimpleObject};
 use serde_json;
 
+/// Arbitrary-precision integer such as a u256 token amount. Output as a decimal string so clients keep
+/// every digit; input may be a decimal or 0x-prefixed hex string, or an integer literal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigInt(pub num_bigint::BigInt);
+
+impl BigInt {
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let (negative, digits) = match value.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, value),
+        };
+        let magnitude = match digits.strip_prefix("0x") {
+            Some(hex) => num_bigint::BigInt::parse_bytes(hex.as_bytes(), 16)?,
+            None if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) => num_bigint::BigInt::parse_bytes(digits.as_bytes(), 10)?,
+            None => return None,
+        };
+        Some(BigInt(if negative { -magnitude } else { magnitude }))
+    }
+}
+
+#[Scalar(name = "BigInt")]
+impl ScalarType for BigInt {
+    fn parse(value: async_graphql::Value) -> InputValueResult<Self> {
+        match &value {
+            async_graphql::Value::String(s) => BigInt::parse(s).ok_or_else(|| InputValueError::custom(format!("'{}' is not an integer", s))),
+            async_graphql::Value::Number(n) => n.as_i64().map(|n| BigInt(n.into()))
+                .or_else(|| n.as_u64().map(|n| BigInt(n.into())))
+                .ok_or_else(|| InputValueError::custom(format!("{} is not an integer", n))),
+            _ => Err(InputValueError::expected_type(value)),
+        }
+    }
+
+    fn to_value(&self) -> async_graphql::Value {
+        async_graphql::Value::String(self.0.to_string())
+    }
+}
+
 #[derive(SimpleObject, Clone)]
-#[graphql(rename_fields = "camelCase")]
+#[graphql(rename_fields = "camelCase", complex)]
 pub struct Event {
     pub id: String,
     pub contract_address: String,
@@ -14,6 +52,111 @@ pub struct Event {
     pub data: Option<serde_json::Value>, // Flattened data structure
     pub raw_data: Vec<String>,
     pub raw_keys: Vec<String>,
+    pub chain_id: Option<String>, // Null for events indexed before chain ids were recorded
+    /// Why the event could not be decoded completely; `data` then holds the members decoded before
+    pub decode_error: Option<String>,
+    #[graphql(skip)]
+    pub decoded: Option<serde_json::Value>, // Stored form of `data`, see `DecodedValue::to_stored`
+}
+
+#[ComplexObject]
+impl Event {
+    /// Integer member at a dot-separated path of member names and list indexes (e.g. "value",
+    /// "payload.amount" or "amounts.0"), with full precision whatever the `valueFormat`. A u256 is one
+    /// member. Null when the member is missing or not declared as an integer type.
+    async fn big_int(&self, field: String) -> Option<BigInt> {
+        crate::decoded_value::integer_member(self.decoded.as_ref()?, &field).map(BigInt)
+    }
+}
+
+/// A call into an indexed contract recovered from a transaction trace
+#[derive(SimpleObject, Clone)]
+#[graphql(rename_fields = "camelCase")]
+pub struct InternalCall {
+    pub id: String,
+    pub contract_address: String,
+    pub caller_address: String,
+    pub block_number: String,
+    pub transaction_hash: String,
+    pub call_index: i32,
+    pub depth: i32,
+    pub phase: String,
+    pub call_type: String,
+    pub entry_point_type: String,
+    pub entry_point_selector: String,
+    pub function_name: Option<String>,
+    pub inputs: Option<serde_json::Value>,
+    pub outputs: Option<serde_json::Value>,
+    pub calldata: Vec<String>,
+    pub result: Vec<String>,
+    pub chain_id: Option<String>,
+}
+
+/// An event from any contract that mentions a watched account in its keys
+#[derive(SimpleObject, Clone)]
+#[graphql(rename_fields = "camelCase")]
+pub struct AccountEvent {
+    pub id: String,
+    pub account_address: String,
+    pub key_position: i32,
+    pub contract_address: String,
+    pub event_type: String,
+    pub event_selector: String,
+    pub block_number: String,
+    pub transaction_hash: String,
+    pub timestamp: String,
+    pub chain_id: String,
+    pub data: Option<serde_json::Value>,
+    pub raw_data: Vec<String>,
+    pub raw_keys: Vec<String>,
+    /// Why the event could not be decoded completely; `data` then holds the members decoded before
+    pub decode_error: Option<String>,
+}
+
+/// An L2->L1 message sent by an indexed contract, or an L1 handler transaction into it
+#[derive(SimpleObject, Clone)]
+#[graphql(rename_fields = "camelCase")]
+pub struct Message {
+    pub id: String,
+    pub contract_address: String,
+    pub direction: String,
+    pub from_address: String,
+    pub to_address: String,
+    pub payload: Vec<String>,
+    pub message_hash: Option<String>,
+    pub nonce: Option<String>,
+    pub entry_point_selector: Option<String>,
+    pub transaction_hash: String,
+    pub block_number: String,
+    pub timestamp: String,
+    pub chain_id: Option<String>,
+}
+
+/// A contract instance created by DEPLOY_ACCOUNT or the deploy syscall
+#[derive(SimpleObject, Clone)]
+#[graphql(rename_fields = "camelCase")]
+pub struct DeployedContract {
+    pub contract_address: String,
+    pub class_hash: String,
+    pub deployer_address: Option<String>,
+    pub transaction_hash: Option<String>,
+    pub deployment_type: String,
+    pub block_number: String,
+    pub timestamp: String,
+    pub chain_id: String,
+}
+
+/// A class declared through a DECLARE transaction
+#[derive(SimpleObject, Clone)]
+#[graphql(rename_fields = "camelCase")]
+pub struct DeclaredClass {
+    pub class_hash: String,
+    pub compiled_class_hash: Option<String>,
+    pub sender_address: String,
+    pub transaction_hash: String,
+    pub block_number: String,
+    pub timestamp: String,
+    pub chain_id: String,
 }
 
 #[derive(SimpleObject)]
@@ -49,6 +192,20 @@ pub enum EventOrderBy {
     TimestampAsc,    // Oldest to latest by timestamp
 }
 
+/// How the decoded values of event data are written
+#[derive(async_graphql::Enum, Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub enum ValueFormat {
+    /// By Cairo type: integers up to 32 bits as numbers, wider ones as decimal strings, felts as hex
+    #[default]
+    Typed,
+    /// Every integer and felt as a 0x hex string
+    Hex,
+    /// Every integer and felt as a decimal string; addresses and class hashes stay hex
+    DecimalString,
+    /// Not decoded: the event's key and data felts as emitted
+    Raw,
+}
+
 // Simple subscription event for real-time updates
 #[derive(SimpleObject, Clone)]
 #[graphql(rename_fields = "camelCase")]
@@ -259,6 +416,33 @@ pub struct AddDeploymentContractInput {
     pub metadata: Option<serde_json::Value>,
 }
 
+/// ABI a contract of a deployment is decoded with instead of its class ABI
+#[derive(SimpleObject, Clone)]
+#[graphql(rename_fields = "camelCase")]
+pub struct AbiOverride {
+    pub id: String,
+    pub deployment_id: Option<String>,
+    pub contract_address: String,
+    pub abi: Option<serde_json::Value>,
+    pub class_hash: Option<String>,
+    pub from_block: Option<String>,
+    pub to_block: Option<String>,
+    pub created_at: String,
+}
+
+/// Either `abi` (the ABI array, or a class holding it) or `classHash`; the block range is inclusive
+/// and open-ended when a bound is missing
+#[derive(InputObject)]
+#[graphql(rename_fields = "camelCase")]
+pub struct SetAbiOverrideInput {
+    pub deployment_id: String,
+    pub contract_address: String,
+    pub abi: Option<serde_json::Value>,
+    pub class_hash: Option<String>,
+    pub from_block: Option<String>,
+    pub to_block: Option<String>,
+}
+
 // API Key types
 #[derive(SimpleObject, Clone)]
 #[graphql(rename_fields = "camelCase")]
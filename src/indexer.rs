@@ -1,9 +1,17 @@
-use crate::database::{Database, EventRecord};
-use crate::starknet::{get_events, get_contract_abi_string, decode_event_using_abi, get_current_block_number, RpcContext};
+use crate::database::{Database, EventRecord, InternalCallRecord};
+use crate::messages::messages_for_contract;
+use crate::chain_guard::{verify_chain, ChainGuardOptions};
+use crate::rpc_types::{BlockId, EventFilter, RpcError};
+use crate::abi_registry::{AbiRegistry, ContractAbis};
+use crate::database::AbiOverrideRecord;
+use crate::starknet::{get_events_chunk, decode_event, decode_function_call_using_abi, DecodeHints, get_block_with_receipts, get_blocks_with_tx_hashes, get_current_block_number, trace_block_transactions, RpcContext};
+use crate::traces::{block_trace_entries, calls_into};
+use crate::live_ws::{next_json, parse_notification, subscribe, BlockBuffer, Notification, WsError, WsSession, IDLE_TIMEOUT};
 use crate::realtime::RealtimeEventManager;
 use crate::graphql::types::Event;
 use serde_json::Value;
-use chrono::Utc;
+use chrono::{DateTime, TimeZone, Utc};
+use std::collections::{BTreeSet, HashMap};
 use tokio::time::{sleep, Duration, Instant};
 use std::sync::Arc;
 
@@ -13,6 +21,20 @@ pub struct ContractConfig {
     pub start_block: Option<u64>,
 }
 
+/// Events requested per `starknet_getEvents` page
+const EVENTS_PAGE_SIZE: u32 = 1000;
+
+/// Key prefix in `indexer_state` of the first block whose internal calls or messages still have to be
+/// fetched, `extras_retry:<contract>`; there is no row while they are up to date
+const EXTRAS_RETRY_KEY: &str = "extras_retry";
+
+/// How new blocks are followed once the indexer is at the head
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LiveMode {
+    Poll, // starknet_blockNumber every sync_interval
+    WebSocket, // RPC 0.8 subscriptions, falling back to polling
+}
+
 #[derive(Clone)]
 pub struct IndexerConfig {
     pub start_block: Option<u64>,
@@ -24,6 +46,13 @@ pub struct IndexerConfig {
     pub max_retries: u32,
     pub allow_list: Option<Vec<String>>, // Added for multi-contract indexing
     pub contract_configs: Option<Vec<ContractConfig>>, // Per-contract configuration
+    pub trace_calls: bool, // Also record internal calls from starknet_traceBlockTransactions
+    pub index_messages: bool, // Also record L1<->L2 messages from block receipts
+    pub live_mode: LiveMode,
+    pub decode_hints: DecodeHints, // Short-string members and felt text guessing
+    pub deployment_id: Option<String>, // Deployment whose ABI overrides apply
+    pub abi_overrides: Vec<AbiOverrideRecord>, // From the command line, for every contract indexed
+    pub resolve_proxies: bool, // Decode proxies with their implementation's ABI
 }
 
 impl Default for IndexerConfig {
@@ -38,6 +67,13 @@ impl Default for IndexerConfig {
             max_retries: 3,
             allow_list: None,
             contract_configs: None,
+            trace_calls: false,
+            index_messages: false,
+            live_mode: LiveMode::Poll,
+            decode_hints: DecodeHints::default(),
+            deployment_id: None,
+            abi_overrides: Vec::new(),
+            resolve_proxies: false,
         }
     }
 }
@@ -48,6 +84,7 @@ pub struct BlockchainIndexer {
     contract_address: String,
     config: IndexerConfig,
     realtime_manager: Option<Arc<RealtimeEventManager>>,
+    abi_registry: tokio::sync::Mutex<AbiRegistry>,
 }
 
 // New struct for handling multiple contracts
@@ -71,28 +108,18 @@ impl MultiContractIndexer {
     pub async fn start_syncing_all(&self) {
         if let Some(allow_list) = &self.config.allow_list {
             println!("🚀 Starting multi-contract indexer for {} contracts", allow_list.len());
-            println!("⏱️  Staggering startup to avoid RPC rate limits...");
             
-            // Start individual indexers for each contract with staggered delays
+            // Start individual indexers for each contract; the shared RPC rate limiter paces them
             let mut handles = Vec::new();
             
-            for (index, contract_address) in allow_list.iter().enumerate() {
+            for contract_address in allow_list.iter() {
                 let database = self.database.clone();
                 let rpc = self.rpc.clone();
                 let config = self.config.clone();
                 let contract = contract_address.clone();
                 let realtime_manager = self.realtime_manager.clone();
                 
-                // Stagger startup by 2 seconds per contract to avoid rate limits
-                let delay_seconds = index as u64 * 2;
-                
                 let handle = tokio::spawn(async move {
-                    // Wait before starting this indexer
-                    if delay_seconds > 0 {
-                        println!("⏳ Starting indexer for {} in {} seconds...", contract, delay_seconds);
-                        tokio::time::sleep(tokio::time::Duration::from_secs(delay_seconds)).await;
-                    }
-                    
                     let indexer = BlockchainIndexer::new(database, rpc, contract, Some(config), realtime_manager);
                     indexer.start_syncing().await;
                 });
@@ -122,34 +149,31 @@ impl MultiContractIndexer {
         }
 
         println!("🚀 Starting multi-contract indexer for deployment {} with {} contracts", deployment_id, contracts.len());
-        println!("⏱️  Staggering startup to avoid RPC rate limits...");
         
-        // Start individual indexers for each contract with staggered delays
+        // Start individual indexers for each contract; the shared RPC rate limiter paces them
         let mut handles = Vec::new();
         
-        for (index, contract) in contracts.iter().enumerate() {
+        for contract in contracts.iter() {
             let database = self.database.clone();
             let rpc = self.rpc.clone();
             let config = self.config.clone();
             let contract_address = contract.contract_address.clone();
             let start_block = contract.start_block;
+            let deployment_id = deployment_id.to_string();
+            let decode_hints = contract_decode_hints(contract.metadata.as_deref(), &contract_address);
             let realtime_manager = self.realtime_manager.clone();
             
-            // Stagger startup by 2 seconds per contract to avoid rate limits
-            let delay_seconds = index as u64 * 2;
-            
             let handle = tokio::spawn(async move {
-                // Wait before starting this indexer
-                if delay_seconds > 0 {
-                    println!("⏳ Starting indexer for {} in {} seconds...", contract_address, delay_seconds);
-                    tokio::time::sleep(tokio::time::Duration::from_secs(delay_seconds)).await;
-                }
-                
                 // Create a custom config for this contract if it has a specific start block
                 let mut contract_config = config.clone();
                 if let Some(start_block) = start_block {
                     contract_config.start_block = Some(start_block);
                 }
+                contract_config.deployment_id = Some(deployment_id);
+                if let Some(hints) = decode_hints {
+                    contract_config.decode_hints.short_strings.extend(hints.short_strings);
+                    contract_config.decode_hints.guess_strings |= hints.guess_strings;
+                }
                 
                 let indexer = BlockchainIndexer::new(database, rpc, contract_address, Some(contract_config), realtime_manager);
                 indexer.start_syncing().await;
@@ -171,11 +195,13 @@ impl MultiContractIndexer {
 
 impl BlockchainIndexer {
     pub fn new(database: Arc<Database>, rpc: RpcContext, contract_address: String, config: Option<IndexerConfig>, realtime_manager: Option<Arc<RealtimeEventManager>>) -> Self {
+        let config = config.unwrap_or_default();
         Self {
+            abi_registry: abi_registry(&contract_address, &config),
             database,
             rpc,
             contract_address,
-            config: config.unwrap_or_default(),
+            config,
             realtime_manager,
         }
     }
@@ -205,7 +231,7 @@ impl BlockchainIndexer {
         };
 
         // Get last synced block or use configured start block
-        let last_synced = match self.database.get_indexer_state(&self.contract_address).await {
+        let last_synced = match self.database.get_chain_indexer_state(&self.rpc.chain_id, &self.contract_address).await {
             Ok(Some(state)) => {
                 // If a start block is configured and it's higher than the last synced block, use the start block
                 if let Some(start_block) = contract_start_block {
@@ -258,20 +284,22 @@ impl BlockchainIndexer {
             
             tokio::spawn(async move {
                 let indexer = BlockchainIndexer {
+                    abi_registry: abi_registry(&contract_address_clone, &config_clone),
                     database: database_clone,
                     rpc: rpc_clone,
                     contract_address: contract_address_clone,
                     config: config_clone,
                     realtime_manager: realtime_manager_clone,
                 };
-                indexer.continuous_sync().await;
+                indexer.live_sync().await;
             })
         };
 
-        // Create a new indexer instance for historical sync
+        // Create a new indexer instance for historical sync; its calls yield to live-head traffic
         let historical_indexer = BlockchainIndexer {
+            abi_registry: abi_registry(&contract_address, &config),
             database,
-            rpc,
+            rpc: rpc.backfill(),
             contract_address,
             config,
             realtime_manager: self.realtime_manager.clone(),
@@ -291,6 +319,12 @@ impl BlockchainIndexer {
         }
     }
 
+    /// ABIs to decode blocks `from_block` to `to_block` of the contract with, overrides included; asked
+    /// for on every pass so new overrides apply to the blocks indexed next
+    async fn load_abis(&self, from_block: u64, to_block: u64) -> ContractAbis {
+        self.abi_registry.lock().await.abis(&self.rpc, &self.database, from_block, to_block).await
+    }
+
     async fn sync_historical_data(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         println!("📚 Starting historical data sync for contract: {}", self.contract_address);
         
@@ -305,7 +339,7 @@ impl BlockchainIndexer {
         };
 
         // Get the last synced block for this contract
-        let last_synced = match self.database.get_indexer_state(&self.contract_address).await? {
+        let last_synced = match self.database.get_chain_indexer_state(&self.rpc.chain_id, &self.contract_address).await? {
             Some(state) => {
                 // If a start block is configured and it's higher than the last synced block, use the start block
                 if let Some(start_block) = contract_start_block {
@@ -339,12 +373,6 @@ impl BlockchainIndexer {
             return Ok(());
         }
 
-        // Fetch contract ABI once
-        let abi_str = get_contract_abi_string(&self.rpc, &self.contract_address)
-            .await
-            .unwrap_or_else(|_| "[]".to_string());
-        let abi_json: Value = serde_json::from_str(&abi_str).unwrap_or(Value::Array(vec![]));
-
         // Process in chunks
         let mut from_block = last_synced;
         let mut total_events = 0;
@@ -356,7 +384,8 @@ impl BlockchainIndexer {
                     from_block, to_block,
                     ((from_block as f64 - last_synced as f64) / (current_block as f64 - last_synced as f64)) * 100.0);
 
-            match self.sync_block_range(from_block, to_block, &abi_json).await {
+            let abis = self.load_abis(from_block, to_block).await;
+            match self.sync_block_range(from_block, to_block, &abis).await {
                 Ok(events_count) => {
                     total_events += events_count;
                     if events_count > 0 {
@@ -366,7 +395,7 @@ impl BlockchainIndexer {
                     }
                     
                     // Update indexer state
-                    self.database.update_indexer_state(&self.contract_address, to_block).await?;
+                    self.database.update_chain_indexer_state(&self.rpc.chain_id, &self.contract_address, to_block).await?;
                 }
                 Err(e) => {
                     eprintln!("   ❌ Error processing chunk: {}", e);
@@ -375,9 +404,6 @@ impl BlockchainIndexer {
             }
 
             from_block = to_block + 1;
-            
-            // Longer delay to avoid rate limiting
-            sleep(Duration::from_millis(500)).await;
         }
 
         if total_events > 0 {
@@ -388,6 +414,183 @@ impl BlockchainIndexer {
         Ok(())
     }
 
+    async fn live_sync(&self) {
+        match self.config.live_mode {
+            LiveMode::Poll => self.continuous_sync().await,
+            LiveMode::WebSocket => self.websocket_sync().await,
+        }
+    }
+
+    /// Follow the head through WebSocket subscriptions. Gaps left by a disconnect are filled through
+    /// HTTP `getEvents` on reconnect, and polling takes over for good if the provider has no WebSocket
+    /// support at all.
+    async fn websocket_sync(&self) {
+        if self.rpc.replaying() {
+            println!("ℹ️  Replaying recorded RPC fixtures - polling instead of subscribing");
+            return self.continuous_sync().await;
+        }
+        if let Some(version) = self.rpc.pool.spec_version().filter(|v| !v.supports("starknet_subscribeEvents")) {
+            println!("ℹ️  Spec {} has no event subscriptions - falling back to polling", version.as_str());
+            return self.continuous_sync().await;
+        }
+        println!("🔌 Starting WebSocket live sync via {}", self.rpc.ws_url);
+        let mut connected_once = false;
+        let mut backoff = Duration::from_secs(1);
+
+        loop {
+            match subscribe(&self.rpc.ws_url, &self.rpc.ws_auth, &self.contract_address).await {
+                Ok(session) => {
+                    connected_once = true;
+                    backoff = Duration::from_secs(1);
+                    println!("✅ Subscribed to new heads and events of {}", self.contract_address);
+
+                    let error = self.run_ws_session(session).await;
+                    eprintln!("⚠️  {} - reconnecting", error);
+                }
+                Err(WsError::Unsupported(reason)) if !connected_once => {
+                    println!("ℹ️  {} - falling back to polling", reason);
+                    return self.continuous_sync().await;
+                }
+                Err(e) => eprintln!("❌ WebSocket reconnect failed: {}", e),
+            }
+
+            // Keep up through HTTP while the subscription is down
+            if let Err(e) = self.sync_latest_blocks().await {
+                eprintln!("❌ Error in continuous sync: {}", e);
+            }
+            sleep(backoff).await;
+            backoff = std::cmp::min(backoff * 2, Duration::from_secs(30));
+        }
+    }
+
+    /// Write pushed events block by block until the connection fails
+    async fn run_ws_session(&self, mut session: WsSession) -> WsError {
+        // Everything up to the head is fetched through HTTP first; pushed events of those blocks are dropped
+        if let Err(e) = self.sync_latest_blocks().await {
+            return WsError::Disconnected(format!("gap fill failed: {}", e));
+        }
+        let filled_through = match self.database.get_chain_indexer_state(&self.rpc.chain_id, &self.contract_address).await {
+            Ok(state) => state.map(|s| s.last_synced_block).unwrap_or_default(),
+            Err(e) => return WsError::Disconnected(format!("failed to read cursor: {}", e)),
+        };
+
+        let mut written_through = filled_through;
+        let mut buffer = BlockBuffer::default();
+        let mut refetch: BTreeSet<u64> = BTreeSet::new(); // Blocks already written that got more events
+        let mut refill = false; // Blocks after `written_through` were rolled back and are fetched over HTTP
+        let mut early = std::mem::take(&mut session.early).into_iter();
+
+        loop {
+            let message = match early.next() {
+                Some(message) => message,
+                None => match tokio::time::timeout(IDLE_TIMEOUT, next_json(&mut session.stream)).await {
+                    Ok(Ok(message)) => message,
+                    Ok(Err(e)) => return e,
+                    Err(_) => return WsError::Disconnected(format!("no message for {}s", IDLE_TIMEOUT.as_secs())),
+                },
+            };
+
+            match parse_notification(&message) {
+                Notification::Event(event) => {
+                    let Some(block_number) = event.get("block_number").and_then(|v| v.as_u64()) else {
+                        continue; // Pending events get written once their block is accepted
+                    };
+                    if block_number <= filled_through {
+                        continue;
+                    }
+                    if block_number <= written_through {
+                        refetch.insert(block_number);
+                    } else {
+                        buffer.push(block_number, event);
+                    }
+                }
+                Notification::NewHead(head) if head < written_through => {
+                    // A shorter chain: blocks after the new head no longer exist
+                    println!("♻️  New head {} is below block {} - rolling back", head, written_through);
+                    if let Err(e) = self.roll_back(head + 1, written_through).await {
+                        return WsError::Disconnected(format!("failed to roll back: {}", e));
+                    }
+                    written_through = head;
+                    buffer.discard_from(head + 1);
+                    refetch.retain(|block| *block <= head);
+                }
+                Notification::NewHead(head) => {
+                    let abis = self.load_abis(filled_through + 1, head.max(filled_through + 1)).await;
+                    let written = if refill && head > written_through {
+                        // Pushed events of rolled back blocks may be missing; the new chain is read over HTTP
+                        buffer.take_through(head);
+                        match self.sync_block_range(written_through + 1, head, &abis).await {
+                            Ok(_) => self.write_pushed_blocks(Vec::new(), &refetch, &abis).await,
+                            Err(e) => Err(e),
+                        }
+                    } else {
+                        self.write_pushed_blocks(buffer.take_through(head), &refetch, &abis).await
+                    };
+                    if let Err(e) = written {
+                        return WsError::Disconnected(format!("failed to write pushed blocks: {}", e));
+                    }
+                    refetch.clear();
+
+                    if head > written_through {
+                        refill = false;
+                        written_through = head;
+                        if let Err(e) = self.database.update_chain_indexer_state(&self.rpc.chain_id, &self.contract_address, head).await {
+                            return WsError::Disconnected(format!("failed to update cursor: {}", e));
+                        }
+                    }
+                }
+                Notification::Reorg(reorg) => {
+                    let from = reorg.get("starting_block_number").and_then(|v| v.as_u64()).unwrap_or(written_through);
+                    let to = reorg.get("ending_block_number").and_then(|v| v.as_u64()).unwrap_or(written_through);
+                    if from > written_through {
+                        buffer.discard_from(from);
+                        continue;
+                    }
+                    println!("♻️  Reorg of blocks {} to {} - fetching them again", from, to);
+                    if let Err(e) = self.roll_back(from, to.min(written_through)).await {
+                        return WsError::Disconnected(format!("failed to roll back: {}", e));
+                    }
+                    written_through = from.saturating_sub(1);
+                    buffer.discard_from(from);
+                    refetch.retain(|block| *block < from);
+                    refill = true;
+                }
+                Notification::Other => {}
+            }
+        }
+    }
+
+    /// Forget blocks `from_block` to `to_block` after a reorg: the contract's rows of those blocks are
+    /// deleted and the cursor moves back to `from_block - 1`, so they are indexed again from the new chain
+    async fn roll_back(&self, from_block: u64, to_block: u64) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.database.delete_contract_blocks(&self.rpc.chain_id, &self.contract_address, from_block, to_block).await?;
+        self.rpc.traces.forget(&self.rpc.network, from_block);
+        self.database.update_chain_indexer_state(&self.rpc.chain_id, &self.contract_address, from_block.saturating_sub(1)).await?;
+        Ok(())
+    }
+
+    /// Store pushed blocks, and fetch again over HTTP the written blocks that got late events
+    async fn write_pushed_blocks(
+        &self,
+        blocks: Vec<(u64, Vec<Value>)>,
+        refetch: &BTreeSet<u64>,
+        abis: &ContractAbis,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        for (block_number, events) in blocks {
+            let count = self.store_events(&events, abis).await?;
+            if count > 0 {
+                println!("🎉 FOUND {} NEW EVENTS from contract in block {} (pushed)", count, block_number);
+            }
+            self.sync_block_extras(block_number, block_number, abis).await;
+        }
+
+        for &block_number in refetch {
+            self.sync_block_range(block_number, block_number, abis).await?;
+        }
+
+        Ok(())
+    }
+
     async fn continuous_sync(&self) {
         println!("🔄 Starting continuous sync (checking every 2 seconds)...");
         let mut last_status_update = Instant::now();
@@ -400,7 +603,7 @@ impl BlockchainIndexer {
                     // Show status update every 60 seconds or when blocks are synced
                     if last_status_update.elapsed() >= Duration::from_secs(60) || blocks_synced > 0 {
                         if let Ok(current_block) = get_current_block_number(&self.rpc).await {
-                            if let Ok(Some(state)) = self.database.get_indexer_state(&self.contract_address).await {
+                            if let Ok(Some(state)) = self.database.get_chain_indexer_state(&self.rpc.chain_id, &self.contract_address).await {
                                 let blocks_behind = current_block.saturating_sub(state.last_synced_block);
                                 
                                 if blocks_behind > 100 {
@@ -432,15 +635,12 @@ impl BlockchainIndexer {
             if sleep_duration > Duration::from_millis(100) {
                 sleep(sleep_duration).await;
             }
-            
-            // Add a small delay to prevent overwhelming the RPC endpoint
-            tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
         }
     }
 
     async fn sync_latest_blocks(&self) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
         // Get current state
-        let last_synced = match self.database.get_indexer_state(&self.contract_address).await? {
+        let last_synced = match self.database.get_chain_indexer_state(&self.rpc.chain_id, &self.contract_address).await? {
             Some(state) => state.last_synced_block,
             None => return Ok(0), // Should not happen after historical sync
         };
@@ -454,21 +654,18 @@ impl BlockchainIndexer {
 
         let blocks_to_sync = current_block - last_synced;
 
-        // Fetch ABI
-        let abi_str = get_contract_abi_string(&self.rpc, &self.contract_address)
-            .await
-            .unwrap_or_else(|_| "[]".to_string());
-        let abi_json: Value = serde_json::from_str(&abi_str).unwrap_or(Value::Array(vec![]));
+        // Fetch ABIs
+        let abis = self.load_abis(last_synced + 1, current_block).await;
 
         // Sync new blocks
-        let events_count = self.sync_block_range(last_synced + 1, current_block, &abi_json).await?;
+        let events_count = self.sync_block_range(last_synced + 1, current_block, &abis).await?;
         
         if events_count > 0 {
             println!("🎉 FOUND {} NEW EVENTS from contract in blocks {} to {} - updating database!", events_count, last_synced + 1, current_block);
         }
 
         // Update state
-        self.database.update_indexer_state(&self.contract_address, current_block).await?;
+        self.database.update_chain_indexer_state(&self.rpc.chain_id, &self.contract_address, current_block).await?;
         
         Ok(blocks_to_sync)
     }
@@ -477,115 +674,215 @@ impl BlockchainIndexer {
         &self, 
         from_block: u64, 
         to_block: u64, 
-        abi_json: &Value
+        abis: &ContractAbis,
     ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
         
-        // Query events from RPC with retry mechanism
+        let raw_events = self.fetch_events(from_block, to_block).await?;
+
+        let count = self.store_events(&raw_events, abis).await?;
+        self.sync_block_extras(from_block, to_block, abis).await;
+        Ok(count)
+    }
+
+    /// All events of the contract in the range, following continuation tokens. Retryable errors are
+    /// retried up to `max_retries` times; a page size above the node's limit is halved, and a rejected
+    /// continuation token restarts the range.
+    async fn fetch_events(&self, from_block: u64, to_block: u64) -> Result<Vec<Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut filter = EventFilter {
+            from_block: Some(BlockId::Number(from_block)),
+            to_block: Some(BlockId::Number(to_block)),
+            address: Some(self.contract_address.clone()),
+            keys: Vec::new(),
+            chunk_size: EVENTS_PAGE_SIZE,
+            continuation_token: None,
+        };
+        let mut events = Vec::new();
         let mut attempts = 0;
-        let max_attempts = self.config.max_retries;
-        let mut raw = None;
-        
-        while attempts < max_attempts {
-            match get_events(
-                &self.rpc,
-                &self.contract_address,
-                Some(&from_block.to_string()),
-                Some(&to_block.to_string()),
-                1000, // Max events per request
-                None, // No continuation for chunk processing
-            ).await {
-                Ok(result) => {
-                    raw = Some(result);
-                    break;
+
+        loop {
+            match get_events_chunk(&self.rpc, &filter).await {
+                Ok(chunk) => {
+                    for event in &chunk.events {
+                        events.push(serde_json::to_value(event)?);
+                    }
+                    match chunk.continuation_token {
+                        Some(token) => filter.continuation_token = Some(token),
+                        None => return Ok(events),
+                    }
                 }
-                Err(e) => {
+                Err(RpcError::PageSizeTooBig) if filter.chunk_size > 1 => {
+                    filter.chunk_size /= 2;
+                    println!("   ⚠️  Page size too big for the node, retrying with {}", filter.chunk_size);
+                }
+                Err(e) if (e.is_retryable() || e == RpcError::InvalidContinuationToken) && attempts + 1 < self.config.max_retries => {
                     attempts += 1;
-                    if attempts < max_attempts {
-                        println!("   ⚠️  RPC error (attempt {}/{}): {}. Retrying in 2 seconds...", attempts, max_attempts, e);
-                        sleep(Duration::from_secs(2)).await;
-                    } else {
-                        return Err(format!("RPC error after {} attempts: {}", max_attempts, e).into());
+                    if e == RpcError::InvalidContinuationToken {
+                        filter.continuation_token = None;
+                        events.clear();
                     }
+                    println!("   ⚠️  RPC error (attempt {}/{}): {}. Retrying in 2 seconds...", attempts, self.config.max_retries, e);
+                    sleep(Duration::from_secs(2)).await;
                 }
+                Err(e) => return Err(format!("Failed to get events for blocks {} to {}: {}", from_block, to_block, e).into()),
             }
         }
-        
-        let raw = raw.ok_or("Failed to get events after all retries")?;
+    }
 
-        let mut events = Vec::new();
+    /// Internal calls and messages of the blocks, when enabled. They are written after the events and a
+    /// failure does not hold those back: it is logged, and the blocks from the failed one on are kept
+    /// under the `extras_retry:<contract>` key and asked for again on the next pass.
+    async fn sync_block_extras(&self, from_block: u64, to_block: u64, abis: &ContractAbis) {
+        if !self.config.trace_calls && !self.config.index_messages {
+            return;
+        }
 
-        if let Some(result) = raw.get("result") {
-            if let Some(events_array) = result.get("events").and_then(|v| v.as_array()) {
-                for (idx, ev) in events_array.iter().enumerate() {
-                    let (event_type, decoded) = decode_event_using_abi(abi_json, ev);
-                    
-                    // Apply event type filter if configured
-                    if let Some(filter_types) = &self.config.event_types {
-                        if !filter_types.contains(&event_type) {
-                            continue;
-                        }
-                    }
-                    
-                    // Apply event keys filter if configured
-                    if let Some(filter_keys) = &self.config.event_keys {
-                        let keys = ev.get("keys").and_then(|v| v.as_array()).cloned().unwrap_or_default();
-                        let keys_str: Vec<String> = keys.iter()
-                            .filter_map(|k| k.as_str().map(|s| s.to_string()))
-                            .collect();
-                        
-                        let has_matching_key = filter_keys.iter().any(|filter_key| {
-                            keys_str.iter().any(|key| key.contains(filter_key))
-                        });
-                        if !has_matching_key {
-                            continue;
-                        }
-                    }
-                    
-                    let tx_hash = ev.get("transaction_hash")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("")
-                        .to_string();
-                    
-                    let block_number = ev.get("block_number")
-                        .and_then(|v| v.as_u64())
-                        .unwrap_or_default();
-                    
-                    let raw_data = ev.get("data")
-                        .and_then(|v| v.as_array())
-                        .cloned()
-                        .unwrap_or_default();
-                    
-                    let raw_keys = ev.get("keys")
-                        .and_then(|v| v.as_array())
-                        .cloned()
-                        .unwrap_or_default();
-
-                    let event_record = EventRecord {
-                        id: format!("{}:{}", tx_hash, idx),
-                        contract_address: crate::database::Database::normalize_address(&self.contract_address),
-                        event_type,
-                        block_number,
-                        transaction_hash: tx_hash,
-                        log_index: idx as i32,
-                        timestamp: Utc::now(), // In production, get actual block timestamp
-                        decoded_data: Some(decoded.to_string()),
-                        raw_data: serde_json::to_string(&raw_data)?,
-                        raw_keys: serde_json::to_string(&raw_keys)?,
-                    };
+        let retry_key = format!("{}:{}", EXTRAS_RETRY_KEY, self.contract_address);
+        let retry_from = match self.database.get_chain_indexer_state(&self.rpc.chain_id, &retry_key).await {
+            Ok(state) => state.map(|s| s.last_synced_block),
+            Err(e) => {
+                eprintln!("   ⚠️  Failed to read the calls and messages retry block: {}", e);
+                None
+            }
+        };
 
-                    events.push(event_record);
+        let start_block = retry_from.map_or(from_block, |retry_from| retry_from.min(from_block));
+        let mut calls_count = 0;
+        let mut messages_count = 0;
+        let mut failed = None;
+
+        for block_number in start_block..=to_block {
+            let synced = async {
+                if self.config.trace_calls {
+                    calls_count += self.sync_internal_calls(block_number, abis).await?;
+                }
+                if self.config.index_messages {
+                    messages_count += self.sync_messages(block_number).await?;
                 }
+                Ok::<_, Box<dyn std::error::Error + Send + Sync>>(())
+            };
+            if let Err(e) = synced.await {
+                failed = Some((block_number, e));
+                break;
             }
         }
 
+        if calls_count > 0 {
+            println!("   📞 Recorded {} internal calls into contract in blocks {} to {}", calls_count, start_block, to_block);
+        }
+        if messages_count > 0 {
+            println!("   ✉️  Recorded {} L1<->L2 messages for contract in blocks {} to {}", messages_count, start_block, to_block);
+        }
+
+        let saved = match failed {
+            Some((block_number, e)) => {
+                eprintln!("   ⚠️  {} - calls and messages from block {} on are retried on the next pass", e, block_number);
+                if retry_from.is_none_or(|retry_from| block_number < retry_from) {
+                    self.database.update_chain_indexer_state(&self.rpc.chain_id, &retry_key, block_number).await
+                } else {
+                    Ok(())
+                }
+            }
+            None if retry_from.is_some_and(|retry_from| retry_from <= to_block) => {
+                self.database.delete_chain_indexer_state(&self.rpc.chain_id, &retry_key).await
+            }
+            None => Ok(()),
+        };
+        if let Err(e) = saved {
+            eprintln!("   ⚠️  Failed to save the calls and messages retry block: {}", e);
+        }
+    }
+
+    /// Decode, filter, store and broadcast events of the contract. `log_index` is the event's position
+    /// in `raw_events`, so callers pass the events of one block range as returned by the node.
+    async fn store_events(
+        &self,
+        raw_events: &[Value],
+        abis: &ContractAbis,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let mut events = Vec::new();
+
+        for (idx, ev) in raw_events.iter().enumerate() {
+            let decoded = decode_event(
+                abis.for_block(ev.get("block_number").and_then(|b| b.as_u64()).unwrap_or(u64::MAX)),
+                ev,
+                &self.config.decode_hints,
+            );
+            let event_type = decoded.event_type.clone();
+            
+            // Apply event type filter if configured
+            if let Some(filter_types) = &self.config.event_types {
+                if !crate::starknet::event_type_matches(filter_types, &event_type) {
+                    continue;
+                }
+            }
+            
+            // Apply event keys filter if configured
+            if let Some(filter_keys) = &self.config.event_keys {
+                let keys = ev.get("keys").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+                let keys_str: Vec<String> = keys.iter()
+                    .filter_map(|k| k.as_str().map(|s| s.to_string()))
+                    .collect();
+                
+                let has_matching_key = filter_keys.iter().any(|filter_key| {
+                    keys_str.iter().any(|key| key.contains(filter_key))
+                });
+                if !has_matching_key {
+                    continue;
+                }
+            }
+            
+            let tx_hash = ev.get("transaction_hash")
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string();
+            
+            let block_number = ev.get("block_number")
+                .and_then(|v| v.as_u64())
+                .unwrap_or_default();
+            
+            let raw_data = ev.get("data")
+                .and_then(|v| v.as_array())
+                .cloned()
+                .unwrap_or_default();
+            
+            let raw_keys = ev.get("keys")
+                .and_then(|v| v.as_array())
+                .cloned()
+                .unwrap_or_default();
+
+            let event_record = EventRecord {
+                id: format!("{}:{}", tx_hash, idx),
+                contract_address: crate::database::Database::normalize_address(&self.contract_address),
+                event_type,
+                block_number,
+                transaction_hash: tx_hash,
+                log_index: idx as i32,
+                timestamp: Utc::now(), // Replaced by the block timestamp below
+                decoded_data: Some(decoded.to_stored().to_string()), // Typed, written in each query's value format
+                raw_data: serde_json::to_string(&raw_data)?,
+                raw_keys: serde_json::to_string(&raw_keys)?,
+                chain_id: Some(self.rpc.chain_id.clone()),
+            };
+
+            events.push(event_record);
+        }
+
         // Insert events into database
         if !events.is_empty() {
+            let timestamps = self.block_timestamps(events.iter().map(|e| e.block_number).collect()).await;
+            for event in events.iter_mut() {
+                if let Some(timestamp) = timestamps.get(&event.block_number) {
+                    event.timestamp = *timestamp;
+                }
+            }
+
             self.database.insert_events(&events).await?;
             
             // Broadcast events to real-time subscribers
             if let Some(realtime_manager) = &self.realtime_manager {
                 for event_record in &events {
                     // Convert EventRecord to GraphQL Event type for broadcasting
+                    let decoded: Option<Value> = event_record.decoded_data.as_ref().and_then(|json| serde_json::from_str(json).ok());
                     let graphql_event = Event {
                         id: event_record.id.clone(),
                         contract_address: event_record.contract_address.clone(),
@@ -594,9 +891,12 @@ impl BlockchainIndexer {
                         transaction_hash: event_record.transaction_hash.clone(),
                         log_index: event_record.log_index,
                         timestamp: event_record.timestamp.to_rfc3339(),
-                        data: Some(serde_json::Value::Object(serde_json::Map::new())), // Will be computed when requested through GraphQL
+                        decode_error: decoded.as_ref().and_then(crate::decoded_value::decode_error),
+                        data: decoded.clone(), // Stored form, written in each subscription's value format
                         raw_data: serde_json::from_str(&event_record.raw_data).unwrap_or_default(),
                         raw_keys: serde_json::from_str(&event_record.raw_keys).unwrap_or_default(),
+                        chain_id: event_record.chain_id.clone(),
+                        decoded,
                     };
                     
                     realtime_manager.broadcast_event(graphql_event).await;
@@ -606,6 +906,77 @@ impl BlockchainIndexer {
 
         Ok(events.len())
     }
+
+    /// Trace the block and record each call made into the contract, including calls made by other
+    /// contracts deeper in the call tree. The block is traced once for every contract of the network.
+    async fn sync_internal_calls(
+        &self,
+        block_number: u64,
+        abis: &ContractAbis,
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let traces = self.rpc.traces.block(&self.rpc.network, block_number, || trace_block_transactions(&self.rpc, block_number)).await
+            .map_err(|e| format!("Failed to trace block {}: {}", block_number, e))?;
+
+        let mut calls = Vec::new();
+        for (tx_hash, trace_root) in block_trace_entries(&traces) {
+            for call in calls_into(trace_root, &self.contract_address) {
+                let decoded = decode_function_call_using_abi(abis.for_block(block_number), &call.entry_point_selector, &call.calldata, &call.result, &self.config.decode_hints);
+
+                calls.push(InternalCallRecord {
+                    id: format!("{}:{}", tx_hash, call.call_index),
+                    contract_address: call.contract_address,
+                    caller_address: call.caller_address,
+                    block_number,
+                    transaction_hash: tx_hash.clone(),
+                    call_index: call.call_index as i32,
+                    depth: call.depth as i32,
+                    phase: call.phase,
+                    call_type: call.call_type,
+                    entry_point_type: call.entry_point_type,
+                    entry_point_selector: call.entry_point_selector,
+                    function_name: decoded.as_ref().map(|(name, _, _)| name.clone()),
+                    calldata: serde_json::to_string(&call.calldata)?,
+                    result: serde_json::to_string(&call.result)?,
+                    decoded_inputs: decoded.as_ref().map(|(_, inputs, _)| inputs.to_string()),
+                    decoded_outputs: decoded.as_ref().map(|(_, _, outputs)| outputs.to_string()),
+                    chain_id: Some(self.rpc.chain_id.clone()),
+                });
+            }
+        }
+
+        self.database.insert_internal_calls(&calls).await?;
+        Ok(calls.len())
+    }
+
+    /// Timestamps of the given blocks, fetched in batched header requests. Blocks that could not be
+    /// fetched are left out, so their events keep the time they were indexed at.
+    async fn block_timestamps(&self, block_numbers: BTreeSet<u64>) -> HashMap<u64, DateTime<Utc>> {
+        let block_numbers: Vec<u64> = block_numbers.into_iter().collect();
+        let blocks = match get_blocks_with_tx_hashes(&self.rpc, &block_numbers).await {
+            Ok(blocks) => blocks,
+            Err(e) => {
+                eprintln!("   ⚠️  Failed to fetch block timestamps: {}", e);
+                return HashMap::new();
+            }
+        };
+
+        block_numbers.into_iter().zip(blocks)
+            .filter_map(|(block_number, block)| {
+                let timestamp = Utc.timestamp_opt(block.ok()?.timestamp as i64, 0).single()?;
+                Some((block_number, timestamp))
+            })
+            .collect()
+    }
+
+    /// Record messages the contract sent to L1 in the block and the L1 handler transactions that invoked it.
+    async fn sync_messages(&self, block_number: u64) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let block = get_block_with_receipts(&self.rpc, block_number).await
+            .map_err(|e| format!("Failed to get block {}: {}", block_number, e))?;
+
+        let messages = messages_for_contract(&block, &self.contract_address, &self.rpc.chain_id);
+        self.database.insert_messages(&messages).await?;
+        Ok(messages.len())
+    }
 }
 
 // Single contract background indexer (kept for REST API compatibility)
@@ -621,6 +992,74 @@ pub async fn start_background_indexer(
     indexer.start_syncing().await;
 }
 
+/// ABI registry of an indexer; each indexer task keeps its own
+fn abi_registry(contract_address: &str, config: &IndexerConfig) -> tokio::sync::Mutex<AbiRegistry> {
+    tokio::sync::Mutex::new(AbiRegistry::new(
+        contract_address,
+        config.deployment_id.clone(),
+        &config.abi_overrides,
+        config.resolve_proxies,
+    ))
+}
+
+/// Decoding hints in a deployment contract's metadata; metadata that cannot be read is reported and skipped
+fn contract_decode_hints(metadata: Option<&str>, contract_address: &str) -> Option<DecodeHints> {
+    let metadata: Value = serde_json::from_str(metadata?).ok()?;
+    match DecodeHints::from_metadata(&metadata) {
+        Ok(hints) => Some(hints),
+        Err(e) => {
+            eprintln!("⚠️  Ignoring decoding hints of contract {}: {}", contract_address, e);
+            None
+        }
+    }
+}
+
+/// Index the contracts of every active deployment, each against the RPC of its deployment's network
+pub async fn start_deployment_indexers(
+    database: Arc<Database>,
+    rpc: RpcContext,
+    config: IndexerConfig,
+    chain_guard: ChainGuardOptions,
+    realtime_manager: Option<Arc<RealtimeEventManager>>,
+) {
+    let deployments = match database.get_deployments(Some("active"), None, 1000, 0).await {
+        Ok(deployments) => deployments,
+        Err(e) => {
+            eprintln!("❌ Failed to load deployments: {}", e);
+            return;
+        }
+    };
+
+    let mut handles = Vec::new();
+    for deployment in deployments {
+        let mut deployment_rpc = match rpc.for_network(&deployment.network) {
+            Ok(deployment_rpc) => deployment_rpc,
+            Err(e) => {
+                eprintln!("❌ Skipping deployment {}: {}", deployment.name, e);
+                continue;
+            }
+        };
+        if let Err(e) = verify_chain(&database, &mut deployment_rpc, chain_guard, false).await {
+            eprintln!("❌ Skipping deployment {}: {}", deployment.name, e);
+            continue;
+        }
+        println!("🌐 Deployment {} indexes {} ({}) via {}", deployment.name, deployment_rpc.network, deployment_rpc.chain_id, deployment_rpc.rpc_url);
+
+        let indexer = MultiContractIndexer::new(database.clone(), deployment_rpc, config.clone(), realtime_manager.clone());
+        handles.push(tokio::spawn(async move {
+            if let Err(e) = indexer.start_syncing_deployment(&deployment.id).await {
+                eprintln!("❌ Indexer for deployment {} failed: {}", deployment.name, e);
+            }
+        }));
+    }
+
+    for handle in handles {
+        if let Err(e) = handle.await {
+            eprintln!("❌ Deployment indexer task failed: {}", e);
+        }
+    }
+}
+
 pub async fn start_multi_contract_background_indexer(
     database: Arc<Database>,
     rpc: RpcContext,
@@ -630,3 +1069,128 @@ pub async fn start_multi_contract_background_indexer(
     let indexer = MultiContractIndexer::new(database, rpc, config, realtime_manager);
     indexer.start_syncing_all().await;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::networks::NetworkRegistry;
+    use crate::rpc_fixtures::{FixtureMode, RpcFixtures};
+
+    /// `EventTestContract` from contracts/src/lib.cairo. Its fixtures are synthetic (hand-written in the
+    /// `--rpc-record` format, made-up hashes) with one event of each emit function.
+    const EVENT_TEST_CONTRACT: &str = "0x02cf12918a78bb09bb553590cc05d1ee8edd6bbb829c84464c0374fa620c983e";
+
+    async fn replay_indexer(fixtures: &str, config: IndexerConfig) -> (BlockchainIndexer, Arc<Database>, std::path::PathBuf) {
+        let dir = format!("{}/tests/fixtures/replay/{}", env!("CARGO_MANIFEST_DIR"), fixtures);
+        let fixtures = RpcFixtures::new(dir, FixtureMode::Replay).unwrap();
+        let rpc = RpcContext::new(Arc::new(NetworkRegistry::builtin_only("mainnet"))).unwrap().with_fixtures(Arc::new(fixtures));
+
+        let path = std::env::temp_dir().join(format!("replay_indexer_{}.db", uuid::Uuid::new_v4()));
+        let database = Arc::new(Database::new(&format!("sqlite:{}", path.to_string_lossy())).await.unwrap());
+        let indexer = BlockchainIndexer::new(database.clone(), rpc, EVENT_TEST_CONTRACT.to_string(), Some(config), None);
+        (indexer, database, path)
+    }
+
+    /// A `mock` network on Sepolia's chain id, served by `rpc_url`
+    fn mock_networks(rpc_url: &str) -> Arc<NetworkRegistry> {
+        let config = format!(r#"{{"mock": {{"chainId": "SN_SEPOLIA", "rpcUrls": ["{}"]}}}}"#, rpc_url);
+        Arc::new(NetworkRegistry::with_networks(NetworkRegistry::parse_config(&config).unwrap(), "mock"))
+    }
+
+    #[tokio::test]
+    async fn test_backfill_replays_event_test_contract() {
+        let config = IndexerConfig { start_block: Some(1412000), ..IndexerConfig::default() };
+        let (indexer, database, path) = replay_indexer("event_test_contract", config).await;
+
+        indexer.sync_historical_data().await.unwrap();
+
+        let mut events = database.get_events(EVENT_TEST_CONTRACT, Some("SN_MAIN"), None, None, None, 100, 0).await.unwrap();
+        events.sort_by_key(|e| (e.block_number, e.log_index));
+        assert_eq!(events.len(), 15);
+        assert_eq!(
+            events.iter().map(|e| e.block_number).collect::<Vec<_>>(),
+            [vec![1412001; 8], vec![1412003; 2], vec![1412005; 5]].concat()
+        );
+        assert!(events.iter().all(|e| e.chain_id.as_deref() == Some("SN_MAIN")));
+        assert!(events.iter().all(|e| e.event_type != "Unknown"));
+
+        // Timestamps come from the block headers of the fixtures
+        assert_eq!(events[0].timestamp.timestamp(), 1746001213);
+        assert_eq!(events[14].timestamp.timestamp(), 1746001298);
+        assert_eq!(events[14].id, "0x6e8a0c2f4b6d8a1c3e5f7b9d0a2c4e6f8b1d3a5c7e9f0b2d4a6c8e1f3b5d7a9:14");
+
+        let state = database.get_chain_indexer_state("SN_MAIN", EVENT_TEST_CONTRACT).await.unwrap().unwrap();
+        assert_eq!(state.last_synced_block, 1412006);
+
+        // A second pass finds nothing new and makes no request the fixtures do not answer
+        indexer.sync_historical_data().await.unwrap();
+        assert_eq!(database.count_events(EVENT_TEST_CONTRACT, Some("SN_MAIN"), None).await.unwrap(), 15);
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn test_events_are_stored_when_tracing_fails() {
+        let mut scenario: crate::mock_rpc::Scenario = serde_yaml::from_str(include_str!("../tests/fixtures/mock_rpc/scenario.yaml")).unwrap();
+        // A provider that cannot trace the first block asked for
+        scenario.faults.push(serde_yaml::from_str("{method: starknet_traceBlockTransactions, kind: error, code: -32601, message: Method not found}").unwrap());
+        let (addr, _chain) = crate::mock_rpc::serve(scenario, "127.0.0.1:0".parse().unwrap()).await.unwrap();
+        let rpc = RpcContext::new(mock_networks(&format!("http://{}", addr))).unwrap();
+
+        let path = std::env::temp_dir().join(format!("extras_retry_{}.db", uuid::Uuid::new_v4()));
+        let database = Arc::new(Database::new(&format!("sqlite:{}", path.to_string_lossy())).await.unwrap());
+        let config = IndexerConfig { trace_calls: true, ..IndexerConfig::default() };
+        let indexer = BlockchainIndexer::new(database.clone(), rpc, "0xc0ffee".to_string(), Some(config), None);
+        let retry_key = format!("{}:0xc0ffee", EXTRAS_RETRY_KEY);
+
+        let abis = indexer.load_abis(101, 103).await;
+        assert_eq!(indexer.sync_block_range(101, 102, &abis).await.unwrap(), 3);
+        assert_eq!(database.count_events("0xc0ffee", Some("SN_SEPOLIA"), None).await.unwrap(), 3);
+        let retry = database.get_chain_indexer_state("SN_SEPOLIA", &retry_key).await.unwrap().unwrap();
+        assert_eq!(retry.last_synced_block, 101);
+
+        // The next pass traces the blocks left behind along with its own
+        indexer.sync_block_range(103, 103, &abis).await.unwrap();
+        assert!(database.get_chain_indexer_state("SN_SEPOLIA", &retry_key).await.unwrap().is_none());
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn test_recorded_backfill_replays_the_same_events() {
+        let scenario = serde_yaml::from_str(include_str!("../tests/fixtures/mock_rpc/scenario.yaml")).unwrap();
+        let (addr, _chain) = crate::mock_rpc::serve(scenario, "127.0.0.1:0".parse().unwrap()).await.unwrap();
+        let dir = std::env::temp_dir().join(format!("recorded_backfill_{}", uuid::Uuid::new_v4()));
+        let config = IndexerConfig { start_block: Some(100), ..IndexerConfig::default() };
+
+        let mut indexed = Vec::new();
+        for (mode, rpc_url) in [(FixtureMode::Record, format!("http://{}", addr)), (FixtureMode::Replay, "http://127.0.0.1:9".to_string())] {
+            // The replay's endpoint is closed, so every answer has to come from the recording
+            let fixtures = RpcFixtures::new(&dir, mode).unwrap();
+            let rpc = RpcContext::new(mock_networks(&rpc_url)).unwrap().with_fixtures(Arc::new(fixtures));
+            let path = std::env::temp_dir().join(format!("recorded_backfill_{}.db", uuid::Uuid::new_v4()));
+            let database = Arc::new(Database::new(&format!("sqlite:{}", path.to_string_lossy())).await.unwrap());
+            let indexer = BlockchainIndexer::new(database.clone(), rpc, "0xc0ffee".to_string(), Some(config.clone()), None);
+
+            indexer.sync_historical_data().await.unwrap();
+
+            let mut events = database.get_events("0xc0ffee", Some("SN_SEPOLIA"), None, None, None, 100, 0).await.unwrap();
+            events.sort_by_key(|e| (e.block_number, e.log_index));
+            let cursor = database.get_chain_indexer_state("SN_SEPOLIA", "0xc0ffee").await.unwrap().unwrap().last_synced_block;
+            indexed.push((events, cursor));
+            let _ = std::fs::remove_file(path);
+        }
+
+        let (recorded, recorded_cursor) = &indexed[0];
+        let (replayed, replayed_cursor) = &indexed[1];
+        assert_eq!(recorded.len(), 3);
+        assert!(recorded.iter().all(|e| e.event_type == "Transfer"));
+        assert_eq!(recorded_cursor, replayed_cursor);
+        assert_eq!(
+            recorded.iter().map(|e| (&e.id, &e.event_type, &e.decoded_data, e.timestamp)).collect::<Vec<_>>(),
+            replayed.iter().map(|e| (&e.id, &e.event_type, &e.decoded_data, e.timestamp)).collect::<Vec<_>>()
+        );
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,199 @@
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use tokio::time::{timeout, Duration};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::{HeaderName, HeaderValue};
+use tokio_tungstenite::tungstenite::Message;
+use crate::rpc_transport::RpcAuth;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+/// Time allowed for the handshake and both subscription confirmations
+const SUBSCRIBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A session without any message for this long is considered dead; heads arrive every block
+pub const IDLE_TIMEOUT: Duration = Duration::from_secs(120);
+
+pub type WsStream = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
+
+#[derive(Debug)]
+pub enum WsError {
+    Unsupported(String), // No WebSocket endpoint, or no RPC 0.8 subscriptions behind it
+    Disconnected(String),
+}
+
+impl std::fmt::Display for WsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WsError::Unsupported(reason) => write!(f, "WebSocket subscriptions unsupported: {}", reason),
+            WsError::Disconnected(reason) => write!(f, "WebSocket disconnected: {}", reason),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Notification {
+    NewHead(u64),
+    Event(Value),
+    Reorg(Value),
+    Other,
+}
+
+/// Subscribed connection, with notifications that arrived while the subscriptions were confirmed
+pub struct WsSession {
+    pub stream: WsStream,
+    pub early: Vec<Value>,
+}
+
+/// Endpoint to use when the network has no WebSocket URL configured: the RPC URL with a ws scheme
+pub fn ws_url_for(rpc_url: &str) -> String {
+    if let Some(rest) = rpc_url.strip_prefix("https://") {
+        format!("wss://{}", rest)
+    } else if let Some(rest) = rpc_url.strip_prefix("http://") {
+        format!("ws://{}", rest)
+    } else {
+        rpc_url.to_string()
+    }
+}
+
+/// Connect with `auth`'s headers and subscribe to new heads and to the contract's events
+pub async fn subscribe(ws_url: &str, auth: &RpcAuth, contract_address: &str) -> Result<WsSession, WsError> {
+    let mut request = ws_url.into_client_request()
+        .map_err(|e| WsError::Unsupported(format!("{}: {}", ws_url, e)))?;
+    for (name, value) in auth.header_pairs() {
+        let name = HeaderName::from_bytes(name.as_bytes()).map_err(|e| WsError::Unsupported(format!("header {}: {}", name, e)))?;
+        let value = HeaderValue::from_str(&value).map_err(|e| WsError::Unsupported(format!("header {}: {}", name, e)))?;
+        request.headers_mut().insert(name, value);
+    }
+
+    let (mut stream, _) = timeout(SUBSCRIBE_TIMEOUT, connect_async(request)).await
+        .map_err(|_| WsError::Unsupported(format!("no handshake from {} within {}s", ws_url, SUBSCRIBE_TIMEOUT.as_secs())))?
+        .map_err(|e| WsError::Unsupported(format!("{}: {}", ws_url, e)))?;
+
+    let requests = [
+        json!({"jsonrpc": "2.0", "id": 1, "method": "starknet_subscribeNewHeads", "params": {}}),
+        json!({"jsonrpc": "2.0", "id": 2, "method": "starknet_subscribeEvents", "params": {"from_address": contract_address}}),
+    ];
+    for request in &requests {
+        stream.send(Message::Text(request.to_string())).await
+            .map_err(|e| WsError::Disconnected(e.to_string()))?;
+    }
+
+    let mut confirmed = 0;
+    let mut early = Vec::new();
+    while confirmed < requests.len() {
+        let message = timeout(SUBSCRIBE_TIMEOUT, next_json(&mut stream)).await
+            .map_err(|_| WsError::Unsupported("subscriptions were not confirmed".to_string()))??;
+
+        if message.get("id").is_none() {
+            early.push(message);
+            continue;
+        }
+        if let Some(error) = message.get("error") {
+            return Err(WsError::Unsupported(format!("subscription rejected: {}", error)));
+        }
+        confirmed += 1;
+    }
+
+    Ok(WsSession { stream, early })
+}
+
+/// Next JSON text message, answering pings on the way
+pub async fn next_json(stream: &mut WsStream) -> Result<Value, WsError> {
+    loop {
+        match stream.next().await {
+            Some(Ok(Message::Text(text))) => {
+                return serde_json::from_str(&text).map_err(|e| WsError::Disconnected(format!("invalid message: {}", e)));
+            }
+            Some(Ok(Message::Ping(payload))) => {
+                stream.send(Message::Pong(payload)).await.map_err(|e| WsError::Disconnected(e.to_string()))?;
+            }
+            Some(Ok(Message::Close(frame))) => {
+                return Err(WsError::Disconnected(format!("closed by server: {:?}", frame)));
+            }
+            Some(Ok(_)) => {}
+            Some(Err(e)) => return Err(WsError::Disconnected(e.to_string())),
+            None => return Err(WsError::Disconnected("stream ended".to_string())),
+        }
+    }
+}
+
+pub fn parse_notification(message: &Value) -> Notification {
+    let result = message.get("params").and_then(|p| p.get("result"));
+    match (message.get("method").and_then(|m| m.as_str()), result) {
+        (Some("starknet_subscriptionNewHeads"), Some(header)) => header.get("block_number")
+            .and_then(|n| n.as_u64())
+            .map(Notification::NewHead)
+            .unwrap_or(Notification::Other),
+        (Some("starknet_subscriptionEvents"), Some(event)) => Notification::Event(event.clone()),
+        (Some("starknet_subscriptionReorg"), Some(reorg)) => Notification::Reorg(reorg.clone()),
+        _ => Notification::Other,
+    }
+}
+
+/// Pushed events held until the head of their block arrives, so each block is written in one piece
+#[derive(Default)]
+pub struct BlockBuffer {
+    blocks: BTreeMap<u64, Vec<Value>>,
+}
+
+impl BlockBuffer {
+    pub fn push(&mut self, block_number: u64, event: Value) {
+        self.blocks.entry(block_number).or_default().push(event);
+    }
+
+    /// Events of every buffered block up to and including `block_number`, oldest block first
+    pub fn take_through(&mut self, block_number: u64) -> Vec<(u64, Vec<Value>)> {
+        let later = self.blocks.split_off(&(block_number + 1));
+        std::mem::replace(&mut self.blocks, later).into_iter().collect()
+    }
+
+    /// Drop the events of `block_number` and later, e.g. of blocks a reorg replaced
+    pub fn discard_from(&mut self, block_number: u64) {
+        self.blocks.split_off(&block_number);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notifications() -> Vec<Value> {
+        serde_json::from_str(include_str!("../tests/fixtures/ws/notifications.json")).unwrap()
+    }
+
+    #[test]
+    fn test_ws_url_is_derived_from_rpc_url() {
+        assert_eq!(ws_url_for("https://starknet-mainnet.public.blastapi.io/rpc/v0_8"), "wss://starknet-mainnet.public.blastapi.io/rpc/v0_8");
+        assert_eq!(ws_url_for("http://127.0.0.1:5050/rpc"), "ws://127.0.0.1:5050/rpc");
+    }
+
+    #[test]
+    fn test_notifications_are_parsed() {
+        let parsed: Vec<Notification> = notifications().iter().map(parse_notification).collect();
+
+        assert_eq!(parsed[0], Notification::NewHead(812001));
+        assert!(matches!(&parsed[1], Notification::Event(event) if event["block_number"] == 812002));
+        assert!(matches!(&parsed[2], Notification::Reorg(reorg) if reorg["starting_block_number"] == 811998));
+        assert_eq!(parsed[3], Notification::Other);
+    }
+
+    #[test]
+    fn test_buffer_releases_blocks_up_to_head() {
+        let mut buffer = BlockBuffer::default();
+        buffer.push(12, json!({"n": 2}));
+        buffer.push(11, json!({"n": 0}));
+        buffer.push(11, json!({"n": 1}));
+        buffer.push(13, json!({"n": 3}));
+
+        let released = buffer.take_through(12);
+        assert_eq!(released.iter().map(|(block, events)| (*block, events.len())).collect::<Vec<_>>(), vec![(11, 2), (12, 1)]);
+        assert_eq!(buffer.take_through(13).len(), 1);
+        assert!(buffer.take_through(20).is_empty());
+
+        buffer.push(14, json!({"n": 4}));
+        buffer.push(15, json!({"n": 5}));
+        buffer.discard_from(15);
+        assert_eq!(buffer.take_through(20).iter().map(|(block, _)| *block).collect::<Vec<_>>(), vec![14]);
+    }
+}
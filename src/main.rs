@@ -10,7 +10,6 @@ use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 use std::env;
 use std::sync::Arc;
-use reqwest::Client;
 use async_graphql::http::GraphiQLSource;
 use async_graphql_axum::{GraphQL, GraphQLSubscription};
 use clap::Parser;
@@ -20,9 +19,27 @@ mod billing;
 mod billing_context;
 mod graphql;
 mod starknet;
+mod abi_registry;
+mod cairo_serde;
+mod decoded_value;
 mod database;
 mod indexer;
 mod realtime;
+mod traces;
+mod networks;
+mod rpc_pool;
+mod rpc_types;
+mod rpc_version;
+mod rpc_fixtures;
+mod rpc_cache;
+mod rpc_transport;
+mod mock_rpc;
+mod rate_limit;
+mod live_ws;
+mod chain_guard;
+mod account_watch;
+mod messages;
+mod class_indexer;
 mod deployment_service;
 mod deployment_service_handler;
 mod api_key_service;
@@ -34,6 +51,24 @@ struct CliArgs {
     #[arg(long, value_name = "URL", value_parser = parse_url, help = "RPC URL for Starknet JSON-RPC (overrides RPC_URL env)")]
     rpc_url: Option<String>,
 
+    #[arg(long, value_name = "NETWORK", help = "Default network for --rpc-url and the background indexer: mainnet, testnet, devnet, local or one from --networks-config (overrides NETWORK env)")]
+    network: Option<String>,
+
+    #[arg(long, value_name = "FILE", help = "JSON file mapping network names to chainId and rpcUrls (overrides NETWORKS_CONFIG env)")]
+    networks_config: Option<String>,
+
+    #[arg(long, value_name = "RPS", help = "Requests per second per RPC endpoint unless configured per network, 0 for unlimited (overrides RPC_RPS env, default 10)")]
+    rpc_rps: Option<f64>,
+
+    #[arg(long, value_name = "REQUESTS", help = "Concurrent requests per RPC endpoint unless configured per network, 0 for unlimited (overrides RPC_MAX_CONCURRENCY env, default 8)")]
+    rpc_max_concurrency: Option<usize>,
+
+    #[arg(long, help = "Keep indexing when the RPC serves another chain than the network or database expects")]
+    allow_chain_mismatch: bool,
+
+    #[arg(long, help = "Drop indexed data of a devnet/local chain when its block hashes show it was restarted")]
+    reset_on_devnet_restart: bool,
+
     #[arg(long, value_name = "CONFIG", help = "Contract configuration: address:start_block,address:start_block (overrides CONTRACT_CONFIG env)")]
     contract_config: Option<String>,
 
@@ -46,17 +81,98 @@ struct CliArgs {
     #[arg(long, value_name = "SECONDS", default_value = "2", help = "Interval between sync checks in seconds")]
     sync_interval: Option<u64>,
 
+    #[arg(long, value_name = "MODE", default_value = "poll", value_parser = ["poll", "websocket"], help = "Follow the head by polling or through RPC 0.8 WebSocket subscriptions (falls back to polling)")]
+    live_mode: String,
+
+    #[arg(long, value_name = "URL", help = "WebSocket endpoint for --live-mode websocket (overrides WS_URL env; default: the RPC URL with a ws scheme)")]
+    ws_url: Option<String>,
+
+    #[arg(long, value_name = "KEY", help = "API key sent to the default network's endpoints (overrides RPC_API_KEY env; header set by RPC_API_KEY_HEADER, default x-api-key)")]
+    rpc_api_key: Option<String>,
+
+    #[arg(long = "rpc-header", value_name = "NAME: VALUE", help = "Extra header for the default network's endpoints, repeatable (overrides RPC_HEADERS env)")]
+    rpc_headers: Vec<String>,
+
+    #[arg(long, value_name = "USER:PASSWORD", help = "Basic auth for the default network's endpoints (overrides RPC_BASIC_AUTH env)")]
+    rpc_basic_auth: Option<String>,
+
+    #[arg(long, value_name = "SECONDS", help = "Timeout of a whole RPC request (overrides RPC_TIMEOUT_SECS env, default 60)")]
+    rpc_timeout: Option<u64>,
+
+    #[arg(long, value_name = "SECONDS", help = "Timeout for connecting to an RPC endpoint (overrides RPC_CONNECT_TIMEOUT_SECS env, default 10)")]
+    rpc_connect_timeout: Option<u64>,
+
+    #[arg(long, help = "Talk HTTP/2 to RPC endpoints without negotiating (sets RPC_HTTP2=1)")]
+    rpc_http2: bool,
+
+    #[arg(long, value_name = "URL", help = "Proxy for all RPC requests (overrides RPC_PROXY env; HTTPS_PROXY and friends apply otherwise)")]
+    rpc_proxy: Option<String>,
+
+    #[arg(long, value_name = "DIR", conflicts_with = "rpc_replay", help = "Write every RPC request and response to fixture files in DIR (overrides RPC_RECORD_DIR env)")]
+    rpc_record: Option<String>,
+
+    #[arg(long, value_name = "DIR", help = "Answer RPC requests from fixtures recorded in DIR, without network access (overrides RPC_REPLAY_DIR env)")]
+    rpc_replay: Option<String>,
+
+    #[arg(long, value_name = "PATH", help = "SQLite file caching immutable RPC responses (overrides RPC_CACHE_PATH env; default: rpc_cache.db)")]
+    rpc_cache: Option<String>,
+
+    #[arg(long, value_name = "MB", help = "Size limit of the RPC cache before least recently used entries are evicted (overrides RPC_CACHE_MAX_MB env; default: 256)")]
+    rpc_cache_max_mb: Option<u64>,
+
+    #[arg(long, conflicts_with = "rpc_cache", help = "Send every RPC request to the providers instead of caching immutable responses")]
+    no_rpc_cache: bool,
+
+    #[arg(long, value_name = "SCENARIO", help = "Only serve a mock Starknet RPC for the chain in this YAML/JSON scenario file, instead of indexing")]
+    mock_rpc: Option<String>,
+
+    #[arg(long, value_name = "PORT", default_value = "5050", help = "Port of the mock RPC server")]
+    mock_rpc_port: u16,
+
     #[arg(long, value_name = "KEYS", help = "Comma-separated list of event keys to filter for")]
     event_keys: Option<String>,
 
     #[arg(long, value_name = "TYPES", help = "Comma-separated list of event types to filter for")]
     event_types: Option<String>,
 
+    #[arg(long, value_name = "MEMBERS", help = "Comma-separated felt252 members holding short strings, as Event.member or member (e.g. Transfer.memo,symbol)")]
+    short_strings: Option<String>,
+
+    #[arg(long, help = "Decode every felt252 that looks like text as a short string (0x4142 -> \"AB\")")]
+    guess_felt_strings: bool,
+
+    #[arg(long, value_name = "ADDRESS=SOURCE[@FROM-TO]", help = "Decode a contract with another ABI: a class hash or an ABI JSON file, optionally for a block range only (repeatable)")]
+    abi_override: Vec<String>,
+
+    #[arg(long, help = "Decode proxy contracts with the ABI of the implementation found in their storage")]
+    resolve_proxies: bool,
+
     #[arg(long, help = "Enable batch processing for better performance")]
     batch_mode: bool,
 
     #[arg(long, value_name = "RETRIES", default_value = "3", help = "Number of retries for failed RPC calls")]
     max_retries: Option<u32>,
+
+    #[arg(long, help = "Record internal calls into indexed contracts using transaction traces (one trace request per block)")]
+    trace_calls: bool,
+
+    #[arg(long, help = "Index the contracts of every active deployment using the RPC of the deployment's network")]
+    index_deployments: bool,
+
+    #[arg(long, help = "Record L2->L1 messages and L1 handler transactions of indexed contracts (one block-with-receipts request per block)")]
+    index_messages: bool,
+
+    #[arg(long, value_name = "ADDRESSES", help = "Comma-separated accounts to follow across all contracts through event keys")]
+    watch_accounts: Option<String>,
+
+    #[arg(long, value_name = "POSITIONS", default_value = "1,2", help = "Event key positions a watched account is matched at (1,2 = Transfer from/to)")]
+    watch_key_positions: String,
+
+    #[arg(long, value_name = "HASHES", help = "Comma-separated class hashes whose new deployments are recorded")]
+    watch_class_hashes: Option<String>,
+
+    #[arg(long, value_name = "ADDRESSES", help = "Comma-separated accounts whose class declarations are recorded")]
+    watch_declarers: Option<String>,
 }
 
 fn parse_url(s: &str) -> Result<String, String> {
@@ -215,43 +331,35 @@ struct DecodedEvent {
 
 
 
-async fn get_contract_abi_handler(Path(contract_address): Path<String>) -> Result<String, StatusCode> {
-    let rpc_url = env::var("RPC_URL").unwrap_or_else(|_| "https://starknet-mainnet.public.blastapi.io".to_string());
-    
-    let rpc_request = serde_json::json!({
+#[allow(clippy::type_complexity)]
+async fn get_contract_abi_handler(
+    axum::extract::State((_database, rpc, _realtime_manager, _billing_service, _cache, _api_key_service)): axum::extract::State<(std::sync::Arc<crate::database::Database>, crate::starknet::RpcContext, std::sync::Arc<crate::realtime::RealtimeEventManager>, std::sync::Arc<crate::billing::BillingService>, crate::deployment_service_handler::SchemaCache, std::sync::Arc<crate::api_key_service::ApiKeyService>)>,
+    Path(contract_address): Path<String>
+) -> Result<String, StatusCode> {
+    // Raw JSON-RPC response of the class, looked up by hash so it is served from the RPC cache
+    let response = fetch_contract_class_response(&rpc, &contract_address).await.map_err(|_| StatusCode::BAD_GATEWAY)?;
+    Ok(response.to_string())
+}
+
+async fn fetch_contract_class_response(rpc: &crate::starknet::RpcContext, contract_address: &str) -> Result<serde_json::Value, String> {
+    let class_hash_request = serde_json::json!({
         "jsonrpc": "2.0",
-        "method": "starknet_getClassAt",
-        "params": [
-            "pending",
-            contract_address
-        ],
+        "method": "starknet_getClassHashAt",
+        "params": ["pending", contract_address],
         "id": 1
     });
+    let class_hash = crate::starknet::rpc_call(rpc, &class_hash_request).await?;
+    let Some(class_hash) = class_hash.get("result").cloned() else {
+        return Ok(class_hash); // The node's error, e.g. CONTRACT_NOT_FOUND
+    };
 
-    let client = Client::new();
-    
-    match client
-        .post(rpc_url)
-        .json(&rpc_request)
-        .send()
-        .await
-    {
-        Ok(response) => {
-            let status = response.status();
-            if status.is_success() {
-                match response.text().await {
-                    Ok(text) => {
-                        // Return the raw JSON response
-                        Ok(text)
-                    }
-                    Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR)
-                }
-            } else {
-                Err(StatusCode::BAD_GATEWAY)
-            }
-        }
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR)
-    }
+    let class_request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": "starknet_getClass",
+        "params": ["pending", class_hash],
+        "id": 1
+    });
+    crate::starknet::rpc_call(rpc, &class_request).await
 }
 
 async fn test_json_handler() -> Json<serde_json::Value> {
@@ -261,7 +369,9 @@ async fn test_json_handler() -> Json<serde_json::Value> {
     }))
 }
 
+#[allow(clippy::type_complexity)]
 async fn fetch_starknet_events_handler(
+    axum::extract::State((_database, rpc, _realtime_manager, _billing_service, _cache, _api_key_service)): axum::extract::State<(std::sync::Arc<crate::database::Database>, crate::starknet::RpcContext, std::sync::Arc<crate::realtime::RealtimeEventManager>, std::sync::Arc<crate::billing::BillingService>, crate::deployment_service_handler::SchemaCache, std::sync::Arc<crate::api_key_service::ApiKeyService>)>,
     request: Option<Json<StarknetEventFilter>>,
 ) -> Result<String, (StatusCode, String)> {
     // Use provided values or defaults
@@ -284,8 +394,6 @@ async fn fetch_starknet_events_handler(
         (address, chunk_size)
     };
     
-    let rpc_url = env::var("RPC_URL").unwrap_or_else(|_| "https://starknet-mainnet.public.blastapi.io".to_string());
-    
     // Get events from Starknet RPC
     let rpc_request = serde_json::json!({
         "jsonrpc": "2.0",
@@ -299,56 +407,24 @@ async fn fetch_starknet_events_handler(
         "id": 1
     });
 
-    let client = Client::new();
-    
-    match client
-        .post(rpc_url)
-        .json(&rpc_request)
-        .send()
-        .await
-    {
-        Ok(response) => {
-            let status = response.status();
-            if status.is_success() {
-                match response.json::<serde_json::Value>().await {
-                    Ok(json_response) => {
-                        // Try to decode events using ABI
-                        let decoded_response = decode_events_with_abi(&json_response, &address).await;
-                        Ok(decoded_response)
-                    }
-                    Err(e) => {
-                        Ok(format!("Error: Failed to parse response - {}", e))
-                    }
-                }
-            } else {
-                let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-                Ok(format!("Error: RPC request failed with status {} - {}", status, error_text))
-            }
+    match crate::starknet::rpc_call(&rpc, &rpc_request).await {
+        Ok(json_response) => {
+            // Try to decode events using ABI
+            let decoded_response = decode_events_with_abi(&rpc, &json_response, &address).await;
+            Ok(decoded_response)
         }
         Err(e) => {
-            Ok(format!("Error: Network error - {}", e))
+            Ok(format!("Error: RPC request failed - {}", e))
         }
     }
 }
 
-async fn decode_events_with_abi(response: &serde_json::Value, contract_address: &str) -> String {
+async fn decode_events_with_abi(rpc: &crate::starknet::RpcContext, response: &serde_json::Value, contract_address: &str) -> String {
     // Get the ABI for the contract
-    let abi_response = get_contract_abi_handler(Path(contract_address.to_string())).await;
+    let abi_response = fetch_contract_class_response(rpc, contract_address).await;
     
     match abi_response {
-        Ok(abi_json_str) => {
-            // First, parse the full RPC response from get_contract_abi_handler
-            let full_abi_rpc_response: serde_json::Value = match serde_json::from_str(&abi_json_str) {
-                Ok(val) => val,
-                Err(_) => {
-                    // If parsing fails, return original response with an error message
-                    return serde_json::to_string_pretty(&serde_json::json!({
-                        "error": "Failed to parse ABI RPC response",
-                        "original_response": response,
-                        "raw_abi_response": abi_json_str
-                    })).unwrap();
-                }
-            };
+        Ok(full_abi_rpc_response) => {
 
             // Now, extract the 'abi' field which is a string, and parse it again into an actual array
             let parsed_abi_array: Option<serde_json::Value> = if let Some(result) = full_abi_rpc_response.get("result") {
@@ -373,18 +449,7 @@ async fn decode_events_with_abi(response: &serde_json::Value, contract_address:
                 
                 if let Some(events_array) = result.get("events").and_then(|e| e.as_array()) {
                     for event in events_array {
-                        if let (Some(data), Some(keys), Some(block_number), Some(tx_hash)) = (
-                            event.get("data"), event.get("keys"), event.get("block_number"), event.get("transaction_hash")
-                        ) {
-                            let decoded_event = decode_single_event(
-                                data.as_array().unwrap_or(&Vec::new()),
-                                keys.as_array().unwrap_or(&Vec::new()),
-                                block_number.as_u64().unwrap_or(0),
-                                tx_hash.as_str().unwrap_or(""),
-                                abi_for_decoding
-                            );
-                            decoded_events.push(decoded_event);
-                        }
+                        decoded_events.push(decode_single_event(event, abi_for_decoding));
                     }
                 }
             }
@@ -403,74 +468,47 @@ async fn decode_events_with_abi(response: &serde_json::Value, contract_address:
     }
 }
 
-fn decode_single_event(
-    data: &[serde_json::Value],
-    keys: &[serde_json::Value],
-    block_number: u64,
-    transaction_hash: &str,
-    abi: &serde_json::Value
-) -> serde_json::Value {
+/// An event of `starknet_getEvents` with its members decoded like indexed events, as `ValueFormat::Typed`
+fn decode_single_event(event: &serde_json::Value, abi: &serde_json::Value) -> serde_json::Value {
+    let (event_type, decoded) = crate::starknet::decode_event_using_abi(abi, event);
     let mut decoded_data = serde_json::Map::new();
     
-    // Try to find the event name and structure from ABI based on the first key (event signature)
-    let (event_name, field_names) = if let Some(first_key) = keys.first() {
-        if let Some(key_str) = first_key.as_str() {
-            find_event_info_from_abi(key_str, abi)
-        } else {
-            ("Unknown".to_string(), Vec::new())
-        }
-    } else {
-        ("Unknown".to_string(), Vec::new())
-    };
-    
     // Only add event_type if we found a real event name from ABI
-    if event_name != "Unknown" {
-        decoded_data.insert("event_type".to_string(), serde_json::Value::String(event_name));
+    if event_type != "Unknown" {
+        decoded_data.insert("event_type".to_string(), serde_json::Value::String(event_type));
     }
-    decoded_data.insert("block_number".to_string(), serde_json::Value::Number(serde_json::Number::from(block_number)));
-    decoded_data.insert("transaction_hash".to_string(), serde_json::Value::String(transaction_hash.to_string()));
-    
-    // Map data to field names from ABI - only use actual ABI field names
-    for (index, value) in data.iter().enumerate() {
-        if index < field_names.len() {
-            decoded_data.insert(field_names[index].clone(), value.clone());
+    decoded_data.insert("block_number".to_string(), event.get("block_number").cloned().unwrap_or_default());
+    decoded_data.insert("transaction_hash".to_string(), event.get("transaction_hash").cloned().unwrap_or_default());
+    // Members only; the raw keys and data are already part of the event
+    for (name, value) in decoded.as_object().into_iter().flatten() {
+        if !crate::decoded_value::is_reserved_field(name) {
+            decoded_data.insert(name.clone(), value.clone());
         }
-        // Don't add fallback param_X names - only use actual ABI field names
+    }
+    if let Some(error) = crate::decoded_value::decode_error(&decoded) {
+        decoded_data.insert("decode_error".to_string(), serde_json::Value::String(error));
     }
     
     serde_json::Value::Object(decoded_data)
 }
 
-fn find_event_info_from_abi(_event_signature: &str, abi: &serde_json::Value) -> (String, Vec<String>) {
-    // Look for events in the ABI - the ABI is directly an array, not nested under "result"
-    if let Some(abi_array) = abi.as_array() {
-        for abi_item in abi_array {
-            if let Some(item_type) = abi_item.get("type").and_then(|t| t.as_str()) {
-                if item_type == "event" {
-                    if let Some(name) = abi_item.get("name").and_then(|n| n.as_str()) {
-                        // Extract field names from the event members
-                        let mut field_names = Vec::new();
-                        if let Some(members) = abi_item.get("members").and_then(|m| m.as_array()) {
-                            for member in members {
-                                if let Some(member_name) = member.get("name").and_then(|n| n.as_str()) {
-                                    field_names.push(member_name.to_string());
-                                }
-                            }
-                        }
-                        
-                        // Extract just the event name (last part after the last "::")
-                        let event_name = name.split("::").last().unwrap_or(name).to_string();
-                        
-                        // Return the first event we find (Transfer, Swap, etc.)
-                        return (event_name, field_names);
-                    }
-                }
-            }
+/// Serve a scenario's chain until interrupted, e.g. for `RPC_URL=http://127.0.0.1:5050/rpc`
+async fn run_mock_rpc(path: &str, port: u16) {
+    let scenario = match crate::mock_rpc::Scenario::load(path) {
+        Ok(scenario) => scenario,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            std::process::exit(1);
+        }
+    };
+    match crate::mock_rpc::serve(scenario, SocketAddr::from(([127, 0, 0, 1], port))).await {
+        Ok((addr, chain)) => println!("🧪 Mock Starknet RPC for {} on http://{}/rpc (head {})", path, addr, chain.head()),
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            std::process::exit(1);
         }
     }
-    
-    // No fallback - only use actual ABI field names
-    ("Unknown".to_string(), Vec::new())
+    let _ = tokio::signal::ctrl_c().await;
 }
 
 #[tokio::main]
@@ -479,9 +517,60 @@ async fn main() {
     dotenv::dotenv().ok();
     // Parse CLI args and override env if provided
     let cli = CliArgs::parse();
+    if let Some(path) = cli.mock_rpc.as_deref() {
+        return run_mock_rpc(path, cli.mock_rpc_port).await;
+    }
     if let Some(url) = cli.rpc_url.as_deref() {
         env::set_var("RPC_URL", url);
     }
+    if let Some(network) = cli.network.as_deref() {
+        env::set_var("NETWORK", network);
+    }
+    if let Some(path) = cli.networks_config.as_deref() {
+        env::set_var("NETWORKS_CONFIG", path);
+    }
+    if let Some(url) = cli.ws_url.as_deref() {
+        env::set_var("WS_URL", url);
+    }
+    if let Some(key) = cli.rpc_api_key.as_deref() {
+        env::set_var("RPC_API_KEY", key);
+    }
+    if !cli.rpc_headers.is_empty() {
+        env::set_var("RPC_HEADERS", cli.rpc_headers.join("; "));
+    }
+    if let Some(credentials) = cli.rpc_basic_auth.as_deref() {
+        env::set_var("RPC_BASIC_AUTH", credentials);
+    }
+    if let Some(secs) = cli.rpc_timeout {
+        env::set_var("RPC_TIMEOUT_SECS", secs.to_string());
+    }
+    if let Some(secs) = cli.rpc_connect_timeout {
+        env::set_var("RPC_CONNECT_TIMEOUT_SECS", secs.to_string());
+    }
+    if cli.rpc_http2 {
+        env::set_var("RPC_HTTP2", "1");
+    }
+    if let Some(proxy) = cli.rpc_proxy.as_deref() {
+        env::set_var("RPC_PROXY", proxy);
+    }
+    if let Some(dir) = cli.rpc_record.as_deref() {
+        env::set_var("RPC_RECORD_DIR", dir);
+    }
+    if let Some(dir) = cli.rpc_replay.as_deref() {
+        env::set_var("RPC_REPLAY_DIR", dir);
+    }
+    if let Some(path) = cli.rpc_cache.as_deref() {
+        env::set_var("RPC_CACHE_PATH", path);
+    }
+    if let Some(max_mb) = cli.rpc_cache_max_mb {
+        env::set_var("RPC_CACHE_MAX_MB", max_mb.to_string());
+    }
+    if let Some(rps) = cli.rpc_rps {
+        env::set_var("RPC_RPS", rps.to_string());
+    }
+    if let Some(concurrency) = cli.rpc_max_concurrency {
+        env::set_var("RPC_MAX_CONCURRENCY", concurrency.to_string());
+    }
 
     
     // Create indexer configuration from CLI args
@@ -508,6 +597,30 @@ async fn main() {
         indexer_config.event_types = Some(event_types.split(',').map(|s| s.trim().to_string()).collect());
         println!("🔧 Using event types filter: {:?}", indexer_config.event_types);
     }
+    if let Some(short_strings) = cli.short_strings {
+        indexer_config.decode_hints.short_strings = short_strings.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect();
+        println!("🔧 Decoding short strings: {:?}", indexer_config.decode_hints.short_strings);
+    }
+    if cli.guess_felt_strings {
+        indexer_config.decode_hints.guess_strings = true;
+        println!("🔧 Guessing text in felt252 values");
+    }
+    for spec in &cli.abi_override {
+        match crate::abi_registry::parse_cli_override(spec) {
+            Ok(record) => {
+                println!("🔧 ABI override for {}: {}", record.contract_address, record.class_hash.as_deref().unwrap_or("uploaded ABI"));
+                indexer_config.abi_overrides.push(record);
+            }
+            Err(e) => {
+                eprintln!("❌ Invalid --abi-override: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    if cli.resolve_proxies {
+        indexer_config.resolve_proxies = true;
+        println!("🔧 Resolving proxy implementations");
+    }
     if cli.batch_mode {
         indexer_config.batch_mode = true;
         println!("🔧 Batch mode enabled");
@@ -516,6 +629,46 @@ async fn main() {
         indexer_config.max_retries = max_retries;
         println!("🔧 Using max retries: {}", max_retries);
     }
+    if cli.trace_calls {
+        indexer_config.trace_calls = true;
+        println!("🔧 Trace-based internal call indexing enabled");
+    }
+    if cli.live_mode == "websocket" {
+        indexer_config.live_mode = crate::indexer::LiveMode::WebSocket;
+        println!("🔧 Live mode: WebSocket subscriptions");
+    }
+    if cli.index_messages {
+        indexer_config.index_messages = true;
+        println!("🔧 L1<->L2 message indexing enabled");
+    }
+
+    let split_list = |list: &Option<String>| -> Vec<String> {
+        list.as_deref()
+            .map(|l| l.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default()
+    };
+    let class_watch_config = crate::class_indexer::ClassWatchConfig {
+        class_hashes: split_list(&cli.watch_class_hashes),
+        declarers: split_list(&cli.watch_declarers),
+        start_block: indexer_config.start_block,
+        sync_interval: indexer_config.sync_interval,
+    };
+
+    let watch_key_positions = split_list(&Some(cli.watch_key_positions.clone()))
+        .iter()
+        .map(|p| p.parse::<usize>())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap_or_else(|_| {
+            eprintln!("❌ --watch-key-positions must be a comma-separated list of numbers");
+            std::process::exit(1);
+        });
+    let account_watch_config = crate::account_watch::AccountWatchConfig {
+        accounts: split_list(&cli.watch_accounts),
+        key_positions: watch_key_positions,
+        start_block: indexer_config.start_block,
+        chunk_size: indexer_config.chunk_size,
+        sync_interval: indexer_config.sync_interval,
+    };
     
     // Parse contract configuration from CLI or environment
     if let Some(contract_config_str) = cli.contract_config.as_deref() {
@@ -577,7 +730,66 @@ async fn main() {
     }
     
     // Build GraphQL schema with database, real-time event manager, and billing service
-    let rpc = crate::starknet::RpcContext::from_env();
+    let networks = match crate::networks::NetworkRegistry::from_env() {
+        Ok(networks) => Arc::new(networks),
+        Err(e) => {
+            eprintln!("❌ Invalid network configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let mut rpc = match crate::starknet::RpcContext::new(networks) {
+        Ok(rpc) => rpc,
+        Err(e) => {
+            eprintln!("❌ Invalid RPC transport configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let fixtures = match (env::var("RPC_REPLAY_DIR"), env::var("RPC_RECORD_DIR")) {
+        (Ok(dir), _) => Some((dir, crate::rpc_fixtures::FixtureMode::Replay)),
+        (_, Ok(dir)) => Some((dir, crate::rpc_fixtures::FixtureMode::Record)),
+        _ => None,
+    };
+    if let Some((dir, mode)) = fixtures {
+        match crate::rpc_fixtures::RpcFixtures::new(&dir, mode) {
+            Ok(fixtures) => {
+                println!("📼 RPC fixtures: {:?} in {}", mode, fixtures.dir().display());
+                rpc = rpc.with_fixtures(Arc::new(fixtures));
+            }
+            Err(e) => {
+                eprintln!("❌ {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    // Replays must not be answered from (or leak into) the cache of real traffic, and entries are keyed by
+    // the expected chain id, so a node allowed to serve another chain must not fill it either
+    if !cli.no_rpc_cache && !rpc.replaying() && !cli.allow_chain_mismatch {
+        let path = env::var("RPC_CACHE_PATH").unwrap_or_else(|_| "rpc_cache.db".to_string());
+        let max_mb: u64 = env::var("RPC_CACHE_MAX_MB").ok().and_then(|v| v.parse().ok()).unwrap_or(256);
+        let finality: u64 = env::var("RPC_CACHE_FINALITY").ok().and_then(|v| v.parse().ok())
+            .unwrap_or(crate::rpc_cache::DEFAULT_FINALITY_DEPTH);
+        match crate::rpc_cache::RpcCache::open(&format!("sqlite:{}", path), max_mb * 1024 * 1024, finality).await {
+            Ok(cache) => {
+                println!("🗄️  RPC cache: {} (max {} MB)", path, max_mb);
+                rpc = rpc.with_cache(Arc::new(cache));
+            }
+            Err(e) => eprintln!("⚠️  RPC cache disabled, could not open {}: {}", path, e),
+        }
+    }
+    let expected_chain = if rpc.chain_id.is_empty() { "chain id taken from the node" } else { rpc.chain_id.as_str() };
+    println!("🌐 Default network: {} ({}) via {}", rpc.network, expected_chain, rpc.rpc_url);
+
+    let chain_guard = crate::chain_guard::ChainGuardOptions {
+        allow_chain_mismatch: cli.allow_chain_mismatch,
+        reset_on_devnet_restart: cli.reset_on_devnet_restart,
+    };
+    // Pick request and response codecs before anything talks to the providers
+    crate::starknet::detect_spec_versions(&rpc).await;
+
+    if let Err(e) = crate::chain_guard::verify_chain(&database, &mut rpc, chain_guard, true).await {
+        eprintln!("❌ Chain verification failed: {}", e);
+        std::process::exit(1);
+    }
     let realtime_manager = Arc::new(crate::realtime::RealtimeEventManager::new());
     let schema = crate::graphql::schema::build_schema(rpc.clone(), database.clone(), realtime_manager.clone(), billing_service.clone());
     
@@ -593,6 +805,7 @@ async fn main() {
         .route("/test", get(test_json_handler))
         .route("/get-abi/:contract_address", get(get_contract_abi_handler))
         .route("/sync-status", get(sync_status_handler))
+        .route("/rpc-status", get(rpc_status_handler))
         .route("/stats/:contract_address", get(indexer_stats_handler))
         // Main GraphQL endpoints
         .route("/graphql", post_service(GraphQL::new(schema.clone())))
@@ -616,6 +829,28 @@ async fn main() {
         axum::serve(listener, app).await.unwrap();
     });
 
+    // Keep endpoint health and heads fresh for every network's pool
+    let health_rpc = rpc.clone();
+    tokio::spawn(async move {
+        crate::starknet::run_endpoint_health_checks(health_rpc, std::time::Duration::from_secs(30)).await;
+    });
+
+    if cli.index_deployments {
+        let deployments_database = database.clone();
+        let deployments_rpc = rpc.clone();
+        let deployments_config = indexer_config.clone();
+        let deployments_realtime = realtime_manager.clone();
+        tokio::spawn(async move {
+            crate::indexer::start_deployment_indexers(
+                deployments_database,
+                deployments_rpc,
+                deployments_config,
+                chain_guard,
+                Some(deployments_realtime),
+            ).await;
+        });
+    }
+
     // Start background indexer for contracts
     let indexer_handle = if let Some(allow_list) = &indexer_config.allow_list {
         // Multi-contract indexing mode
@@ -638,10 +873,26 @@ async fn main() {
         None
     };
 
+    if account_watch_config.is_enabled() {
+        let account_watcher = crate::account_watch::AccountWatcher::new(database.clone(), rpc.clone(), account_watch_config);
+        tokio::spawn(async move {
+            account_watcher.start_syncing().await;
+        });
+    }
+
+    // Deployment and declaration stream runs independently of the event indexer
+    if class_watch_config.is_enabled() {
+        let class_indexer = crate::class_indexer::ClassIndexer::new(database.clone(), rpc.clone(), class_watch_config);
+        tokio::spawn(async move {
+            class_indexer.start_syncing().await;
+        });
+    }
+
     println!("✅ All services started successfully!");
     println!("   📊 GraphQL Playground: http://localhost:3000/graphql");
     println!("   🔍 GraphiQL Interface: http://localhost:3000/graphiql");
     println!("   📈 Sync Status API: http://localhost:3000/sync-status");
+    println!("   🩺 RPC Status API: http://localhost:3000/rpc-status");
     println!("   🔌 WebSocket Subscriptions: ws://localhost:3000/ws");
 
     // Wait for either service to complete (they should run indefinitely)
@@ -753,10 +1004,34 @@ async fn sync_status_handler(
         "blocks_behind": blocks_behind,
         "sync_percentage": format!("{:.2}%", sync_percentage),
         "contract_address": contract_address,
+        "rpc_spec_version": rpc.pool.spec_version(),
         "last_updated": indexer_state.updated_at.to_rfc3339()
     }))
 }
 
+#[allow(clippy::type_complexity)]
+async fn rpc_status_handler(
+    axum::extract::State((_database, rpc, _realtime_manager, _billing_service, _cache, _api_key_service)): axum::extract::State<(std::sync::Arc<crate::database::Database>, crate::starknet::RpcContext, std::sync::Arc<crate::realtime::RealtimeEventManager>, std::sync::Arc<crate::billing::BillingService>, crate::deployment_service_handler::SchemaCache, std::sync::Arc<crate::api_key_service::ApiKeyService>)>
+) -> Json<serde_json::Value> {
+    let networks: serde_json::Map<String, serde_json::Value> = rpc.pools()
+        .map(|(network, pool)| (network.clone(), serde_json::json!({
+            "spec_version": pool.spec_version(),
+            "endpoints": pool.status()
+        })))
+        .collect();
+    let cache = match &rpc.cache {
+        Some(cache) => serde_json::to_value(cache.stats().await).unwrap_or_default(),
+        None => serde_json::Value::Null,
+    };
+
+    Json(serde_json::json!({
+        "default_network": rpc.network,
+        "max_head_lag": crate::rpc_pool::MAX_HEAD_LAG,
+        "networks": networks,
+        "cache": cache
+    }))
+}
+
 async fn indexer_stats_handler(
     axum::extract::State((database, _rpc, _realtime_manager, _billing_service, _cache, _api_key_service)): axum::extract::State<(std::sync::Arc<crate::database::Database>, crate::starknet::RpcContext, std::sync::Arc<crate::realtime::RealtimeEventManager>, std::sync::Arc<crate::billing::BillingService>, crate::deployment_service_handler::SchemaCache, std::sync::Arc<crate::api_key_service::ApiKeyService>)>,
     Path(contract_address): Path<String>
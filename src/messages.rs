@@ -0,0 +1,158 @@
+use crate::class_indexer::block_timestamp;
+use crate::database::{Database, MessageRecord};
+use serde_json::Value;
+use sha3::{Digest, Keccak256};
+
+/// Left-pad a felt (or L1 address) to a 32 byte big-endian word, as `abi.encodePacked(uint256)` does.
+fn felt_to_word(felt: &str) -> Option<[u8; 32]> {
+    let hex_part = felt.strip_prefix("0x").unwrap_or(felt);
+    if hex_part.len() > 64 {
+        return None;
+    }
+    let bytes = hex::decode(format!("{:0>64}", hex_part)).ok()?;
+    bytes.try_into().ok()
+}
+
+fn keccak_words(words: &[&str]) -> Option<String> {
+    let mut hasher = Keccak256::new();
+    for word in words {
+        hasher.update(felt_to_word(word)?);
+    }
+    Some(format!("0x{}", hex::encode(hasher.finalize())))
+}
+
+/// Hash under which StarknetMessaging on L1 tracks an L1→L2 message
+pub fn l1_to_l2_message_hash(from_address: &str, to_address: &str, nonce: &str, selector: &str, payload: &[String]) -> Option<String> {
+    let length = format!("{:x}", payload.len());
+    let mut words = vec![from_address, to_address, nonce, selector, length.as_str()];
+    words.extend(payload.iter().map(|p| p.as_str()));
+    keccak_words(&words)
+}
+
+/// Hash that has to be consumed on L1 for an L2→L1 message
+pub fn l2_to_l1_message_hash(from_address: &str, to_address: &str, payload: &[String]) -> Option<String> {
+    let length = format!("{:x}", payload.len());
+    let mut words = vec![from_address, to_address, length.as_str()];
+    words.extend(payload.iter().map(|p| p.as_str()));
+    keccak_words(&words)
+}
+
+fn felts(value: Option<&Value>) -> Vec<String> {
+    value.and_then(|v| v.as_array())
+        .map(|items| items.iter().filter_map(|i| i.as_str().map(|s| s.to_string())).collect())
+        .unwrap_or_default()
+}
+
+/// Messages sent by `contract_address` and L1 handler transactions into it, from a
+/// `starknet_getBlockWithReceipts` result of chain `chain_id`.
+pub fn messages_for_contract(block: &Value, contract_address: &str, chain_id: &str) -> Vec<MessageRecord> {
+    let target = Database::normalize_address(contract_address);
+    let block_number = block.get("block_number").and_then(|v| v.as_u64()).unwrap_or_default();
+    let timestamp = block_timestamp(block);
+    let mut messages = Vec::new();
+
+    for entry in block.get("transactions").and_then(|v| v.as_array()).into_iter().flatten() {
+        let (Some(tx), Some(receipt)) = (entry.get("transaction"), entry.get("receipt")) else { continue };
+        let Some(tx_hash) = receipt.get("transaction_hash").and_then(|v| v.as_str()) else { continue };
+
+        for (index, message) in receipt.get("messages_sent").and_then(|v| v.as_array()).into_iter().flatten().enumerate() {
+            let from_address = message.get("from_address").and_then(|v| v.as_str()).unwrap_or_default();
+            if Database::normalize_address(from_address) != target {
+                continue;
+            }
+            let to_address = message.get("to_address").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+            let payload = felts(message.get("payload"));
+
+            messages.push(MessageRecord {
+                id: format!("{}:L2_TO_L1:{}", tx_hash, index),
+                contract_address: target.clone(),
+                direction: "L2_TO_L1".to_string(),
+                from_address: target.clone(),
+                message_hash: l2_to_l1_message_hash(from_address, &to_address, &payload).map(|h| Database::normalize_address(&h)),
+                to_address,
+                payload: serde_json::to_string(&payload).unwrap_or_default(),
+                nonce: None,
+                entry_point_selector: None,
+                transaction_hash: tx_hash.to_string(),
+                block_number,
+                timestamp,
+                chain_id: Some(chain_id.to_string()),
+            });
+        }
+
+        let is_l1_handler = tx.get("type").and_then(|v| v.as_str()) == Some("L1_HANDLER");
+        let handler_target = tx.get("contract_address").and_then(|v| v.as_str()).map(Database::normalize_address);
+        if !is_l1_handler || handler_target.as_deref() != Some(target.as_str()) {
+            continue;
+        }
+
+        // The first calldata element of an L1 handler is the L1 sender, the rest is the message payload
+        let calldata = felts(tx.get("calldata"));
+        let Some((from_address, payload)) = calldata.split_first() else { continue };
+        let nonce = tx.get("nonce").and_then(|v| v.as_str()).unwrap_or("0x0").to_string();
+        let selector = tx.get("entry_point_selector").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+
+        // Receipts carry the hash since RPC 0.7; older nodes need it recomputed
+        let message_hash = receipt.get("message_hash")
+            .and_then(|v| v.as_str())
+            .map(|h| h.to_string())
+            .or_else(|| l1_to_l2_message_hash(from_address, &target, &nonce, &selector, payload));
+
+        messages.push(MessageRecord {
+            id: format!("{}:L1_TO_L2:0", tx_hash),
+            contract_address: target.clone(),
+            direction: "L1_TO_L2".to_string(),
+            from_address: from_address.clone(),
+            to_address: target.clone(),
+            payload: serde_json::to_string(payload).unwrap_or_default(),
+            message_hash: message_hash.map(|h| Database::normalize_address(&h)),
+            nonce: Some(nonce),
+            entry_point_selector: Some(selector),
+            transaction_hash: tx_hash.to_string(),
+            block_number,
+            timestamp,
+            chain_id: Some(chain_id.to_string()),
+        });
+    }
+
+    messages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BRIDGE: &str = "0x073314940630fd6dcda0d772d4c972c4e0a9946bef9dabf4ef84eda8ef542b82";
+    const L1_BRIDGE: &str = "0xae0ee0a63a2ce6baeeffe56e7714fb4efe48d419";
+
+    fn block() -> Value {
+        let raw: Value = serde_json::from_str(include_str!("../tests/fixtures/messages/bridge_block.json")).unwrap();
+        raw["result"].clone()
+    }
+
+    #[test]
+    fn test_l1_handlers_are_linked_to_message_hash() {
+        let messages = messages_for_contract(&block(), BRIDGE, "SN_MAIN");
+        let deposits: Vec<_> = messages.iter().filter(|m| m.direction == "L1_TO_L2").collect();
+
+        assert_eq!(deposits.len(), 2);
+        assert_eq!(deposits[0].from_address, L1_BRIDGE);
+        assert_eq!(deposits[0].payload, r#"["0x3f1a2b","0x2386f26fc10000","0x0"]"#);
+        // Taken from the receipt
+        assert_eq!(deposits[0].message_hash.as_deref(), Some("0x7f4b2a1c9e8d6f5a3b2c1d0e9f8a7b6c5d4e3f2a1b0c9d8e7f6a5b4c3d2e1f0a"));
+        // Recomputed for a receipt without message_hash
+        assert_eq!(deposits[1].message_hash.as_deref(), Some("0x52911950c08e63b6db440cd37c26d318a182e4cc08d8ef48feab5c8d20c427c4"));
+        assert_eq!(deposits[1].nonce.as_deref(), Some("0x1a2c"));
+    }
+
+    #[test]
+    fn test_only_messages_from_contract_are_kept() {
+        let messages = messages_for_contract(&block(), BRIDGE, "SN_MAIN");
+        let withdrawals: Vec<_> = messages.iter().filter(|m| m.direction == "L2_TO_L1").collect();
+
+        assert_eq!(withdrawals.len(), 1);
+        assert_eq!(withdrawals[0].id, "0x3d5f7b9e1a3c5e7f9b1d3a5c7e9f1b3d5a7c9e1f3b5d7a9c1e3f5b7d9a1c3e5:L2_TO_L1:1");
+        assert_eq!(withdrawals[0].to_address, L1_BRIDGE);
+        assert_eq!(withdrawals[0].message_hash.as_deref(), Some("0xad2e988271e635e564c4ffc7f5615b8c29adf271431492d6775a35405972bf6f"));
+    }
+}
@@ -0,0 +1,655 @@
+use crate::starknet::sn_keccak;
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Synthetic chain served by the mock RPC, read from a YAML or JSON file
+#[derive(Debug, Clone, Deserialize)]
+pub struct Scenario {
+    #[serde(default = "default_chain_id")]
+    pub chain_id: String, // Short string, e.g. "SN_SEPOLIA"
+    #[serde(default = "default_spec_version")]
+    pub spec_version: String,
+    #[serde(default)]
+    pub head: Option<u64>, // Last block of the chain; the highest listed block when missing
+    #[serde(default)]
+    pub initial_head: Option<u64>, // Head when the server starts; `head` when missing
+    #[serde(default)]
+    pub block_time_ms: u64, // The head advances by one block per interval; 0 keeps it where it is
+    #[serde(default = "default_page_size")]
+    pub max_page_size: u32,
+    #[serde(default = "default_true")]
+    pub batches: bool, // False answers JSON-RPC batches with a single error, like providers without batch support
+    #[serde(default)]
+    pub contracts: Vec<MockContract>,
+    #[serde(default)]
+    pub blocks: Vec<MockBlock>, // Blocks with events; unlisted blocks up to the head exist empty
+    #[serde(default)]
+    pub pending: Option<MockBlock>,
+    #[serde(default)]
+    pub reorgs: Vec<MockReorg>,
+    #[serde(default)]
+    pub faults: Vec<MockFault>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MockContract {
+    pub address: String,
+    #[serde(default = "default_class_hash")]
+    pub class_hash: String,
+    #[serde(default)]
+    pub abi: Value, // Sierra ABI as a list; served as the JSON string nodes return
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MockBlock {
+    #[serde(default)]
+    pub number: u64,
+    #[serde(default)]
+    pub timestamp: Option<u64>,
+    #[serde(default)]
+    pub events: Vec<MockEvent>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MockEvent {
+    #[serde(default)]
+    pub address: Option<String>, // First contract when missing
+    #[serde(default)]
+    pub name: Option<String>, // Event name in the ABI; its selector becomes the first key
+    #[serde(default)]
+    pub keys: Vec<String>,
+    #[serde(default)]
+    pub data: Vec<String>,
+    #[serde(default)]
+    pub tx: Option<usize>, // Transaction index in the block, 0 when missing
+}
+
+/// When the head reaches `at_head`, every block from `from_block` on gets a new hash and the events of
+/// `blocks`; replaced blocks that are not listed become empty.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MockReorg {
+    pub at_head: u64,
+    pub from_block: u64,
+    #[serde(default)]
+    pub blocks: Vec<MockBlock>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FaultKind {
+    RateLimit, // HTTP 429 with Retry-After
+    HttpStatus, // Any other HTTP status, e.g. 503
+    Error, // JSON-RPC error object
+}
+
+/// Failure injected into matching calls: calls number `after` to `after + times - 1` fail
+#[derive(Debug, Clone, Deserialize)]
+pub struct MockFault {
+    #[serde(default)]
+    pub method: Option<String>, // Every method when missing
+    pub kind: FaultKind,
+    #[serde(default)]
+    pub after: u32,
+    #[serde(default = "default_times")]
+    pub times: u32,
+    #[serde(default)]
+    pub status: Option<u16>,
+    #[serde(default)]
+    pub retry_after: Option<u64>,
+    #[serde(default)]
+    pub code: Option<i64>,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+fn default_chain_id() -> String {
+    "SN_SEPOLIA".to_string()
+}
+
+fn default_spec_version() -> String {
+    "0.8.0".to_string()
+}
+
+fn default_page_size() -> u32 {
+    1024
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_times() -> u32 {
+    1
+}
+
+fn default_class_hash() -> String {
+    "0x1".to_string()
+}
+
+impl Scenario {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let raw = std::fs::read_to_string(path).map_err(|e| format!("Failed to read scenario {}: {}", path, e))?;
+        if path.ends_with(".json") {
+            serde_json::from_str(&raw).map_err(|e| format!("Invalid scenario {}: {}", path, e))
+        } else {
+            serde_yaml::from_str(&raw).map_err(|e| format!("Invalid scenario {}: {}", path, e))
+        }
+    }
+
+    fn last_block(&self) -> u64 {
+        self.head.unwrap_or_else(|| self.blocks.iter().map(|b| b.number).max().unwrap_or(0))
+    }
+}
+
+#[derive(Debug)]
+struct ChainState {
+    head: u64,
+    blocks: BTreeMap<u64, MockBlock>,
+    reorgs_applied: Vec<u64>, // `from_block` of every applied reorg
+    fault_calls: Vec<u32>,
+}
+
+/// Mutable chain behind the mock server
+#[derive(Debug)]
+pub struct MockChain {
+    scenario: Scenario,
+    state: Mutex<ChainState>,
+}
+
+impl MockChain {
+    pub fn new(scenario: Scenario) -> Arc<Self> {
+        let head = scenario.initial_head.unwrap_or_else(|| scenario.last_block());
+        let state = ChainState {
+            head,
+            blocks: scenario.blocks.iter().map(|b| (b.number, b.clone())).collect(),
+            reorgs_applied: Vec::new(),
+            fault_calls: vec![0; scenario.faults.len()],
+        };
+        let chain = Arc::new(Self { scenario, state: Mutex::new(state) });
+        chain.apply_reorgs(&mut chain.state.lock().unwrap());
+        chain
+    }
+
+    pub fn head(&self) -> u64 {
+        self.state.lock().unwrap().head
+    }
+
+    /// Move the head one block forward, applying reorgs scripted for the new head
+    pub fn advance(&self) -> u64 {
+        let mut state = self.state.lock().unwrap();
+        state.head = (state.head + 1).min(self.scenario.last_block());
+        self.apply_reorgs(&mut state);
+        state.head
+    }
+
+    fn apply_reorgs(&self, state: &mut ChainState) {
+        for (index, reorg) in self.scenario.reorgs.iter().enumerate() {
+            if reorg.at_head > state.head || index < state.reorgs_applied.len() {
+                continue;
+            }
+            state.blocks.retain(|number, _| *number < reorg.from_block);
+            state.blocks.extend(reorg.blocks.iter().map(|b| (b.number, b.clone())));
+            state.reorgs_applied.push(reorg.from_block);
+            println!("🔀 Mock chain reorganized from block {} at head {}", reorg.from_block, state.head);
+        }
+    }
+
+    fn block_hash(state: &ChainState, number: u64) -> String {
+        let generation = state.reorgs_applied.iter().filter(|from| **from <= number).count();
+        felt(&sn_keccak(&format!("block:{}:{}", number, generation)))
+    }
+
+    fn tx_hash(state: &ChainState, number: u64, tx: usize) -> String {
+        let generation = state.reorgs_applied.iter().filter(|from| **from <= number).count();
+        felt(&sn_keccak(&format!("tx:{}:{}:{}", number, tx, generation)))
+    }
+
+    fn timestamp(block: Option<&MockBlock>, number: u64) -> u64 {
+        block.and_then(|b| b.timestamp).unwrap_or(1_700_000_000 + number * 30)
+    }
+
+    fn default_address(&self) -> String {
+        self.scenario.contracts.first().map(|c| felt(&c.address)).unwrap_or_else(|| "0x1".to_string())
+    }
+
+    fn event_keys(event: &MockEvent) -> Vec<String> {
+        event.name.iter().map(|name| felt(&sn_keccak(name)))
+            .chain(event.keys.iter().map(|k| felt(k)))
+            .collect()
+    }
+
+    /// Events of a block as `starknet_getEvents` returns them; `number` is None for the pending block
+    fn emitted_events(&self, state: &ChainState, block: &MockBlock, number: Option<u64>) -> Vec<Value> {
+        block.events.iter().map(|event| {
+            let mut emitted = json!({
+                "from_address": event.address.as_deref().map(felt).unwrap_or_else(|| self.default_address()),
+                "keys": Self::event_keys(event),
+                "data": event.data.iter().map(|d| felt(d)).collect::<Vec<_>>(),
+                "transaction_hash": match number {
+                    Some(number) => Self::tx_hash(state, number, event.tx.unwrap_or(0)),
+                    None => felt(&sn_keccak(&format!("pending_tx:{}", event.tx.unwrap_or(0)))),
+                },
+            });
+            if let Some(number) = number {
+                emitted["block_hash"] = json!(Self::block_hash(state, number));
+                emitted["block_number"] = json!(number);
+            }
+            emitted
+        }).collect()
+    }
+
+    fn resolve_block(&self, state: &ChainState, block_id: &Value) -> Result<Option<u64>, Value> {
+        let not_found = || rpc_error(24, "Block not found");
+        match block_id {
+            Value::String(tag) if tag == "latest" => Ok(Some(state.head)),
+            Value::String(tag) if tag == "pending" => Ok(None),
+            Value::Object(id) => {
+                if let Some(number) = id.get("block_number").and_then(|n| n.as_u64()) {
+                    return if number <= state.head { Ok(Some(number)) } else { Err(not_found()) };
+                }
+                let hash = id.get("block_hash").and_then(|h| h.as_str()).map(felt).ok_or_else(not_found)?;
+                (0..=state.head).rev().find(|n| Self::block_hash(state, *n) == hash).map(Some).ok_or_else(not_found)
+            }
+            _ => Err(rpc_error(-32602, "Invalid block id")),
+        }
+    }
+
+    fn block_header(&self, state: &ChainState, number: Option<u64>) -> Value {
+        let block = match number {
+            Some(number) => state.blocks.get(&number),
+            None => self.scenario.pending.as_ref(),
+        };
+        let parent = match number {
+            Some(0) => "0x0".to_string(),
+            Some(number) => Self::block_hash(state, number - 1),
+            None => Self::block_hash(state, state.head),
+        };
+        let mut header = json!({
+            "parent_hash": parent,
+            "timestamp": Self::timestamp(block, number.unwrap_or(state.head + 1)),
+            "sequencer_address": "0x1",
+            "l1_gas_price": {"price_in_fri": "0x1", "price_in_wei": "0x1"},
+            "l1_data_gas_price": {"price_in_fri": "0x1", "price_in_wei": "0x1"},
+            "l2_gas_price": {"price_in_fri": "0x1", "price_in_wei": "0x1"},
+            "l1_da_mode": "BLOB",
+            "starknet_version": "0.13.5",
+        });
+        if let Some(number) = number {
+            header["status"] = json!("ACCEPTED_ON_L2");
+            header["block_hash"] = json!(Self::block_hash(state, number));
+            header["block_number"] = json!(number);
+            header["new_root"] = json!("0x0");
+        }
+        header
+    }
+
+    /// Transactions of a block with the events each one emitted, in transaction order
+    fn block_transactions(&self, state: &ChainState, number: Option<u64>) -> Vec<(String, Vec<Value>)> {
+        let block = match number {
+            Some(number) => state.blocks.get(&number).cloned(),
+            None => self.scenario.pending.clone(),
+        };
+        let Some(block) = block else {
+            return Vec::new();
+        };
+
+        let mut transactions: BTreeMap<usize, (String, Vec<Value>)> = BTreeMap::new();
+        for (event, emitted) in block.events.iter().zip(self.emitted_events(state, &block, number)) {
+            let tx_hash = emitted["transaction_hash"].as_str().unwrap_or_default().to_string();
+            transactions.entry(event.tx.unwrap_or(0)).or_insert_with(|| (tx_hash, Vec::new())).1.push(emitted);
+        }
+        transactions.into_values().collect()
+    }
+
+    fn get_events(&self, state: &ChainState, filter: &Value) -> Result<Value, Value> {
+        let chunk_size = filter.get("chunk_size").and_then(|c| c.as_u64()).unwrap_or(0);
+        if chunk_size > self.scenario.max_page_size as u64 {
+            return Err(rpc_error(31, "Requested page size is too big"));
+        }
+        let from = match filter.get("from_block") {
+            Some(id) => self.resolve_block(state, id)?.unwrap_or(state.head + 1),
+            None => 0,
+        };
+        let (to, include_pending) = match filter.get("to_block") {
+            Some(id) => match self.resolve_block(state, id)? {
+                Some(number) => (number, false),
+                None => (state.head, true),
+            },
+            None => (state.head, false),
+        };
+        let address = filter.get("address").and_then(|a| a.as_str()).map(felt);
+        let key_filter: Vec<Vec<String>> = filter.get("keys").and_then(|k| serde_json::from_value(k.clone()).ok()).unwrap_or_default();
+
+        let mut events: Vec<Value> = state.blocks.range(from..=to)
+            .flat_map(|(number, block)| self.emitted_events(state, block, Some(*number)))
+            .collect();
+        if include_pending {
+            if let Some(pending) = &self.scenario.pending {
+                events.extend(self.emitted_events(state, pending, None));
+            }
+        }
+        events.retain(|event| {
+            let keys: Vec<&str> = event["keys"].as_array().map(|k| k.iter().filter_map(|k| k.as_str()).collect()).unwrap_or_default();
+            address.as_deref().map(|a| event["from_address"] == a).unwrap_or(true)
+                && key_filter.iter().enumerate().all(|(position, accepted)| {
+                    accepted.is_empty() || keys.get(position).map(|k| accepted.iter().any(|a| felt(a) == *k)).unwrap_or(false)
+                })
+        });
+
+        let offset = match filter.get("continuation_token").and_then(|t| t.as_str()) {
+            Some(token) => token.parse::<usize>().ok().filter(|o| *o <= events.len())
+                .ok_or_else(|| rpc_error(33, "The supplied continuation token is invalid or unknown"))?,
+            None => 0,
+        };
+        let end = (offset + chunk_size.max(1) as usize).min(events.len());
+        let mut chunk = json!({"events": events[offset..end].to_vec()});
+        if end < events.len() {
+            chunk["continuation_token"] = json!(end.to_string());
+        }
+        Ok(chunk)
+    }
+
+    fn contract(&self, address: &Value) -> Result<&MockContract, Value> {
+        let address = address.as_str().map(felt).unwrap_or_default();
+        self.scenario.contracts.iter().find(|c| felt(&c.address) == address).ok_or_else(|| rpc_error(20, "Contract not found"))
+    }
+
+    fn class(contract: &MockContract) -> Value {
+        json!({
+            "sierra_program": [],
+            "contract_class_version": "0.1.0",
+            "entry_points_by_type": {"CONSTRUCTOR": [], "EXTERNAL": [], "L1_HANDLER": []},
+            "abi": contract.abi.to_string(),
+        })
+    }
+
+    /// Result of one JSON-RPC request, or its error object
+    fn dispatch(&self, method: &str, params: &Value) -> Result<Value, Value> {
+        let state = self.state.lock().unwrap();
+        let param = |index: usize, name: &str| params.get(index).or_else(|| params.get(name)).cloned().unwrap_or(Value::Null);
+
+        match method {
+            "starknet_specVersion" => Ok(json!(self.scenario.spec_version)),
+            "starknet_chainId" => Ok(json!(format!("0x{}", hex::encode(&self.scenario.chain_id)))),
+            "starknet_blockNumber" => Ok(json!(state.head)),
+            "starknet_blockHashAndNumber" => Ok(json!({"block_hash": Self::block_hash(&state, state.head), "block_number": state.head})),
+            "starknet_getBlockWithTxHashes" => {
+                let number = self.resolve_block(&state, &param(0, "block_id"))?;
+                let mut block = self.block_header(&state, number);
+                block["transactions"] = json!(self.block_transactions(&state, number).into_iter().map(|(hash, _)| hash).collect::<Vec<_>>());
+                Ok(block)
+            }
+            "starknet_getBlockWithReceipts" => {
+                let number = self.resolve_block(&state, &param(0, "block_id"))?;
+                let mut block = self.block_header(&state, number);
+                block["transactions"] = json!(self.block_transactions(&state, number).into_iter().map(|(hash, events)| json!({
+                    "transaction": {"transaction_hash": hash, "type": "INVOKE", "version": "0x3", "sender_address": "0x1", "calldata": []},
+                    "receipt": receipt(&hash, events),
+                })).collect::<Vec<_>>());
+                Ok(block)
+            }
+            "starknet_getTransactionReceipt" => {
+                let hash = param(0, "transaction_hash").as_str().map(felt).unwrap_or_default();
+                (0..=state.head).map(Some).chain([None])
+                    .flat_map(|number| self.block_transactions(&state, number))
+                    .find(|(tx_hash, _)| *tx_hash == hash)
+                    .map(|(tx_hash, events)| receipt(&tx_hash, events))
+                    .ok_or_else(|| rpc_error(29, "Transaction hash not found"))
+            }
+            "starknet_getStateUpdate" => {
+                let number = self.resolve_block(&state, &param(0, "block_id"))?;
+                Ok(json!({
+                    "block_hash": number.map(|n| Self::block_hash(&state, n)),
+                    "new_root": "0x0",
+                    "old_root": "0x0",
+                    "state_diff": {"storage_diffs": [], "deprecated_declared_classes": [], "declared_classes": [], "deployed_contracts": [], "replaced_classes": [], "nonces": []}
+                }))
+            }
+            "starknet_traceBlockTransactions" => {
+                self.resolve_block(&state, &param(0, "block_id"))?;
+                Ok(json!([]))
+            }
+            "starknet_getEvents" => self.get_events(&state, &param(0, "filter")),
+            "starknet_getClassAt" => self.contract(&param(1, "contract_address")).map(Self::class),
+            "starknet_getClassHashAt" => self.contract(&param(1, "contract_address")).map(|c| json!(felt(&c.class_hash))),
+            "starknet_getClass" => {
+                let class_hash = param(1, "class_hash").as_str().map(felt).unwrap_or_default();
+                self.scenario.contracts.iter().find(|c| felt(&c.class_hash) == class_hash)
+                    .map(Self::class)
+                    .ok_or_else(|| rpc_error(28, "Class hash not found"))
+            }
+            _ => Err(rpc_error(-32601, "Method not found")),
+        }
+    }
+
+    /// HTTP-level fault for a call to `method`, counting the call
+    fn http_fault(&self, methods: &[&str]) -> Option<Response> {
+        let fault = self.take_fault(methods, |kind| !matches!(kind, FaultKind::Error))?;
+        Some(match fault.kind {
+            FaultKind::RateLimit => (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(header::RETRY_AFTER, fault.retry_after.unwrap_or(1).to_string())],
+                "Too many requests",
+            ).into_response(),
+            _ => {
+                let status = StatusCode::from_u16(fault.status.unwrap_or(503)).unwrap_or(StatusCode::SERVICE_UNAVAILABLE);
+                (status, fault.message.unwrap_or_else(|| "Service unavailable".to_string())).into_response()
+            }
+        })
+    }
+
+    fn take_fault(&self, methods: &[&str], kind: impl Fn(&FaultKind) -> bool) -> Option<MockFault> {
+        let mut state = self.state.lock().unwrap();
+        for (index, fault) in self.scenario.faults.iter().enumerate() {
+            let matches = kind(&fault.kind) && fault.method.as_deref().map(|m| methods.contains(&m)).unwrap_or(true);
+            if !matches {
+                continue;
+            }
+            let call = state.fault_calls[index];
+            state.fault_calls[index] += 1;
+            if call >= fault.after && call < fault.after + fault.times {
+                return Some(fault.clone());
+            }
+        }
+        None
+    }
+
+    fn respond(&self, request: &Value) -> Value {
+        let method = request.get("method").and_then(|m| m.as_str()).unwrap_or_default();
+        let params = request.get("params").cloned().unwrap_or(json!([]));
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+
+        let result = match self.take_fault(&[method], |kind| matches!(kind, FaultKind::Error)) {
+            Some(fault) => Err(rpc_error(fault.code.unwrap_or(-32603), fault.message.as_deref().unwrap_or("Internal error"))),
+            None => self.dispatch(method, &params),
+        };
+        match result {
+            Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+            Err(error) => json!({"jsonrpc": "2.0", "id": id, "error": error}),
+        }
+    }
+}
+
+fn rpc_error(code: i64, message: &str) -> Value {
+    json!({"code": code, "message": message})
+}
+
+/// Felt without leading zeros, as nodes return it
+fn felt(value: &str) -> String {
+    let digits = value.trim_start_matches("0x").trim_start_matches('0').to_lowercase();
+    if digits.is_empty() {
+        "0x0".to_string()
+    } else {
+        format!("0x{}", digits)
+    }
+}
+
+fn receipt(tx_hash: &str, events: Vec<Value>) -> Value {
+    let events: Vec<Value> = events.into_iter()
+        .map(|e| json!({"from_address": e["from_address"], "keys": e["keys"], "data": e["data"]}))
+        .collect();
+    json!({
+        "type": "INVOKE",
+        "transaction_hash": tx_hash,
+        "actual_fee": {"amount": "0x0", "unit": "FRI"},
+        "execution_status": "SUCCEEDED",
+        "finality_status": "ACCEPTED_ON_L2",
+        "messages_sent": [],
+        "events": events,
+        "execution_resources": {"l1_gas": 0, "l1_data_gas": 0, "l2_gas": 0}
+    })
+}
+
+async fn rpc_handler(State(chain): State<Arc<MockChain>>, Json(body): Json<Value>) -> Response {
+    let methods: Vec<&str> = match &body {
+        Value::Array(batch) => batch.iter().filter_map(|r| r.get("method").and_then(|m| m.as_str())).collect(),
+        request => request.get("method").and_then(|m| m.as_str()).into_iter().collect(),
+    };
+    if let Some(fault) = chain.http_fault(&methods) {
+        return fault;
+    }
+
+    match body {
+        Value::Array(_) if !chain.scenario.batches => {
+            Json(json!({"jsonrpc": "2.0", "id": null, "error": rpc_error(-32600, "Batch requests are not supported")})).into_response()
+        }
+        Value::Array(batch) => Json(Value::Array(batch.iter().map(|r| chain.respond(r)).collect())).into_response(),
+        request => Json(chain.respond(&request)).into_response(),
+    }
+}
+
+pub fn router(chain: Arc<MockChain>) -> Router {
+    Router::new().route("/", post(rpc_handler)).route("/rpc", post(rpc_handler)).with_state(chain)
+}
+
+/// Serve the scenario on `addr` (port 0 picks a free one), advancing the head every `block_time_ms`
+pub async fn serve(scenario: Scenario, addr: SocketAddr) -> Result<(SocketAddr, Arc<MockChain>), String> {
+    let block_time = Duration::from_millis(scenario.block_time_ms);
+    let chain = MockChain::new(scenario);
+    let listener = tokio::net::TcpListener::bind(addr).await.map_err(|e| format!("Failed to bind {}: {}", addr, e))?;
+    let local_addr = listener.local_addr().map_err(|e| e.to_string())?;
+
+    let app = router(chain.clone());
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, app).await {
+            eprintln!("❌ Mock RPC server stopped: {}", e);
+        }
+    });
+
+    if !block_time.is_zero() {
+        let ticking = chain.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(block_time).await;
+                ticking.advance();
+            }
+        });
+    }
+
+    Ok((local_addr, chain))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::networks::NetworkRegistry;
+    use crate::rpc_types::{BlockId, EventFilter, RpcError};
+    use crate::starknet::{get_block_hash, get_current_block_number, get_events_chunk, RpcContext};
+
+    fn scenario() -> Scenario {
+        serde_yaml::from_str(include_str!("../tests/fixtures/mock_rpc/scenario.yaml")).unwrap()
+    }
+
+    async fn context(scenarios: Vec<Scenario>) -> (RpcContext, Vec<Arc<MockChain>>) {
+        let mut urls = Vec::new();
+        let mut chains = Vec::new();
+        for scenario in scenarios {
+            let (addr, chain) = serve(scenario, "127.0.0.1:0".parse().unwrap()).await.unwrap();
+            urls.push(format!("\"http://{}\"", addr));
+            chains.push(chain);
+        }
+        let config = format!(r#"{{"mock": {{"chainId": "SN_SEPOLIA", "rpcUrls": [{}]}}}}"#, urls.join(","));
+        let networks = NetworkRegistry::with_networks(NetworkRegistry::parse_config(&config).unwrap(), "mock");
+        (RpcContext::new(Arc::new(networks)).unwrap(), chains)
+    }
+
+    fn filter(chunk_size: u32) -> EventFilter {
+        EventFilter {
+            from_block: Some(BlockId::Number(0)),
+            to_block: Some(BlockId::Latest),
+            address: Some("0x00c0ffee".to_string()),
+            keys: Vec::new(),
+            chunk_size,
+            continuation_token: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_events_are_paged_and_follow_the_head() {
+        let (rpc, chains) = context(vec![scenario()]).await;
+        assert_eq!(get_current_block_number(&rpc).await.unwrap(), 103);
+
+        let mut filter = filter(2);
+        let first = get_events_chunk(&rpc, &filter).await.unwrap();
+        assert_eq!(first.events.len(), 2);
+        assert_eq!(first.events[0].keys[0], felt(&sn_keccak("Transfer")));
+        filter.continuation_token = first.continuation_token;
+        let second = get_events_chunk(&rpc, &filter).await.unwrap();
+        assert_eq!(second.events.len(), 1);
+        assert!(second.continuation_token.is_none());
+
+        assert_eq!(get_events_chunk(&rpc, &self::filter(5000)).await.unwrap_err(), RpcError::PageSizeTooBig);
+
+        let mut pending = self::filter(100);
+        pending.to_block = Some(BlockId::Pending);
+        let with_pending = get_events_chunk(&rpc, &pending).await.unwrap();
+        assert_eq!(with_pending.events.len(), 4);
+        assert!(with_pending.events[3].block_number.is_none());
+
+        chains[0].advance();
+        assert_eq!(get_current_block_number(&rpc).await.unwrap(), 104);
+    }
+
+    #[tokio::test]
+    async fn test_scripted_reorg_replaces_blocks() {
+        let (rpc, chains) = context(vec![scenario()]).await;
+        let hash_before = get_block_hash(&rpc, 102).await.unwrap();
+        let untouched = get_block_hash(&rpc, 101).await.unwrap();
+
+        chains[0].advance();
+        chains[0].advance();
+        assert_eq!(chains[0].head(), 105);
+
+        assert_ne!(get_block_hash(&rpc, 102).await.unwrap(), hash_before);
+        assert_eq!(get_block_hash(&rpc, 101).await.unwrap(), untouched);
+        let events = get_events_chunk(&rpc, &filter(100)).await.unwrap().events;
+        assert_eq!(events.iter().map(|e| e.block_number.unwrap()).collect::<Vec<_>>(), vec![101, 101, 103]);
+    }
+
+    #[tokio::test]
+    async fn test_failing_endpoint_fails_over() {
+        let mut failing = scenario();
+        failing.faults = serde_yaml::from_str("[{kind: http_status, status: 503, times: 100}]").unwrap();
+        let mut limited = scenario();
+        limited.faults = serde_yaml::from_str("[{kind: rate_limit, retry_after: 0, times: 1}]").unwrap();
+
+        let (rpc, _chains) = context(vec![failing, limited]).await;
+        for _ in 0..4 {
+            assert_eq!(get_current_block_number(&rpc).await.unwrap(), 103);
+        }
+        let status = rpc.pool.status();
+        assert!(status[0].errors >= 1);
+        assert_eq!(status[1].errors, 0);
+    }
+}
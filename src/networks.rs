@@ -0,0 +1,330 @@
+use crate::rate_limit::RateLimit;
+use crate::rpc_transport::{RpcAuth, TransportConfig};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::sync::{Arc, RwLock};
+
+/// RPC endpoints and expected chain id of a Starknet network
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkConfig {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub chain_id: String, // Short string form, e.g. "SN_MAIN"; empty for a development network that takes the node's
+    pub rpc_urls: Vec<String>,
+    #[serde(default)]
+    pub rpc_weights: Vec<u32>, // Share of calls per endpoint, aligned with rpc_urls; 1 when missing
+    #[serde(default)]
+    pub rpc_rps: Vec<f64>, // Requests per second per endpoint, aligned with rpc_urls; registry default when missing
+    #[serde(default)]
+    pub rpc_max_concurrency: Vec<usize>, // Concurrent requests per endpoint, aligned with rpc_urls
+    #[serde(default)]
+    pub rpc_auth: Vec<RpcAuth>, // Credentials per endpoint, aligned with rpc_urls
+    #[serde(default)]
+    pub ws_url: Option<String>, // RPC 0.8 WebSocket endpoint; derived from the primary RPC URL when missing
+}
+
+impl NetworkConfig {
+    fn new(name: &str, chain_id: &str, rpc_url: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            chain_id: chain_id.to_string(),
+            rpc_urls: vec![rpc_url.to_string()],
+            rpc_weights: Vec::new(),
+            rpc_rps: Vec::new(),
+            rpc_max_concurrency: Vec::new(),
+            rpc_auth: Vec::new(),
+            ws_url: None,
+        }
+    }
+
+    pub fn primary_rpc_url(&self) -> &str {
+        self.rpc_urls.first().map(|s| s.as_str()).unwrap_or_default()
+    }
+
+    /// Credentials of each endpoint, aligned with rpc_urls
+    pub fn endpoint_auth(&self) -> Vec<RpcAuth> {
+        (0..self.rpc_urls.len()).map(|index| self.rpc_auth.get(index).cloned().unwrap_or_default()).collect()
+    }
+
+    /// Give `auth` to every endpoint that has no credentials of its own
+    fn fill_auth(&mut self, auth: &RpcAuth) {
+        self.rpc_auth = self.endpoint_auth().into_iter()
+            .map(|own| if own.is_empty() { auth.clone() } else { own })
+            .collect();
+    }
+}
+
+/// Networks deployments can be created on, keyed by the name stored in `DeploymentRecord.network`
+#[derive(Debug, Clone)]
+pub struct NetworkRegistry {
+    networks: BTreeMap<String, NetworkConfig>,
+    default_network: String,
+    default_rate_limit: RateLimit, // For endpoints without their own limits
+    transport: TransportConfig, // HTTP settings of every endpoint
+    adopted_chain_ids: Arc<RwLock<BTreeMap<String, String>>>, // Row keys of development networks without a chain id
+}
+
+impl NetworkRegistry {
+    fn builtin() -> BTreeMap<String, NetworkConfig> {
+        [
+            NetworkConfig::new("mainnet", "SN_MAIN", "https://starknet-mainnet.public.blastapi.io"),
+            NetworkConfig::new("testnet", "SN_SEPOLIA", "https://starknet-sepolia.public.blastapi.io"),
+            // Development chains take the chain id their node reports on first contact
+            NetworkConfig::new("devnet", "", "http://127.0.0.1:5050/rpc"),
+            NetworkConfig::new("local", "", "http://127.0.0.1:5050/rpc"),
+        ]
+        .into_iter()
+        .map(|network| (network.name.clone(), network))
+        .collect()
+    }
+
+    /// Built-in networks, then the NETWORKS_CONFIG file, then per-network env overrides:
+    /// - `<NETWORK>_RPC_URLS`: comma-separated endpoints, e.g. TESTNET_RPC_URLS
+    /// - `<NETWORK>_RPC_WEIGHTS`: comma-separated weights matching the endpoints, e.g. 3,1
+    /// - `<NETWORK>_RPC_RPS` / `<NETWORK>_RPC_MAX_CONCURRENCY`: per-endpoint limits, e.g. 25,5
+    /// - `<NETWORK>_CHAIN_ID`: expected chain id, e.g. MAINNET_CHAIN_ID=SN_MAIN; devnet/local take the node's when unset
+    /// - `<NETWORK>_WS_URL`: WebSocket endpoint for the websocket live mode
+    /// - `<NETWORK>_RPC_HEADERS` / `_RPC_API_KEY` / `_RPC_API_KEY_HEADER` / `_RPC_BASIC_AUTH`: credentials of
+    ///   the network's endpoints that have none in the config file
+    /// - `RPC_RPS` / `RPC_MAX_CONCURRENCY`: limits of every endpoint not configured otherwise (0 = unlimited)
+    /// - `RPC_URL` keeps pointing the default network (NETWORK, "mainnet" if unset) at one endpoint,
+    ///   `WS_URL` sets its WebSocket endpoint and `RPC_HEADERS`, `RPC_API_KEY`, ... its credentials
+    /// - Transport settings (timeouts, HTTP/2, keep-alive, gzip, proxy), see `TransportConfig::from_env`
+    pub fn from_env() -> Result<Self, String> {
+        let mut networks = Self::builtin();
+
+        if let Ok(path) = env::var("NETWORKS_CONFIG") {
+            let raw = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read networks config {}: {}", path, e))?;
+            networks.extend(Self::parse_config(&raw)?);
+        }
+
+        for network in networks.values_mut() {
+            let prefix = network.name.to_uppercase().replace('-', "_");
+            if let Ok(urls) = env::var(format!("{}_RPC_URLS", prefix)) {
+                network.rpc_urls = urls.split(',').map(|u| u.trim().to_string()).filter(|u| !u.is_empty()).collect();
+            }
+            if let Ok(weights) = env::var(format!("{}_RPC_WEIGHTS", prefix)) {
+                network.rpc_weights = parse_list(&weights, &format!("{}_RPC_WEIGHTS", prefix))?;
+            }
+            if let Ok(rps) = env::var(format!("{}_RPC_RPS", prefix)) {
+                network.rpc_rps = parse_list(&rps, &format!("{}_RPC_RPS", prefix))?;
+            }
+            if let Ok(concurrency) = env::var(format!("{}_RPC_MAX_CONCURRENCY", prefix)) {
+                network.rpc_max_concurrency = parse_list(&concurrency, &format!("{}_RPC_MAX_CONCURRENCY", prefix))?;
+            }
+            if let Ok(chain_id) = env::var(format!("{}_CHAIN_ID", prefix)) {
+                network.chain_id = chain_id;
+            }
+            if let Ok(ws_url) = env::var(format!("{}_WS_URL", prefix)) {
+                network.ws_url = Some(ws_url);
+            }
+            if let Some(auth) = RpcAuth::from_env(&format!("{}_RPC", prefix))? {
+                network.fill_auth(&auth);
+            }
+        }
+
+        let default_network = env::var("NETWORK").unwrap_or_else(|_| "mainnet".to_string()).to_lowercase();
+        let network = networks.get_mut(&default_network)
+            .ok_or_else(|| format!("Unknown network '{}'", default_network))?;
+        if let Ok(rpc_url) = env::var("RPC_URL") {
+            network.rpc_urls = vec![rpc_url];
+            network.rpc_weights = Vec::new();
+            network.rpc_rps = Vec::new();
+            network.rpc_max_concurrency = Vec::new();
+            network.rpc_auth = Vec::new();
+            network.ws_url = None;
+        }
+        if let Ok(ws_url) = env::var("WS_URL") {
+            network.ws_url = Some(ws_url);
+        }
+        if let Some(auth) = RpcAuth::from_env("RPC")? {
+            network.fill_auth(&auth);
+        }
+
+        let mut default_rate_limit = RateLimit::default();
+        if let Ok(rps) = env::var("RPC_RPS") {
+            default_rate_limit.requests_per_second = rps.trim().parse()
+                .map_err(|_| format!("Invalid RPC_RPS '{}'", rps))?;
+        }
+        if let Ok(concurrency) = env::var("RPC_MAX_CONCURRENCY") {
+            default_rate_limit.max_concurrent = concurrency.trim().parse()
+                .map_err(|_| format!("Invalid RPC_MAX_CONCURRENCY '{}'", concurrency))?;
+        }
+
+        let transport = TransportConfig::from_env()?;
+
+        let registry = Self { networks, default_network, default_rate_limit, transport, adopted_chain_ids: Arc::default() };
+        registry.validate()?;
+        Ok(registry)
+    }
+
+    /// Built-in networks with default limits, ignoring the environment
+    #[cfg(test)]
+    pub fn builtin_only(default_network: &str) -> Self {
+        Self::with_networks(Self::builtin(), default_network)
+    }
+
+    /// Exactly these networks with default limits, ignoring the environment
+    #[cfg(test)]
+    pub fn with_networks(networks: BTreeMap<String, NetworkConfig>, default_network: &str) -> Self {
+        Self {
+            networks,
+            default_network: default_network.to_string(),
+            default_rate_limit: RateLimit::default(),
+            transport: TransportConfig::default(),
+            adopted_chain_ids: Arc::default(),
+        }
+    }
+
+    /// Parse a JSON object of `{"<name>": {"chainId": "...", "rpcUrls": ["..."], "rpcWeights": [..], "rpcRps": [..], "rpcMaxConcurrency": [..], "rpcAuth": [..]}}`
+    pub fn parse_config(raw: &str) -> Result<BTreeMap<String, NetworkConfig>, String> {
+        let parsed: BTreeMap<String, NetworkConfig> = serde_json::from_str(raw)
+            .map_err(|e| format!("Invalid networks config: {}", e))?;
+
+        Ok(parsed.into_iter()
+            .map(|(name, mut network)| {
+                let name = name.to_lowercase();
+                network.name = name.clone();
+                (name, network)
+            })
+            .collect())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for network in self.networks.values() {
+            if network.rpc_urls.is_empty() {
+                return Err(format!("Network '{}' has no RPC endpoints", network.name));
+            }
+            if network.rpc_weights.len() > network.rpc_urls.len()
+                || network.rpc_rps.len() > network.rpc_urls.len()
+                || network.rpc_max_concurrency.len() > network.rpc_urls.len()
+                || network.rpc_auth.len() > network.rpc_urls.len() {
+                return Err(format!("Network '{}' has more RPC weights, limits or credentials than endpoints", network.name));
+            }
+            if network.chain_id.is_empty() && !is_development_network(&network.name) {
+                return Err(format!("Network '{}' has no chain id", network.name));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&NetworkConfig> {
+        self.networks.get(&name.to_lowercase())
+    }
+
+    /// Chain id rows of the network are stored under: the configured one, or the key a development network
+    /// adopted on first contact (empty until then)
+    pub fn chain_id(&self, network: &NetworkConfig) -> String {
+        if !network.chain_id.is_empty() {
+            return network.chain_id.clone();
+        }
+        self.adopted_chain_ids.read().unwrap().get(&network.name).cloned().unwrap_or_default()
+    }
+
+    /// Store rows of a development network without a configured chain id under `chain_key` from now on
+    pub fn adopt_chain_id(&self, network: &str, chain_key: &str) {
+        self.adopted_chain_ids.write().unwrap().insert(network.to_string(), chain_key.to_string());
+    }
+
+    pub fn default_network(&self) -> &NetworkConfig {
+        &self.networks[&self.default_network]
+    }
+
+    pub fn networks(&self) -> impl Iterator<Item = &NetworkConfig> {
+        self.networks.values()
+    }
+
+    /// Limits of each of the network's endpoints, falling back to the registry default
+    pub fn rate_limits(&self, network: &NetworkConfig) -> Vec<RateLimit> {
+        (0..network.rpc_urls.len()).map(|index| RateLimit {
+            requests_per_second: network.rpc_rps.get(index).copied().unwrap_or(self.default_rate_limit.requests_per_second),
+            max_concurrent: network.rpc_max_concurrency.get(index).copied().unwrap_or(self.default_rate_limit.max_concurrent),
+        }).collect()
+    }
+
+    pub fn transport(&self) -> &TransportConfig {
+        &self.transport
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.networks.keys().map(|name| name.as_str()).collect()
+    }
+}
+
+fn parse_list<T: std::str::FromStr>(raw: &str, variable: &str) -> Result<Vec<T>, String> {
+    raw.split(',')
+        .map(|item| item.trim().parse::<T>().map_err(|_| format!("Invalid value '{}' in {}", item.trim(), variable)))
+        .collect()
+}
+
+/// `starknet_chainId` returns the chain id as a felt; decode it to its short string form.
+pub fn chain_id_from_felt(felt: &str) -> String {
+    let hex_part = felt.trim_start_matches("0x");
+    let padded = if hex_part.len() % 2 == 1 { format!("0{}", hex_part) } else { hex_part.to_string() };
+    match hex::decode(&padded) {
+        Ok(bytes) if !bytes.is_empty() && bytes.iter().all(|b| b.is_ascii_graphic()) => {
+            String::from_utf8(bytes).unwrap_or_else(|_| felt.to_string())
+        }
+        _ => felt.to_string(),
+    }
+}
+
+/// Development networks are restarted from genesis routinely, so their data may be reset automatically.
+pub fn is_development_network(name: &str) -> bool {
+    matches!(name, "devnet" | "local")
+}
+
+/// Key the rows of a development network that took `chain_id` from its node are stored under. Stock
+/// nodes report a public chain's id (starknet-devnet says SN_SEPOLIA), so the network name keeps their
+/// rows and cursors apart from that network's, and a reset only drops the development network's own.
+pub fn adopted_chain_key(network: &str, chain_id: &str) -> String {
+    format!("{}:{}", network, chain_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_file_adds_networks() {
+        let networks = NetworkRegistry::parse_config(r#"{
+            "Katana": { "chainId": "KATANA", "rpcUrls": ["http://localhost:5050", "http://localhost:5051"], "rpcWeights": [3, 1], "rpcRps": [50],
+                        "rpcAuth": [{ "apiKey": "k1" }] }
+        }"#).unwrap();
+
+        let katana = &networks["katana"];
+        assert_eq!(katana.name, "katana");
+        assert_eq!(katana.chain_id, "KATANA");
+        assert_eq!(katana.primary_rpc_url(), "http://localhost:5050");
+        assert_eq!(katana.rpc_weights, vec![3, 1]);
+
+        let registry = NetworkRegistry {
+            networks: networks.clone(),
+            default_network: "katana".to_string(),
+            default_rate_limit: RateLimit { requests_per_second: 10.0, max_concurrent: 4 },
+            transport: TransportConfig::default(),
+            adopted_chain_ids: Arc::default(),
+        };
+        assert_eq!(registry.rate_limits(katana), vec![
+            RateLimit { requests_per_second: 50.0, max_concurrent: 4 },
+            RateLimit { requests_per_second: 10.0, max_concurrent: 4 },
+        ]);
+
+        // Endpoints without credentials of their own take the network's
+        let mut katana = katana.clone();
+        katana.fill_auth(&RpcAuth { api_key: Some("shared".to_string()), ..Default::default() });
+        let keys: Vec<Option<String>> = katana.endpoint_auth().into_iter().map(|auth| auth.api_key).collect();
+        assert_eq!(keys, vec![Some("k1".to_string()), Some("shared".to_string())]);
+    }
+
+    #[test]
+    fn test_chain_id_is_decoded() {
+        assert_eq!(chain_id_from_felt("0x534e5f4d41494e"), "SN_MAIN");
+        assert_eq!(chain_id_from_felt("0x534e5f5345504f4c4941"), "SN_SEPOLIA");
+        assert_eq!(chain_id_from_felt("0x0"), "0x0");
+    }
+}
@@ -0,0 +1,237 @@
+use std::sync::Mutex;
+use tokio::sync::Notify;
+use tokio::time::{Duration, Instant};
+
+/// Outbound budget of one RPC provider; 0 means unlimited
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    pub requests_per_second: f64,
+    pub max_concurrent: usize,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            requests_per_second: 10.0,
+            max_concurrent: 8,
+        }
+    }
+}
+
+/// Live-head traffic is served before backfill traffic waiting on the same provider
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RpcPriority {
+    Live,
+    Backfill,
+}
+
+#[derive(Debug)]
+struct LimiterState {
+    tokens: f64,
+    refilled_at: Instant,
+    in_flight: usize,
+    live_waiting: usize,
+    paused_until: Option<Instant>,
+}
+
+/// Token bucket plus concurrency cap for one provider. The bucket holds up to one second of requests,
+/// so short bursts go through at once and sustained traffic settles at `requests_per_second`.
+#[derive(Debug)]
+pub struct RateLimiter {
+    limit: RateLimit,
+    state: Mutex<LimiterState>,
+    changed: Notify,
+}
+
+/// Held while a request is in flight; dropping it frees the concurrency slot
+pub struct RatePermit<'a> {
+    limiter: &'a RateLimiter,
+}
+
+/// Counts a live caller as waiting until it gets its permit or gives up
+struct LiveWaiter<'a> {
+    limiter: &'a RateLimiter,
+}
+
+impl RateLimiter {
+    pub fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            state: Mutex::new(LimiterState {
+                tokens: Self::burst(&limit),
+                refilled_at: Instant::now(),
+                in_flight: 0,
+                live_waiting: 0,
+                paused_until: None,
+            }),
+            changed: Notify::new(),
+        }
+    }
+
+    fn burst(limit: &RateLimit) -> f64 {
+        limit.requests_per_second.max(1.0)
+    }
+
+    pub fn limit(&self) -> RateLimit {
+        self.limit
+    }
+
+    /// Wait for a request slot on this provider. `cost` is the number of requests sent at once:
+    /// providers meter the entries of a batch one by one.
+    pub async fn acquire(&self, priority: RpcPriority, cost: usize) -> RatePermit<'_> {
+        let _waiter = (priority == RpcPriority::Live).then(|| {
+            self.state.lock().unwrap().live_waiting += 1;
+            LiveWaiter { limiter: self }
+        });
+
+        loop {
+            // Registered before checking, so a release between the check and the wait is not missed
+            let changed = self.changed.notified();
+
+            let wait = {
+                let mut state = self.state.lock().unwrap();
+                match self.try_take(&mut state, priority, cost, Instant::now()) {
+                    Ok(()) => return RatePermit { limiter: self },
+                    Err(wait) => wait,
+                }
+            };
+
+            match wait {
+                Some(delay) => {
+                    tokio::select! {
+                        _ = tokio::time::sleep(delay) => {}
+                        _ = changed => {}
+                    }
+                }
+                None => changed.await,
+            }
+        }
+    }
+
+    /// Take a token and a concurrency slot, or say how long to wait (`None`: until a permit is released)
+    fn try_take(&self, state: &mut LimiterState, priority: RpcPriority, cost: usize, now: Instant) -> Result<(), Option<Duration>> {
+        let rps = self.limit.requests_per_second;
+        if rps > 0.0 {
+            let elapsed = now.saturating_duration_since(state.refilled_at).as_secs_f64();
+            state.tokens = (state.tokens + elapsed * rps).min(Self::burst(&self.limit));
+        }
+        state.refilled_at = now;
+
+        if let Some(until) = state.paused_until {
+            if now < until {
+                return Err(Some(until - now));
+            }
+            state.paused_until = None;
+        }
+
+        if priority == RpcPriority::Backfill {
+            if state.live_waiting > 0 {
+                return Err(None);
+            }
+            // Keep one slot free for live traffic when there is more than one
+            let backfill_slots = self.limit.max_concurrent.saturating_sub(1).max(1);
+            if self.limit.max_concurrent > 0 && state.in_flight >= backfill_slots {
+                return Err(None);
+            }
+        }
+
+        if self.limit.max_concurrent > 0 && state.in_flight >= self.limit.max_concurrent {
+            return Err(None);
+        }
+
+        if rps > 0.0 {
+            // A batch larger than the bucket only has to wait for a full bucket
+            let needed = (cost.max(1) as f64).min(Self::burst(&self.limit));
+            if state.tokens < needed {
+                return Err(Some(Duration::from_secs_f64((needed - state.tokens) / rps)));
+            }
+            state.tokens -= needed;
+        }
+
+        state.in_flight += 1;
+        Ok(())
+    }
+
+    /// The provider answered 429: stop sending to it for `retry_after`
+    pub fn throttle(&self, retry_after: Duration) {
+        let mut state = self.state.lock().unwrap();
+        let until = Instant::now() + retry_after;
+        state.paused_until = Some(state.paused_until.map_or(until, |current| current.max(until)));
+        state.tokens = 0.0;
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.state.lock().unwrap().in_flight
+    }
+}
+
+impl Drop for RatePermit<'_> {
+    fn drop(&mut self) {
+        self.limiter.state.lock().unwrap().in_flight -= 1;
+        self.limiter.changed.notify_waiters();
+    }
+}
+
+impl Drop for LiveWaiter<'_> {
+    fn drop(&mut self) {
+        self.limiter.state.lock().unwrap().live_waiting -= 1;
+        self.limiter.changed.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(requests_per_second: f64, max_concurrent: usize) -> RateLimiter {
+        RateLimiter::new(RateLimit { requests_per_second, max_concurrent })
+    }
+
+    #[test]
+    fn test_bucket_refills_at_configured_rate() {
+        let limiter = limiter(2.0, 0);
+        let mut state = limiter.state.lock().unwrap();
+        let start = state.refilled_at;
+
+        assert!(limiter.try_take(&mut state, RpcPriority::Live, 1, start).is_ok());
+        assert!(limiter.try_take(&mut state, RpcPriority::Live, 1, start).is_ok());
+        assert_eq!(limiter.try_take(&mut state, RpcPriority::Live, 1, start), Err(Some(Duration::from_millis(500))));
+        assert!(limiter.try_take(&mut state, RpcPriority::Live, 1, start + Duration::from_millis(500)).is_ok());
+
+        // A batch waits for as many tokens as it has requests
+        let later = start + Duration::from_secs(10);
+        assert!(limiter.try_take(&mut state, RpcPriority::Live, 2, later).is_ok());
+        assert_eq!(limiter.try_take(&mut state, RpcPriority::Live, 2, later), Err(Some(Duration::from_secs(1))));
+    }
+
+    #[test]
+    fn test_backfill_yields_to_live_traffic() {
+        let limiter = limiter(0.0, 3);
+        let mut state = limiter.state.lock().unwrap();
+        let now = Instant::now();
+
+        state.live_waiting = 1;
+        assert_eq!(limiter.try_take(&mut state, RpcPriority::Backfill, 1, now), Err(None));
+
+        state.live_waiting = 0;
+        assert!(limiter.try_take(&mut state, RpcPriority::Backfill, 1, now).is_ok());
+        assert!(limiter.try_take(&mut state, RpcPriority::Backfill, 1, now).is_ok());
+        // The last slot is kept for live traffic
+        assert_eq!(limiter.try_take(&mut state, RpcPriority::Backfill, 1, now), Err(None));
+        assert!(limiter.try_take(&mut state, RpcPriority::Live, 1, now).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_permits_cap_concurrency() {
+        let limiter = limiter(0.0, 1);
+        let permit = limiter.acquire(RpcPriority::Live, 1).await;
+        assert_eq!(limiter.in_flight(), 1);
+
+        let waiting = tokio::time::timeout(Duration::from_millis(20), limiter.acquire(RpcPriority::Live, 1)).await;
+        assert!(waiting.is_err());
+
+        drop(permit);
+        let _permit = limiter.acquire(RpcPriority::Live, 1).await;
+        assert_eq!(limiter.in_flight(), 1);
+    }
+}
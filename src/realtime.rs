@@ -87,7 +87,7 @@ impl RealtimeEventManager {
 
         // Check event types if specified
         if let Some(ref event_types) = filter.event_types {
-            if !event_types.contains(&event.event_type) {
+            if !crate::starknet::event_type_matches(event_types, &event.event_type) {
                 return false;
             }
         }
@@ -0,0 +1,292 @@
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::Value;
+use sha3::{Digest, Keccak256};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Blocks below the head after which block data is treated as final
+pub const DEFAULT_FINALITY_DEPTH: u64 = 100;
+
+/// Methods whose answers can be served from the cache: classes by hash, and block data and receipts
+/// once they are deeper than the finality depth
+const CACHED_METHODS: &[&str] = &[
+    "starknet_getClass",
+    "starknet_getBlockWithTxHashes",
+    "starknet_getBlockWithTxs",
+    "starknet_getBlockWithReceipts",
+    "starknet_getStateUpdate",
+    "starknet_traceBlockTransactions",
+    "starknet_getTransactionReceipt",
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheStats {
+    pub entries: u64,
+    pub bytes: u64,
+    pub max_bytes: u64,
+    pub hits: u64,
+    pub misses: u64,
+    pub stores: u64,
+    pub evictions: u64,
+}
+
+/// Persistent cache of immutable RPC responses in its own SQLite file. Entries are keyed by a hash of
+/// the chain id and the request, so every network shares one file; the least recently used entries are
+/// evicted once the stored responses exceed `max_bytes`.
+#[derive(Debug)]
+pub struct RpcCache {
+    pool: SqlitePool,
+    max_bytes: u64,
+    finality_depth: u64,
+    bytes: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    stores: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl RpcCache {
+    pub async fn open(database_url: &str, max_bytes: u64, finality_depth: u64) -> Result<Self, sqlx::Error> {
+        let options = SqliteConnectOptions::from_str(database_url)?.create_if_missing(true);
+        let pool = SqlitePool::connect_with(options).await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS rpc_cache (
+                key TEXT PRIMARY KEY,
+                chain_id TEXT NOT NULL,
+                method TEXT NOT NULL,
+                response TEXT NOT NULL,
+                size INTEGER NOT NULL,
+                created_at TEXT NOT NULL,
+                last_used_at TEXT NOT NULL
+            )
+            "#
+        ).execute(&pool).await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_rpc_cache_last_used ON rpc_cache(last_used_at)")
+            .execute(&pool).await?;
+
+        let bytes: i64 = sqlx::query_scalar("SELECT COALESCE(SUM(size), 0) FROM rpc_cache").fetch_one(&pool).await?;
+
+        Ok(Self {
+            pool,
+            max_bytes,
+            finality_depth,
+            bytes: AtomicU64::new(bytes as u64),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            stores: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        })
+    }
+
+    /// Cached response to `payload`, carrying the payload's id
+    pub async fn get(&self, chain_id: &str, payload: &Value) -> Option<Value> {
+        let key = cache_key(chain_id, payload)?;
+        let cached: Option<String> = sqlx::query_scalar("SELECT response FROM rpc_cache WHERE key = ?")
+            .bind(&key)
+            .fetch_optional(&self.pool)
+            .await
+            .unwrap_or_else(|e| {
+                eprintln!("⚠️  RPC cache read failed: {}", e);
+                None
+            });
+
+        let Some(mut response) = cached.and_then(|c| serde_json::from_str::<Value>(&c).ok()) else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            return None;
+        };
+        self.hits.fetch_add(1, Ordering::Relaxed);
+
+        let _ = sqlx::query("UPDATE rpc_cache SET last_used_at = ? WHERE key = ?")
+            .bind(Utc::now().to_rfc3339())
+            .bind(&key)
+            .execute(&self.pool)
+            .await;
+        response["id"] = payload.get("id").cloned().unwrap_or(Value::Null);
+        Some(response)
+    }
+
+    /// Store `response` if it can no longer change with the chain at `head`
+    pub async fn put(&self, chain_id: &str, payload: &Value, response: &Value, head: Option<u64>) {
+        if !is_final(payload, response, head.map(|h| h.saturating_sub(self.finality_depth))) {
+            return;
+        }
+        let Some(key) = cache_key(chain_id, payload) else {
+            return;
+        };
+
+        let text = response.to_string();
+        let size = text.len() as u64;
+        if size > self.max_bytes {
+            return;
+        }
+        let now = Utc::now().to_rfc3339();
+        let stored = sqlx::query(
+            "INSERT OR IGNORE INTO rpc_cache (key, chain_id, method, response, size, created_at, last_used_at) VALUES (?, ?, ?, ?, ?, ?, ?)"
+        )
+            .bind(&key)
+            .bind(chain_id)
+            .bind(payload.get("method").and_then(|m| m.as_str()).unwrap_or_default())
+            .bind(&text)
+            .bind(size as i64)
+            .bind(&now)
+            .bind(&now)
+            .execute(&self.pool)
+            .await;
+
+        match stored {
+            Ok(result) if result.rows_affected() > 0 => {
+                self.stores.fetch_add(1, Ordering::Relaxed);
+                if self.bytes.fetch_add(size, Ordering::Relaxed) + size > self.max_bytes {
+                    self.evict().await;
+                }
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("⚠️  RPC cache write failed: {}", e),
+        }
+    }
+
+    /// Drop least recently used entries until the cache is back under 90% of its limit
+    async fn evict(&self) {
+        let target = self.max_bytes / 10 * 9;
+        while self.bytes.load(Ordering::Relaxed) > target {
+            let oldest: Vec<(String, i64)> = match sqlx::query_as("SELECT key, size FROM rpc_cache ORDER BY last_used_at ASC LIMIT 100")
+                .fetch_all(&self.pool)
+                .await
+            {
+                Ok(oldest) if !oldest.is_empty() => oldest,
+                _ => return,
+            };
+
+            for (key, size) in oldest {
+                if self.bytes.load(Ordering::Relaxed) <= target {
+                    return;
+                }
+                if sqlx::query("DELETE FROM rpc_cache WHERE key = ?").bind(&key).execute(&self.pool).await.is_ok() {
+                    self.bytes.fetch_sub(size as u64, Ordering::Relaxed);
+                    self.evictions.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+    }
+
+    pub async fn stats(&self) -> CacheStats {
+        let entries: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM rpc_cache").fetch_one(&self.pool).await.unwrap_or(0);
+        CacheStats {
+            entries: entries as u64,
+            bytes: self.bytes.load(Ordering::Relaxed),
+            max_bytes: self.max_bytes,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            stores: self.stores.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Content address of a cacheable request: the chain and the request without its id. Classes are
+/// addressed by hash alone, whatever block the lookup named. Requests that are never cached have none.
+fn cache_key(chain_id: &str, payload: &Value) -> Option<String> {
+    let method = payload.get("method").and_then(|m| m.as_str())?;
+    if !CACHED_METHODS.contains(&method) {
+        return None;
+    }
+    let params = payload.get("params").cloned().unwrap_or(Value::Null);
+    let addressed = match method {
+        "starknet_getClass" => param(&params, 1, "class_hash")?,
+        "starknet_getTransactionReceipt" => param(&params, 0, "transaction_hash")?,
+        // Only blocks named by number; tags and hashes are looked up on the node
+        _ => param(&params, 0, "block_id")?.get("block_number").cloned()?,
+    };
+
+    let digest = Keccak256::digest(format!("{}|{}|{}", chain_id, method, addressed).as_bytes());
+    Some(hex::encode(digest))
+}
+
+fn param(params: &Value, index: usize, name: &str) -> Option<Value> {
+    params.get(index).or_else(|| params.get(name)).cloned()
+}
+
+/// Whether a response is immutable: classes always are, block data once the block is at or below
+/// `finalized`, and receipts once accepted on L1 or in such a block
+fn is_final(payload: &Value, response: &Value, finalized: Option<u64>) -> bool {
+    let Some(result) = response.get("result") else {
+        return false; // Errors such as BLOCK_NOT_FOUND may not hold for long
+    };
+    let method = payload.get("method").and_then(|m| m.as_str()).unwrap_or_default();
+    let params = payload.get("params").cloned().unwrap_or(Value::Null);
+    let is_final_block = |number: Option<u64>| matches!((number, finalized), (Some(n), Some(f)) if n <= f);
+
+    match method {
+        "starknet_getClass" => true,
+        "starknet_getTransactionReceipt" => {
+            result.get("finality_status").and_then(|s| s.as_str()) == Some("ACCEPTED_ON_L1")
+                || is_final_block(result.get("block_number").and_then(|n| n.as_u64()))
+        }
+        m if CACHED_METHODS.contains(&m) => {
+            is_final_block(param(&params, 0, "block_id").and_then(|id| id.get("block_number").and_then(|n| n.as_u64())))
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn request(method: &str, params: Value) -> Value {
+        json!({"jsonrpc": "2.0", "method": method, "params": params, "id": 1})
+    }
+
+    #[test]
+    fn test_only_immutable_responses_are_final() {
+        let header = json!({"result": {"block_number": 900}});
+        let old_block = request("starknet_getBlockWithTxHashes", json!([{"block_number": 900}]));
+        let latest = request("starknet_getBlockWithTxHashes", json!(["latest"]));
+
+        assert!(is_final(&old_block, &header, Some(900)));
+        assert!(!is_final(&old_block, &header, Some(899)));
+        assert!(!is_final(&old_block, &header, None));
+        assert!(cache_key("SN_MAIN", &latest).is_none());
+        assert!(!is_final(&old_block, &json!({"error": {"code": 24}}), Some(1000)));
+
+        let class = request("starknet_getClass", json!(["pending", "0xabc"]));
+        assert!(is_final(&class, &json!({"result": {}}), None));
+        // Addressed by class hash, whatever block the lookup named
+        assert_eq!(cache_key("SN_MAIN", &class), cache_key("SN_MAIN", &request("starknet_getClass", json!(["latest", "0xabc"]))));
+        assert_ne!(cache_key("SN_MAIN", &class), cache_key("SN_SEPOLIA", &class));
+
+        let receipt = request("starknet_getTransactionReceipt", json!(["0x1"]));
+        assert!(is_final(&receipt, &json!({"result": {"finality_status": "ACCEPTED_ON_L1"}}), None));
+        assert!(!is_final(&receipt, &json!({"result": {"finality_status": "ACCEPTED_ON_L2", "block_number": 950}}), Some(900)));
+        assert!(cache_key("SN_MAIN", &request("starknet_getClassAt", json!(["pending", "0xabc"]))).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cache_counts_hits_and_evicts_least_recently_used() {
+        let path = std::env::temp_dir().join(format!("rpc_cache_{}.db", uuid::Uuid::new_v4()));
+        let cache = RpcCache::open(&format!("sqlite:{}", path.to_string_lossy()), 400, 10).await.unwrap();
+        let block = |n: u64| request("starknet_getBlockWithTxHashes", json!([{"block_number": n}]));
+        let response = |n: u64| json!({"jsonrpc": "2.0", "id": 1, "result": {"block_number": n, "padding": "x".repeat(100)}});
+
+        cache.put("SN_MAIN", &block(1), &response(1), Some(100)).await;
+        cache.put("SN_MAIN", &block(95), &response(95), Some(100)).await; // Not final yet
+        assert_eq!(cache.get("SN_MAIN", &block(1)).await.unwrap()["result"]["block_number"], 1);
+        assert!(cache.get("SN_MAIN", &block(95)).await.is_none());
+
+        for n in 2..=4 {
+            cache.put("SN_MAIN", &block(n), &response(n), Some(100)).await;
+        }
+        let stats = cache.stats().await;
+        assert_eq!((stats.hits, stats.misses, stats.stores), (1, 1, 4));
+        assert!(stats.evictions >= 1);
+        assert!(stats.bytes <= 400);
+        assert!(cache.get("SN_MAIN", &block(4)).await.is_some());
+
+        let _ = std::fs::remove_file(path);
+    }
+}
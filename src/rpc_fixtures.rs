@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixtureMode {
+    Record, // Call the providers and write every request/response pair
+    Replay, // Answer from the recorded pairs without touching the network
+}
+
+/// Responses recorded for one request. A request made several times (e.g. `starknet_blockNumber`) is
+/// answered with its responses in recorded order, the last one repeating.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FixtureEntry {
+    params: Value,
+    responses: Vec<Value>,
+}
+
+#[derive(Debug, Default)]
+struct FixtureState {
+    entries: HashMap<(String, String), Vec<FixtureEntry>>, // (network, method) -> entries, in file order
+    served: HashMap<(String, String, String), usize>, // Replay position per (network, method, params)
+}
+
+/// Record-and-replay store for JSON-RPC traffic. Fixtures live in `<dir>/<network>/<method>.json`,
+/// one pretty-printed array of entries per method, so recordings can be reviewed and edited by hand.
+#[derive(Debug)]
+pub struct RpcFixtures {
+    dir: PathBuf,
+    mode: FixtureMode,
+    state: Mutex<FixtureState>,
+}
+
+impl RpcFixtures {
+    /// Store for `dir`. Replay loads every recorded network up front; recording appends to whatever
+    /// is already there.
+    pub fn new(dir: impl Into<PathBuf>, mode: FixtureMode) -> Result<Self, String> {
+        let dir = dir.into();
+        let mut state = FixtureState::default();
+
+        if dir.is_dir() {
+            for network_dir in read_dir(&dir)?.into_iter().filter(|p| p.is_dir()) {
+                let network = file_name(&network_dir);
+                for file in read_dir(&network_dir)?.into_iter().filter(|p| p.extension().map(|e| e == "json").unwrap_or(false)) {
+                    let method = file.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+                    let text = std::fs::read_to_string(&file).map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+                    let entries: Vec<FixtureEntry> = serde_json::from_str(&text)
+                        .map_err(|e| format!("Invalid fixture {}: {}", file.display(), e))?;
+                    state.entries.insert((network.clone(), method), entries);
+                }
+            }
+        } else if mode == FixtureMode::Replay {
+            return Err(format!("Fixture directory {} does not exist", dir.display()));
+        }
+
+        Ok(Self { dir, mode, state: Mutex::new(state) })
+    }
+
+    pub fn mode(&self) -> FixtureMode {
+        self.mode
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Recorded response to `payload`, carrying the payload's id
+    pub fn replay(&self, network: &str, payload: &Value) -> Result<Value, String> {
+        let (method, params) = request_key(payload);
+        let mut state = self.state.lock().unwrap();
+
+        let entry = state.entries.get(&(network.to_string(), method.clone()))
+            .and_then(|entries| entries.iter().find(|entry| entry.params == params))
+            .filter(|entry| !entry.responses.is_empty())
+            .cloned()
+            .ok_or_else(|| format!("No recorded response for {} {} on {} in {}", method, params, network, self.dir.display()))?;
+
+        let position = state.served.entry((network.to_string(), method, params.to_string())).or_insert(0);
+        let mut response = entry.responses[(*position).min(entry.responses.len() - 1)].clone();
+        *position += 1;
+
+        response["id"] = payload.get("id").cloned().unwrap_or(Value::Null);
+        Ok(response)
+    }
+
+    /// Append a response to the recording of `payload` and rewrite that method's fixture file
+    pub fn record(&self, network: &str, payload: &Value, response: &Value) {
+        let (method, params) = request_key(payload);
+        let mut state = self.state.lock().unwrap();
+
+        let entries = state.entries.entry((network.to_string(), method.clone())).or_default();
+        match entries.iter_mut().find(|entry| entry.params == params) {
+            Some(entry) => entry.responses.push(response.clone()),
+            None => entries.push(FixtureEntry { params, responses: vec![response.clone()] }),
+        }
+
+        let network_dir = self.dir.join(network);
+        let path = network_dir.join(format!("{}.json", method));
+        let written = std::fs::create_dir_all(&network_dir)
+            .and_then(|_| std::fs::write(&path, serde_json::to_string_pretty(entries).unwrap_or_default()));
+        if let Err(e) = written {
+            eprintln!("⚠️  Failed to record RPC fixture {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Method and params of a request; the id is not part of the key
+fn request_key(payload: &Value) -> (String, Value) {
+    let method = payload.get("method").and_then(|m| m.as_str()).unwrap_or_default().to_string();
+    let params = payload.get("params").cloned().unwrap_or(Value::Null);
+    (method, params)
+}
+
+fn read_dir(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recorded_responses_replay_in_order() {
+        let dir = std::env::temp_dir().join(format!("rpc_fixtures_{}", uuid::Uuid::new_v4()));
+        let head = |id: u64| serde_json::json!({"jsonrpc": "2.0", "method": "starknet_blockNumber", "params": [], "id": id});
+
+        let recorder = RpcFixtures::new(&dir, FixtureMode::Record).unwrap();
+        recorder.record("sepolia", &head(1), &serde_json::json!({"jsonrpc": "2.0", "id": 1, "result": 100}));
+        recorder.record("sepolia", &head(2), &serde_json::json!({"jsonrpc": "2.0", "id": 2, "result": 101}));
+
+        let replay = RpcFixtures::new(&dir, FixtureMode::Replay).unwrap();
+        assert_eq!(replay.replay("sepolia", &head(7)).unwrap(), serde_json::json!({"jsonrpc": "2.0", "id": 7, "result": 100}));
+        assert_eq!(replay.replay("sepolia", &head(8)).unwrap()["result"], 101);
+        // The last response keeps being served
+        assert_eq!(replay.replay("sepolia", &head(9)).unwrap()["result"], 101);
+
+        assert!(replay.replay("mainnet", &head(1)).is_err());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
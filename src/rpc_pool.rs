@@ -0,0 +1,368 @@
+use crate::rate_limit::{RateLimit, RateLimiter};
+use crate::rpc_transport::RpcAuth;
+use crate::rpc_version::SpecVersion;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Consecutive failures after which an endpoint is pulled out of rotation
+const EJECT_AFTER_FAILURES: u32 = 3;
+
+/// Error rate (exponentially weighted) above which an endpoint is pulled out of rotation
+const EJECT_ERROR_RATE: f64 = 0.5;
+
+/// Calls an endpoint must have served before its error rate is trusted
+const MIN_SAMPLES: u64 = 10;
+
+/// First retest delay for an ejected endpoint; doubled on every failed retest
+const RETEST_AFTER: Duration = Duration::from_secs(30);
+const MAX_RETEST_AFTER: Duration = Duration::from_secs(300);
+
+/// Blocks an endpoint may trail the highest head seen in the pool before it is considered stale
+pub const MAX_HEAD_LAG: u64 = 5;
+
+/// How often a provider's `starknet_specVersion` is asked again, so a node upgrade is noticed
+const SPEC_RECHECK_AFTER: Duration = Duration::from_secs(600);
+
+/// Weight of the newest sample in latency and error rate averages
+const EWMA_ALPHA: f64 = 0.2;
+
+#[derive(Debug)]
+struct EndpointState {
+    latency_ms: Option<f64>,
+    error_rate: f64,
+    requests: u64,
+    errors: u64,
+    consecutive_failures: u32,
+    ejected_until: Option<Instant>,
+    retest_after: Duration,
+    head: Option<u64>,
+    current_weight: i64, // Smooth weighted round-robin state
+}
+
+impl EndpointState {
+    fn is_ejected(&self, now: Instant) -> bool {
+        self.ejected_until.map(|until| now < until).unwrap_or(false)
+    }
+
+    fn is_lagging(&self, max_head: Option<u64>) -> bool {
+        matches!((self.head, max_head), (Some(head), Some(max)) if head + MAX_HEAD_LAG < max)
+    }
+}
+
+/// One provider of a network, with its live health statistics
+#[derive(Debug)]
+pub struct RpcEndpoint {
+    pub url: String,
+    pub weight: u32,
+    pub limiter: RateLimiter,
+    pub auth: RpcAuth,
+    batches: AtomicBool, // Cleared once the provider rejects a JSON-RPC batch
+    state: Mutex<EndpointState>,
+    spec: Mutex<Option<SpecState>>, // None until the provider answered `starknet_specVersion`
+}
+
+#[derive(Debug)]
+struct SpecState {
+    reported: String,
+    version: SpecVersion,
+    checked_at: Instant,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EndpointStatus {
+    pub url: String,
+    pub weight: u32,
+    pub healthy: bool,
+    pub ejected: bool,
+    pub lagging: bool,
+    pub latency_ms: Option<f64>,
+    pub error_rate: f64,
+    pub requests: u64,
+    pub errors: u64,
+    pub head: Option<u64>,
+    pub requests_per_second: f64,
+    pub max_concurrent: usize,
+    pub in_flight: usize,
+    pub batches: bool,
+    pub authenticated: bool, // Credentials are configured; never shown
+    pub spec_version: Option<String>, // As reported by the provider
+    pub codec: Option<SpecVersion>,
+}
+
+/// Weighted endpoints of one network. Calls are spread by smooth weighted round-robin over healthy
+/// endpoints; the remaining ones are returned as fallbacks, fastest first.
+#[derive(Debug)]
+pub struct RpcPool {
+    endpoints: Vec<RpcEndpoint>,
+}
+
+impl RpcPool {
+    pub fn new(urls: &[String], weights: &[u32], limits: &[RateLimit], auth: &[RpcAuth]) -> Self {
+        let endpoints = urls.iter().enumerate().map(|(index, url)| RpcEndpoint {
+            url: url.clone(),
+            weight: weights.get(index).copied().unwrap_or(1).max(1),
+            limiter: RateLimiter::new(limits.get(index).copied().unwrap_or_default()),
+            auth: auth.get(index).cloned().unwrap_or_default(),
+            batches: AtomicBool::new(true),
+            state: Mutex::new(EndpointState {
+                latency_ms: None,
+                error_rate: 0.0,
+                requests: 0,
+                errors: 0,
+                consecutive_failures: 0,
+                ejected_until: None,
+                retest_after: RETEST_AFTER,
+                head: None,
+                current_weight: 0,
+            }),
+            spec: Mutex::new(None),
+        }).collect();
+
+        Self { endpoints }
+    }
+
+    pub fn endpoints(&self) -> &[RpcEndpoint] {
+        &self.endpoints
+    }
+
+    fn max_head(&self) -> Option<u64> {
+        self.endpoints.iter().filter_map(|e| e.state.lock().unwrap().head).max()
+    }
+
+    /// Highest head reported by any endpoint
+    pub fn head(&self) -> Option<u64> {
+        self.max_head()
+    }
+
+    fn is_usable(state: &EndpointState, max_head: Option<u64>, now: Instant) -> bool {
+        !state.is_ejected(now) && !state.is_lagging(max_head)
+    }
+
+    /// Whether the endpoint's last known head trails the pool by more than `MAX_HEAD_LAG` blocks
+    pub fn is_lagging(&self, endpoint: &RpcEndpoint) -> bool {
+        let max_head = self.max_head();
+        endpoint.state.lock().unwrap().is_lagging(max_head)
+    }
+
+    /// Endpoints to try for one call, in order
+    pub fn candidates(&self) -> Vec<&RpcEndpoint> {
+        let now = Instant::now();
+        let max_head = self.max_head();
+
+        let (mut usable, mut fallbacks): (Vec<&RpcEndpoint>, Vec<&RpcEndpoint>) = self.endpoints.iter()
+            .partition(|e| Self::is_usable(&e.state.lock().unwrap(), max_head, now));
+
+        if let Some(first) = self.pick_weighted(&usable) {
+            usable.retain(|e| !std::ptr::eq(*e, first));
+            usable.sort_by(|a, b| a.latency().total_cmp(&b.latency()));
+            let mut ordered = vec![first];
+            ordered.extend(usable);
+            // Unhealthy endpoints are still tried as a last resort rather than failing outright
+            fallbacks.sort_by(|a, b| a.latency().total_cmp(&b.latency()));
+            ordered.extend(fallbacks);
+            ordered
+        } else {
+            fallbacks.sort_by(|a, b| a.latency().total_cmp(&b.latency()));
+            fallbacks
+        }
+    }
+
+    fn pick_weighted<'a>(&self, usable: &[&'a RpcEndpoint]) -> Option<&'a RpcEndpoint> {
+        let total: i64 = usable.iter().map(|e| e.weight as i64).sum();
+        let mut best: Option<(&RpcEndpoint, i64)> = None;
+
+        for endpoint in usable {
+            let mut state = endpoint.state.lock().unwrap();
+            state.current_weight += endpoint.weight as i64;
+            if best.map(|(_, weight)| state.current_weight > weight).unwrap_or(true) {
+                best = Some((endpoint, state.current_weight));
+            }
+        }
+
+        let (chosen, _) = best?;
+        chosen.state.lock().unwrap().current_weight -= total;
+        Some(chosen)
+    }
+
+    /// Oldest codec among the endpoints that reported a version. Features that are missing on any
+    /// endpoint of the pool are treated as unavailable.
+    pub fn spec_version(&self) -> Option<SpecVersion> {
+        self.endpoints.iter().filter_map(|e| e.spec_version()).min()
+    }
+
+    pub fn status(&self) -> Vec<EndpointStatus> {
+        let now = Instant::now();
+        let max_head = self.max_head();
+
+        self.endpoints.iter().map(|endpoint| {
+            let state = endpoint.state.lock().unwrap();
+            let ejected = state.is_ejected(now);
+            let lagging = state.is_lagging(max_head);
+            EndpointStatus {
+                url: endpoint.url.clone(),
+                weight: endpoint.weight,
+                healthy: !ejected && !lagging,
+                ejected,
+                lagging,
+                latency_ms: state.latency_ms,
+                error_rate: state.error_rate,
+                requests: state.requests,
+                errors: state.errors,
+                head: state.head,
+                requests_per_second: endpoint.limiter.limit().requests_per_second,
+                max_concurrent: endpoint.limiter.limit().max_concurrent,
+                in_flight: endpoint.limiter.in_flight(),
+                batches: endpoint.supports_batches(),
+                authenticated: !endpoint.auth.is_empty(),
+                spec_version: endpoint.reported_spec_version(),
+                codec: endpoint.spec_version(),
+            }
+        }).collect()
+    }
+}
+
+impl RpcEndpoint {
+    fn latency(&self) -> f64 {
+        self.state.lock().unwrap().latency_ms.unwrap_or(f64::MAX)
+    }
+
+    pub fn record_success(&self, latency: Duration) {
+        let mut state = self.state.lock().unwrap();
+        let latency_ms = latency.as_secs_f64() * 1000.0;
+
+        state.latency_ms = Some(match state.latency_ms {
+            Some(previous) => previous * (1.0 - EWMA_ALPHA) + latency_ms * EWMA_ALPHA,
+            None => latency_ms,
+        });
+        state.error_rate *= 1.0 - EWMA_ALPHA;
+        state.requests += 1;
+        state.consecutive_failures = 0;
+
+        if state.ejected_until.take().is_some() {
+            println!("✅ RPC endpoint {} is healthy again", self.url);
+        }
+        state.retest_after = RETEST_AFTER;
+    }
+
+    pub fn record_failure(&self, reason: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.error_rate = state.error_rate * (1.0 - EWMA_ALPHA) + EWMA_ALPHA;
+        state.requests += 1;
+        state.errors += 1;
+        state.consecutive_failures += 1;
+
+        let now = Instant::now();
+        let retesting = state.ejected_until.map(|until| now >= until).unwrap_or(false);
+        let unhealthy = state.consecutive_failures >= EJECT_AFTER_FAILURES
+            || (state.requests >= MIN_SAMPLES && state.error_rate > EJECT_ERROR_RATE);
+
+        if retesting {
+            state.retest_after = std::cmp::min(state.retest_after * 2, MAX_RETEST_AFTER);
+            state.ejected_until = Some(now + state.retest_after);
+        } else if unhealthy && state.ejected_until.is_none() {
+            state.ejected_until = Some(now + state.retest_after);
+            eprintln!("⚠️  Ejecting RPC endpoint {} for {}s: {}", self.url, state.retest_after.as_secs(), reason);
+        }
+    }
+
+    pub fn supports_batches(&self) -> bool {
+        self.batches.load(Ordering::Relaxed)
+    }
+
+    /// Send this provider single calls from now on
+    pub fn disable_batches(&self) {
+        if self.batches.swap(false, Ordering::Relaxed) {
+            println!("ℹ️  RPC endpoint {} does not accept batch requests, using single calls", self.url);
+        }
+    }
+
+    /// Ejected endpoints are left alone until their retest is due
+    pub fn due_for_check(&self) -> bool {
+        !self.state.lock().unwrap().is_ejected(Instant::now())
+    }
+
+    /// Codec matching the provider's spec version, once known
+    pub fn spec_version(&self) -> Option<SpecVersion> {
+        self.spec.lock().unwrap().as_ref().map(|spec| spec.version)
+    }
+
+    pub fn reported_spec_version(&self) -> Option<String> {
+        self.spec.lock().unwrap().as_ref().map(|spec| spec.reported.clone())
+    }
+
+    pub fn spec_check_due(&self) -> bool {
+        self.spec.lock().unwrap().as_ref().map(|spec| spec.checked_at.elapsed() >= SPEC_RECHECK_AFTER).unwrap_or(true)
+    }
+
+    /// Store the provider's `starknet_specVersion` answer, warning when it changed or has no exact codec
+    pub fn record_spec_version(&self, reported: &str) {
+        let mut spec = self.spec.lock().unwrap();
+        let Some((version, exact)) = SpecVersion::detect(reported) else {
+            eprintln!("⚠️  RPC endpoint {} reported an unreadable spec version '{}'", self.url, reported);
+            return;
+        };
+
+        let previous = spec.as_ref().map(|s| s.reported.clone());
+        if previous.as_deref() != Some(reported) {
+            match previous {
+                Some(previous) => println!("⚠️  RPC endpoint {} changed spec version from {} to {}", self.url, previous, reported),
+                None => println!("ℹ️  RPC endpoint {} serves spec {}", self.url, reported),
+            }
+            if !exact {
+                eprintln!("⚠️  No codec for spec {} on {}, decoding as {}", reported, self.url, version.as_str());
+            }
+        }
+
+        *spec = Some(SpecState { reported: reported.to_string(), version, checked_at: Instant::now() });
+    }
+
+    /// Head reported by this endpoint's `starknet_blockNumber`
+    pub fn record_head(&self, head: u64) {
+        self.state.lock().unwrap().head = Some(head);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(weights: &[u32]) -> RpcPool {
+        let urls: Vec<String> = (0..weights.len()).map(|i| format!("http://rpc-{}", i)).collect();
+        RpcPool::new(&urls, weights, &[], &[])
+    }
+
+    #[test]
+    fn test_calls_are_spread_by_weight() {
+        let pool = pool(&[3, 1]);
+        let firsts: Vec<String> = (0..8).map(|_| pool.candidates()[0].url.clone()).collect();
+
+        assert_eq!(firsts.iter().filter(|u| u.as_str() == "http://rpc-0").count(), 6);
+        assert_eq!(firsts.iter().filter(|u| u.as_str() == "http://rpc-1").count(), 2);
+    }
+
+    #[test]
+    fn test_failing_endpoint_is_ejected_but_kept_as_last_resort() {
+        let pool = pool(&[5, 1]);
+        for _ in 0..EJECT_AFTER_FAILURES {
+            pool.endpoints()[0].record_failure("connection refused");
+        }
+
+        let candidates = pool.candidates();
+        assert_eq!(candidates[0].url, "http://rpc-1");
+        assert_eq!(candidates[1].url, "http://rpc-0");
+        assert!(pool.status()[0].ejected);
+    }
+
+    #[test]
+    fn test_lagging_endpoint_is_not_preferred() {
+        let pool = pool(&[5, 1]);
+        pool.endpoints()[0].record_head(1000);
+        pool.endpoints()[1].record_head(1000 + MAX_HEAD_LAG + 1);
+
+        assert_eq!(pool.candidates()[0].url, "http://rpc-1");
+        assert!(pool.status()[0].lagging);
+    }
+}
@@ -0,0 +1,270 @@
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::Value;
+
+/// Error of a Starknet JSON-RPC call: the transport failed, the node answered with one of the spec's
+/// error codes, or the result did not have the expected shape.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RpcError {
+    Transport(String),
+    ContractNotFound,
+    EntrypointNotFound,
+    BlockNotFound,
+    InvalidTxnIndex,
+    ClassHashNotFound,
+    TxnHashNotFound,
+    PageSizeTooBig,
+    NoBlocks,
+    InvalidContinuationToken,
+    TooManyKeysInFilter,
+    ContractError(Value), // Revert reason of a failed `starknet_call`
+    UnexpectedError(String),
+    MethodNotFound(String),
+    InvalidParams(String),
+    Other { code: i64, message: String },
+    InvalidResponse(String),
+}
+
+impl RpcError {
+    /// Map the `error` member of a JSON-RPC response to the spec's error codes
+    pub fn from_error_object(error: &Value) -> Self {
+        let code = error.get("code").and_then(|c| c.as_i64()).unwrap_or_default();
+        let message = error.get("message").and_then(|m| m.as_str()).unwrap_or_default().to_string();
+        let data = error.get("data").cloned().unwrap_or(Value::Null);
+
+        match code {
+            20 => RpcError::ContractNotFound,
+            21 => RpcError::EntrypointNotFound,
+            24 => RpcError::BlockNotFound,
+            27 => RpcError::InvalidTxnIndex,
+            28 => RpcError::ClassHashNotFound,
+            29 => RpcError::TxnHashNotFound,
+            31 => RpcError::PageSizeTooBig,
+            32 => RpcError::NoBlocks,
+            33 => RpcError::InvalidContinuationToken,
+            34 => RpcError::TooManyKeysInFilter,
+            40 => RpcError::ContractError(data),
+            63 => RpcError::UnexpectedError(data.as_str().map(|s| s.to_string()).unwrap_or(message)),
+            -32601 => RpcError::MethodNotFound(message),
+            -32602 => RpcError::InvalidParams(message),
+            _ => RpcError::Other { code, message },
+        }
+    }
+
+    /// Spec error code, if the node answered with one
+    pub fn code(&self) -> Option<i64> {
+        match self {
+            RpcError::ContractNotFound => Some(20),
+            RpcError::EntrypointNotFound => Some(21),
+            RpcError::BlockNotFound => Some(24),
+            RpcError::InvalidTxnIndex => Some(27),
+            RpcError::ClassHashNotFound => Some(28),
+            RpcError::TxnHashNotFound => Some(29),
+            RpcError::PageSizeTooBig => Some(31),
+            RpcError::NoBlocks => Some(32),
+            RpcError::InvalidContinuationToken => Some(33),
+            RpcError::TooManyKeysInFilter => Some(34),
+            RpcError::ContractError(_) => Some(40),
+            RpcError::UnexpectedError(_) => Some(63),
+            RpcError::MethodNotFound(_) => Some(-32601),
+            RpcError::InvalidParams(_) => Some(-32602),
+            RpcError::Other { code, .. } => Some(*code),
+            RpcError::Transport(_) | RpcError::InvalidResponse(_) => None,
+        }
+    }
+
+    /// Whether the same request may succeed later, e.g. once a lagging node has the block
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            RpcError::Transport(_) | RpcError::BlockNotFound | RpcError::UnexpectedError(_) | RpcError::Other { code: -32603, .. }
+        )
+    }
+}
+
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RpcError::Transport(reason) => write!(f, "{}", reason),
+            RpcError::ContractNotFound => write!(f, "contract not found"),
+            RpcError::EntrypointNotFound => write!(f, "requested entrypoint does not exist in the contract"),
+            RpcError::BlockNotFound => write!(f, "block not found"),
+            RpcError::InvalidTxnIndex => write!(f, "invalid transaction index in a block"),
+            RpcError::ClassHashNotFound => write!(f, "class hash not found"),
+            RpcError::TxnHashNotFound => write!(f, "transaction hash not found"),
+            RpcError::PageSizeTooBig => write!(f, "requested page size is too big"),
+            RpcError::NoBlocks => write!(f, "there are no blocks"),
+            RpcError::InvalidContinuationToken => write!(f, "the supplied continuation token is invalid or unknown"),
+            RpcError::TooManyKeysInFilter => write!(f, "too many keys provided in a filter"),
+            RpcError::ContractError(data) => write!(f, "contract error: {}", data),
+            RpcError::UnexpectedError(reason) => write!(f, "unexpected error: {}", reason),
+            RpcError::MethodNotFound(message) => write!(f, "method not found: {}", message),
+            RpcError::InvalidParams(message) => write!(f, "invalid params: {}", message),
+            RpcError::Other { code, message } => write!(f, "rpc error {}: {}", code, message),
+            RpcError::InvalidResponse(reason) => write!(f, "invalid response: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+impl From<RpcError> for String {
+    fn from(error: RpcError) -> Self {
+        error.to_string()
+    }
+}
+
+/// Block a call refers to
+#[allow(dead_code)] // Typed view of the spec; not every variant or field is used yet
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockId {
+    Number(u64),
+    Hash(String),
+    Latest,
+    Pending,
+}
+
+impl Serialize for BlockId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            BlockId::Number(number) => serde_json::json!({"block_number": number}).serialize(serializer),
+            BlockId::Hash(hash) => serde_json::json!({"block_hash": hash}).serialize(serializer),
+            BlockId::Latest => serializer.serialize_str("latest"),
+            BlockId::Pending => serializer.serialize_str("pending"),
+        }
+    }
+}
+
+/// `starknet_getEvents` filter; an empty list in `keys` accepts any value at that position
+#[derive(Debug, Clone, Serialize)]
+pub struct EventFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_block: Option<BlockId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to_block: Option<BlockId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub keys: Vec<Vec<String>>,
+    pub chunk_size: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub continuation_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EmittedEvent {
+    pub from_address: String,
+    pub keys: Vec<String>,
+    pub data: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_hash: Option<String>, // Missing for pending events
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<u64>,
+    pub transaction_hash: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EventsChunk {
+    pub events: Vec<EmittedEvent>,
+    #[serde(default)]
+    pub continuation_token: Option<String>,
+}
+
+/// Header fields and transaction hashes; pending blocks have no hash or number
+#[allow(dead_code)] // Typed view of the spec; not every variant or field is used yet
+#[derive(Debug, Clone, Deserialize)]
+pub struct BlockWithTxHashes {
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub block_hash: Option<String>,
+    pub parent_hash: String,
+    #[serde(default)]
+    pub block_number: Option<u64>,
+    pub timestamp: u64,
+    #[serde(default)]
+    pub sequencer_address: Option<String>,
+    #[serde(default)]
+    pub transactions: Vec<String>,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, Deserialize)]
+pub struct ContractClass {
+    /// JSON string for Sierra classes, an array for Cairo 0 classes
+    #[serde(default)]
+    pub abi: Option<Value>,
+    #[serde(default)]
+    pub contract_class_version: Option<String>,
+}
+
+impl ContractClass {
+    /// The ABI as JSON, whichever form the class carries it in
+    pub fn abi_json(&self) -> Option<Value> {
+        match self.abi.as_ref()? {
+            Value::String(abi) => serde_json::from_str(abi).ok(),
+            abi @ Value::Array(_) => Some(abi.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Event as a receipt lists it, in emission order
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReceiptEvent {
+    pub from_address: String,
+    pub keys: Vec<String>,
+    pub data: Vec<String>,
+}
+
+/// The part of a receipt the indexer reads; other fields are left to the JSON form
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransactionReceipt {
+    #[serde(default)]
+    pub events: Vec<ReceiptEvent>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn responses() -> Value {
+        serde_json::from_str(include_str!("../tests/fixtures/rpc/error_responses.json")).unwrap()
+    }
+
+    #[test]
+    fn test_spec_error_codes_are_mapped() {
+        let responses = responses();
+        let errors: Vec<RpcError> = responses.as_array().unwrap().iter()
+            .map(|response| RpcError::from_error_object(&response["error"]))
+            .collect();
+
+        assert_eq!(errors[0], RpcError::BlockNotFound);
+        assert_eq!(errors[1], RpcError::PageSizeTooBig);
+        assert_eq!(errors[2], RpcError::InvalidContinuationToken);
+        assert_eq!(errors[3], RpcError::ContractNotFound);
+        assert!(matches!(&errors[4], RpcError::ContractError(data) if data["revert_error"].is_string()));
+        assert_eq!(errors[5], RpcError::Other { code: 429, message: "Too many requests".to_string() });
+        assert!(errors[0].is_retryable());
+        assert!(!errors[3].is_retryable());
+        assert_eq!(errors[2].code(), Some(33));
+    }
+
+    #[test]
+    fn test_event_filter_serializes_block_ids() {
+        let filter = EventFilter {
+            from_block: Some(BlockId::Number(100)),
+            to_block: Some(BlockId::Latest),
+            address: Some("0x1".to_string()),
+            keys: Vec::new(),
+            chunk_size: 1000,
+            continuation_token: None,
+        };
+
+        assert_eq!(serde_json::to_value(&filter).unwrap(), serde_json::json!({
+            "from_block": {"block_number": 100},
+            "to_block": "latest",
+            "address": "0x1",
+            "chunk_size": 1000
+        }));
+    }
+}
@@ -0,0 +1,121 @@
+use serde::Serialize;
+use serde_json::Value;
+
+/// Starknet JSON-RPC spec versions the client has codecs for. Results are decoded into the newest
+/// shape, so the rest of the indexer reads one format whatever the provider runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum SpecVersion {
+    #[serde(rename = "0.6")]
+    V0_6,
+    #[serde(rename = "0.7")]
+    V0_7,
+    #[serde(rename = "0.8")]
+    V0_8,
+}
+
+impl SpecVersion {
+    /// Codec for a `starknet_specVersion` answer such as "0.7.1". Versions outside the supported range
+    /// get the nearest codec; `exact` is false for them.
+    pub fn detect(reported: &str) -> Option<(Self, bool)> {
+        let mut parts = reported.trim().trim_start_matches('v').split('.');
+        let major: u64 = parts.next()?.parse().ok()?;
+        let minor: u64 = parts.next()?.parse().ok()?;
+
+        Some(match (major, minor) {
+            (0, 6) => (SpecVersion::V0_6, true),
+            (0, 7) => (SpecVersion::V0_7, true),
+            (0, 8) => (SpecVersion::V0_8, true),
+            (0, m) if m < 6 => (SpecVersion::V0_6, false),
+            _ => (SpecVersion::V0_8, false),
+        })
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SpecVersion::V0_6 => "0.6",
+            SpecVersion::V0_7 => "0.7",
+            SpecVersion::V0_8 => "0.8",
+        }
+    }
+
+    /// Whether a provider on this version serves `method`
+    pub fn supports(&self, method: &str) -> bool {
+        match method {
+            "starknet_getBlockWithReceipts" => *self >= SpecVersion::V0_7,
+            "starknet_subscribeNewHeads"
+            | "starknet_subscribeEvents"
+            | "starknet_subscribeTransactionStatus"
+            | "starknet_subscribePendingTransactions"
+            | "starknet_unsubscribe"
+            | "starknet_getMessagesStatus"
+            | "starknet_getStorageProof"
+            | "starknet_getCompiledCasm" => *self >= SpecVersion::V0_8,
+            _ => true,
+        }
+    }
+
+    /// Bring the `result` of a `method` response into the newest shape
+    pub fn decode_response(&self, method: &str, mut response: Value) -> Value {
+        if *self >= SpecVersion::V0_7 {
+            return response;
+        }
+
+        if let Some(result) = response.get_mut("result") {
+            match method {
+                "starknet_getTransactionReceipt" => upgrade_receipt(result),
+                "starknet_getBlockWithReceipts" => {
+                    if let Some(transactions) = result.get_mut("transactions").and_then(|t| t.as_array_mut()) {
+                        for transaction in transactions {
+                            if let Some(receipt) = transaction.get_mut("receipt") {
+                                upgrade_receipt(receipt);
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        response
+    }
+}
+
+/// 0.6 receipts carry the fee as a plain felt in WEI; later versions say which token paid it
+fn upgrade_receipt(receipt: &mut Value) {
+    if let Some(fee) = receipt.get("actual_fee").and_then(|f| f.as_str()).map(|f| f.to_string()) {
+        receipt["actual_fee"] = serde_json::json!({"amount": fee, "unit": "WEI"});
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixtures() -> Value {
+        serde_json::from_str(include_str!("../tests/fixtures/rpc/spec_versions.json")).unwrap()
+    }
+
+    #[test]
+    fn test_reported_versions_pick_a_codec() {
+        assert_eq!(SpecVersion::detect("0.6.0"), Some((SpecVersion::V0_6, true)));
+        assert_eq!(SpecVersion::detect("0.7.1"), Some((SpecVersion::V0_7, true)));
+        assert_eq!(SpecVersion::detect("0.8.0-rc.2"), Some((SpecVersion::V0_8, true)));
+        assert_eq!(SpecVersion::detect("0.9.0"), Some((SpecVersion::V0_8, false)));
+        assert_eq!(SpecVersion::detect("0.5.1"), Some((SpecVersion::V0_6, false)));
+        assert_eq!(SpecVersion::detect("latest"), None);
+
+        assert!(!SpecVersion::V0_6.supports("starknet_getBlockWithReceipts"));
+        assert!(SpecVersion::V0_7.supports("starknet_getBlockWithReceipts"));
+        assert!(!SpecVersion::V0_7.supports("starknet_subscribeEvents"));
+        assert!(SpecVersion::V0_8.supports("starknet_subscribeEvents"));
+    }
+
+    #[test]
+    fn test_receipts_decode_to_the_same_shape() {
+        let fixtures = fixtures();
+        let receipt_v0_6 = SpecVersion::V0_6.decode_response("starknet_getTransactionReceipt", fixtures["receipt_v0_6"].clone());
+        let receipt_v0_7 = SpecVersion::V0_7.decode_response("starknet_getTransactionReceipt", fixtures["receipt_v0_7"].clone());
+
+        assert_eq!(receipt_v0_6["result"]["actual_fee"], receipt_v0_7["result"]["actual_fee"]);
+        assert_eq!(receipt_v0_6["result"]["events"], receipt_v0_7["result"]["events"]);
+    }
+}
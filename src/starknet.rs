@@ -1,156 +1,589 @@
 use reqwest::Client;
+use serde::de::DeserializeOwned;
 use serde_json::Value;
-use std::env;
+use sha3::{Digest, Keccak256};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use crate::networks::{is_development_network, NetworkConfig, NetworkRegistry};
+use crate::cairo_serde::{self, FeltCursor};
+use crate::decoded_value::{DecodedEvent, DecodedValue};
+use crate::graphql::types::ValueFormat;
+use crate::rate_limit::RpcPriority;
+use crate::rpc_cache::RpcCache;
+use crate::rpc_fixtures::{FixtureMode, RpcFixtures};
+use crate::rpc_pool::{RpcEndpoint, RpcPool};
+use crate::rpc_transport::RpcAuth;
+use crate::rpc_types::{BlockId, BlockWithTxHashes, ContractClass, EventFilter, EventsChunk, RpcError};
+use crate::traces::SharedTraces;
 
 #[derive(Clone)]
 pub struct RpcContext {
-    pub rpc_url: String,
+    pub rpc_url: String, // Primary endpoint, for logs; calls go through `pool`
+    pub ws_url: String, // WebSocket endpoint for subscriptions
+    pub ws_auth: RpcAuth, // Credentials of the primary endpoint, sent with the WebSocket handshake
     pub http: Client,
+    pub network: String,
+    pub chain_id: String,
+    pub networks: Arc<NetworkRegistry>,
+    pub pool: Arc<RpcPool>,
+    pools: Arc<HashMap<String, Arc<RpcPool>>>, // One per network, so health and rate limits are shared by every context
+    pub priority: RpcPriority,
+    pub fixtures: Option<Arc<RpcFixtures>>, // Record or replay every call instead of only sending it
+    pub cache: Option<Arc<RpcCache>>, // Immutable responses (classes, final blocks and receipts) kept on disk
+    pub traces: Arc<SharedTraces>, // Block traces of every network, shared by the contract indexers
 }
 
 impl RpcContext {
-    pub fn from_env() -> Self {
-        let rpc_url = env::var("RPC_URL")
-            .unwrap_or_else(|_| "https://starknet-mainnet.public.blastapi.io".to_string());
+    /// Context for the registry's default network. Every network shares one HTTP client built from the
+    /// registry's transport settings; credentials are per endpoint.
+    pub fn new(networks: Arc<NetworkRegistry>) -> Result<Self, String> {
+        let http = networks.transport().client()?;
+        let pools: HashMap<String, Arc<RpcPool>> = networks.networks()
+            .map(|network| {
+                let pool = RpcPool::new(&network.rpc_urls, &network.rpc_weights, &networks.rate_limits(network), &network.endpoint_auth());
+                (network.name.clone(), Arc::new(pool))
+            })
+            .collect();
+        let network = networks.default_network().clone();
+        Ok(Self::with_network(http, &network, networks, Arc::new(pools)))
+    }
+
+    fn with_network(
+        http: Client,
+        network: &NetworkConfig,
+        networks: Arc<NetworkRegistry>,
+        pools: Arc<HashMap<String, Arc<RpcPool>>>,
+    ) -> Self {
         Self {
-            rpc_url,
-            http: Client::new(),
+            rpc_url: network.primary_rpc_url().to_string(),
+            ws_url: network.ws_url.clone().unwrap_or_else(|| crate::live_ws::ws_url_for(network.primary_rpc_url())),
+            ws_auth: network.endpoint_auth().into_iter().next().unwrap_or_default(),
+            http,
+            network: network.name.clone(),
+            chain_id: networks.chain_id(network),
+            networks,
+            pool: pools[&network.name].clone(),
+            pools,
+            priority: RpcPriority::Live,
+            fixtures: None,
+            cache: None,
+            traces: Arc::new(SharedTraces::default()),
         }
     }
+
+    /// Context for another registered network, sharing the HTTP client and endpoint pools
+    pub fn for_network(&self, name: &str) -> Result<Self, String> {
+        let network = self.networks.get(name)
+            .ok_or_else(|| format!("Unknown network '{}'", name))?
+            .clone();
+        let mut ctx = Self::with_network(self.http.clone(), &network, self.networks.clone(), self.pools.clone());
+        ctx.fixtures = self.fixtures.clone();
+        ctx.cache = self.cache.clone();
+        ctx.traces = self.traces.clone();
+        Ok(ctx)
+    }
+
+    /// Same contexts, but calls are recorded to or replayed from `fixtures`
+    pub fn with_fixtures(self, fixtures: Arc<RpcFixtures>) -> Self {
+        Self { fixtures: Some(fixtures), ..self }
+    }
+
+    /// Same contexts, but immutable responses are looked up in and stored to `cache`
+    pub fn with_cache(self, cache: Arc<RpcCache>) -> Self {
+        Self { cache: Some(cache), ..self }
+    }
+
+    /// The response cache, except on development networks: a restarted devnet comes back with the same
+    /// chain id, and cached blocks of the previous run would hide the restart from the chain guard
+    fn response_cache(&self) -> Option<&Arc<RpcCache>> {
+        self.cache.as_ref().filter(|_| !is_development_network(&self.network))
+    }
+
+    /// Whether calls are answered from recorded fixtures
+    pub fn replaying(&self) -> bool {
+        self.fixtures.as_ref().map(|f| f.mode() == FixtureMode::Replay).unwrap_or(false)
+    }
+
+    /// Same network, but calls wait behind live-head traffic
+    pub fn backfill(&self) -> Self {
+        Self { priority: RpcPriority::Backfill, ..self.clone() }
+    }
+
+    /// Endpoint pools of all networks, by network name
+    pub fn pools(&self) -> impl Iterator<Item = (&String, &Arc<RpcPool>)> {
+        self.pools.iter()
+    }
+}
+
+struct EndpointError {
+    message: String,
+    retry_after: Option<Duration>, // Set when the endpoint answered 429
+}
+
+/// Send one request to one endpoint, returning the parsed response or why the endpoint failed
+async fn call_endpoint(http: &Client, endpoint: &RpcEndpoint, payload: &Value, priority: RpcPriority) -> Result<Value, EndpointError> {
+    let cost = payload.as_array().map(|batch| batch.len()).unwrap_or(1);
+    let _permit = endpoint.limiter.acquire(priority, cost).await;
+    let failed = |message: String| EndpointError { message, retry_after: None };
+
+    let res = endpoint.auth.apply(http.post(&endpoint.url))
+        .json(payload)
+        .send()
+        .await
+        .map_err(|e| failed(format!("network error: {}", e)))?;
+
+    let status = res.status();
+    let retry_after = res.headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .map(Duration::from_secs);
+    let body_text = res.text().await.map_err(|e| failed(format!("body error: {}", e)))?;
+
+    if status == 429 {
+        return Err(EndpointError {
+            message: format!("rpc status {}: {}", status, body_text),
+            retry_after: Some(retry_after.unwrap_or(Duration::from_secs(1))),
+        });
+    }
+
+    if !status.is_success() {
+        return Err(failed(format!("rpc status {}: {}", status, body_text)));
+    }
+
+    serde_json::from_str(&body_text).map_err(|e| failed(format!("json parse error: {} | body={} ", e, body_text)))
 }
 
+/// Call the network's endpoints in pool order until one answers. Every request waits for the endpoint's
+/// rate limiter; a failing endpoint is reported to the pool and the call moves on to the next one.
+/// `starknet_blockNumber` answers also feed the head-lag check, so a stale answer is only returned when
+/// no other endpoint is in sync.
 pub async fn rpc_call(ctx: &RpcContext, payload: &Value) -> Result<Value, String> {
-    let max_retries = 3;
-    let mut attempt = 0;
-    
+    if let Some(fixtures) = ctx.fixtures.as_ref().filter(|_| ctx.replaying()) {
+        return fixtures.replay(&ctx.network, payload);
+    }
+    if let Some(cache) = ctx.response_cache() {
+        if let Some(response) = cache.get(&ctx.chain_id, payload).await {
+            // A recording must answer cached calls too, or replaying it would miss them
+            record(ctx, payload, &response);
+            return Ok(response);
+        }
+    }
+
+    let response = send(ctx, payload).await?;
+    if let Some(cache) = ctx.response_cache() {
+        cache.put(&ctx.chain_id, payload, &response, ctx.pool.head()).await;
+    }
+    Ok(response)
+}
+
+/// Call the pool, recording the exchange when fixtures are being recorded
+async fn send(ctx: &RpcContext, payload: &Value) -> Result<Value, String> {
+    let response = call_pool(ctx, payload).await?;
+    record(ctx, payload, &response);
+    Ok(response)
+}
+
+fn record(ctx: &RpcContext, payload: &Value, response: &Value) {
+    if let Some(fixtures) = &ctx.fixtures {
+        fixtures.record(&ctx.network, payload, response);
+    }
+}
+
+async fn call_pool(ctx: &RpcContext, payload: &Value) -> Result<Value, String> {
+    let max_rounds = 3;
+    let mut round = 0;
+    let method = payload.get("method").and_then(|m| m.as_str()).unwrap_or_default();
+    let is_head_query = method == "starknet_blockNumber";
+
     loop {
-        attempt += 1;
-        
-        let res = ctx
-            .http
-            .post(&ctx.rpc_url)
-            .json(payload)
-            .send()
-            .await
-            .map_err(|e| format!("network error: {}", e))?;
-        
-        let status = res.status();
-        let body_text = res.text().await.map_err(|e| format!("body error: {}", e))?;
-        
-        // Check if we got a rate limit error
-        if status == 429 {
-            if attempt <= max_retries {
-                let delay = std::cmp::min(2u64.pow(attempt as u32), 30); // Exponential backoff, max 30 seconds
-                eprintln!("⚠️  Rate limited (attempt {}/{}), waiting {} seconds...", attempt, max_retries, delay);
-                tokio::time::sleep(tokio::time::Duration::from_secs(delay)).await;
+        round += 1;
+        let mut rate_limited = false;
+        let mut last_error = String::from("no RPC endpoints configured");
+        let mut stale_response = None;
+        let mut attempted = false;
+
+        for endpoint in ctx.pool.candidates() {
+            // Endpoints on a spec version without the method are not asked at all
+            if endpoint.spec_version().map(|version| !version.supports(method)).unwrap_or(false) {
                 continue;
-            } else {
-                return Err(format!("rpc status {} after {} retries: {}", status, max_retries, body_text));
+            }
+            attempted = true;
+
+            let started = Instant::now();
+            match call_endpoint(&ctx.http, endpoint, payload, ctx.priority).await {
+                Ok(response) => {
+                    endpoint.record_success(started.elapsed());
+                    let response = match endpoint.spec_version() {
+                        Some(version) => version.decode_response(method, response),
+                        None => response,
+                    };
+                    if is_head_query {
+                        if let Some(head) = response.get("result").and_then(|v| v.as_u64()) {
+                            endpoint.record_head(head);
+                            if ctx.pool.is_lagging(endpoint) {
+                                stale_response.get_or_insert(response);
+                                continue;
+                            }
+                        }
+                    }
+                    return Ok(response);
+                }
+                Err(EndpointError { message, retry_after: Some(retry_after) }) => {
+                    // Our limit is above the provider's: pause it rather than count it as unhealthy
+                    rate_limited = true;
+                    endpoint.limiter.throttle(retry_after);
+                    eprintln!("⚠️  Rate limited by {}, pausing it for {}s", endpoint.url, retry_after.as_secs());
+                    last_error = message;
+                }
+                Err(EndpointError { message, retry_after: None }) => {
+                    endpoint.record_failure(&message);
+                    if ctx.pool.endpoints().len() > 1 {
+                        eprintln!("⚠️  RPC endpoint {} failed, trying next: {}", endpoint.url, message);
+                    }
+                    last_error = message;
+                }
             }
         }
-        
-        if !status.is_success() {
-            return Err(format!("rpc status {}: {}", status, body_text));
+
+        if let Some(response) = stale_response {
+            return Ok(response);
         }
-        
-        return serde_json::from_str(&body_text).map_err(|e| format!("json parse error: {} | body={} ", e, body_text));
+
+        if !attempted && !ctx.pool.endpoints().is_empty() {
+            return Ok(method_not_supported(payload, method));
+        }
+
+        // The paused limiters hold the next round back until the providers accept requests again
+        if rate_limited && round < max_rounds {
+            continue;
+        }
+
+        return Err(last_error);
     }
 }
 
-pub async fn get_contract_class(ctx: &RpcContext, address: &str) -> Result<Value, String> {
-    let payload = serde_json::json!({
+/// Error response for a method no endpoint's spec version serves, as a node would answer it
+fn method_not_supported(payload: &Value, method: &str) -> Value {
+    serde_json::json!({
         "jsonrpc": "2.0",
-        "method": "starknet_getClassAt",
-        "params": ["pending", address],
+        "id": payload.get("id").cloned().unwrap_or(Value::Null),
+        "error": {"code": -32601, "message": format!("{} is not served by the spec version of any endpoint", method)}
+    })
+}
+
+/// Requests per JSON-RPC batch; providers commonly cap batches at 50-100 entries
+pub const MAX_BATCH_SIZE: usize = 50;
+
+/// Send `payloads` as JSON-RPC 2.0 batches of at most `MAX_BATCH_SIZE`, returning one response per
+/// payload in the same order. Each response carries `result` or `error` like a single `rpc_call`.
+pub async fn rpc_batch_call(ctx: &RpcContext, payloads: &[Value]) -> Result<Vec<Value>, String> {
+    if let Some(fixtures) = ctx.fixtures.as_ref().filter(|_| ctx.replaying()) {
+        return payloads.iter().map(|payload| fixtures.replay(&ctx.network, payload)).collect();
+    }
+
+    // Only the requests the cache cannot answer are sent
+    let mut responses: Vec<Option<Value>> = Vec::with_capacity(payloads.len());
+    for payload in payloads {
+        responses.push(match ctx.response_cache() {
+            Some(cache) => cache.get(&ctx.chain_id, payload).await,
+            None => None,
+        });
+    }
+    for (payload, response) in payloads.iter().zip(&responses) {
+        if let Some(response) = response {
+            record(ctx, payload, response);
+        }
+    }
+    let missing: Vec<usize> = (0..payloads.len()).filter(|&i| responses[i].is_none()).collect();
+    let pending: Vec<Value> = missing.iter().map(|&i| payloads[i].clone()).collect();
+
+    let mut fetched = Vec::with_capacity(pending.len());
+    for chunk in pending.chunks(MAX_BATCH_SIZE) {
+        fetched.extend(rpc_batch(ctx, chunk).await?);
+    }
+    for (i, response) in missing.into_iter().zip(fetched) {
+        if let Some(cache) = ctx.response_cache() {
+            cache.put(&ctx.chain_id, &payloads[i], &response, ctx.pool.head()).await;
+        }
+        responses[i] = Some(response);
+    }
+    Ok(responses.into_iter().flatten().collect())
+}
+
+/// One batch on the first endpoint that accepts it, or single calls when none does
+async fn rpc_batch(ctx: &RpcContext, payloads: &[Value]) -> Result<Vec<Value>, String> {
+    if payloads.len() > 1 {
+        // Ids are the payload positions, so responses can be matched back in any order
+        let batch: Vec<Value> = payloads.iter().enumerate().map(|(id, payload)| {
+            let mut request = payload.clone();
+            request["id"] = serde_json::json!(id);
+            request
+        }).collect();
+        let batch = Value::Array(batch);
+        let methods: Vec<&str> = payloads.iter()
+            .map(|payload| payload.get("method").and_then(|m| m.as_str()).unwrap_or_default())
+            .collect();
+
+        let candidates = ctx.pool.candidates().into_iter()
+            .filter(|e| e.supports_batches())
+            .filter(|e| e.spec_version().map(|version| methods.iter().all(|m| version.supports(m))).unwrap_or(true));
+        for endpoint in candidates {
+            let started = Instant::now();
+            match call_endpoint(&ctx.http, endpoint, &batch, ctx.priority).await {
+                Ok(Value::Array(responses)) => {
+                    endpoint.record_success(started.elapsed());
+                    let responses = match_batch_responses(payloads.len(), responses);
+                    let responses: Vec<Value> = match endpoint.spec_version() {
+                        Some(version) => responses.into_iter().zip(&methods)
+                            .map(|(response, method)| version.decode_response(method, response))
+                            .collect(),
+                        None => responses,
+                    };
+                    // Recorded one by one, so a replay answers the same requests however they are batched
+                    for (payload, response) in payloads.iter().zip(&responses) {
+                        record(ctx, payload, response);
+                    }
+                    return Ok(responses);
+                }
+                // A single error object instead of an array: the provider does not do batches
+                Ok(_) => endpoint.disable_batches(),
+                Err(EndpointError { retry_after: Some(retry_after), .. }) => endpoint.limiter.throttle(retry_after),
+                Err(EndpointError { message, .. }) => endpoint.record_failure(&message),
+            }
+        }
+    }
+
+    let mut responses = Vec::with_capacity(payloads.len());
+    for payload in payloads {
+        responses.push(send(ctx, payload).await?);
+    }
+    Ok(responses)
+}
+
+/// Order batch responses by request id; a request the provider did not answer gets an error response
+pub fn match_batch_responses(count: usize, responses: Vec<Value>) -> Vec<Value> {
+    let mut by_id: HashMap<u64, Value> = responses.into_iter()
+        .filter_map(|response| response.get("id").and_then(|id| id.as_u64()).map(|id| (id, response)))
+        .collect();
+
+    (0..count as u64).map(|id| by_id.remove(&id).unwrap_or_else(|| serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {"code": -32603, "message": "no response for this request in the batch"}
+    }))).collect()
+}
+
+/// `starknet_getBlockWithTxHashes` for many blocks, batched
+pub async fn get_blocks_with_tx_hashes(ctx: &RpcContext, block_numbers: &[u64]) -> Result<Vec<Result<BlockWithTxHashes, RpcError>>, RpcError> {
+    let payloads: Vec<Value> = block_numbers.iter().map(|block_number| serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": "starknet_getBlockWithTxHashes",
+        "params": [BlockId::Number(*block_number)],
         "id": 1
-    });
-    rpc_call(ctx, &payload).await
+    })).collect();
+    let responses = rpc_batch_call(ctx, &payloads).await.map_err(RpcError::Transport)?;
+    Ok(responses.into_iter().map(parse_response).collect())
 }
 
-pub async fn get_contract_abi_string(ctx: &RpcContext, address: &str) -> Result<String, String> {
-    let class = get_contract_class(ctx, address).await?;
-    let abi_str = class
-        .get("result")
-        .and_then(|r| r.get("abi"))
-        .and_then(|a| a.as_str())
-        .ok_or_else(|| "missing abi in class".to_string())?;
-    Ok(abi_str.to_string())
+/// `starknet_getTransactionReceipt` for many transactions, batched; results as `TransactionReceipt`s
+/// or as the node's JSON
+pub async fn get_transaction_receipts<T: DeserializeOwned>(ctx: &RpcContext, tx_hashes: &[String]) -> Result<Vec<Result<T, RpcError>>, RpcError> {
+    let payloads: Vec<Value> = tx_hashes.iter().map(|tx_hash| serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": "starknet_getTransactionReceipt",
+        "params": [tx_hash],
+        "id": 1
+    })).collect();
+    let responses = rpc_batch_call(ctx, &payloads).await.map_err(RpcError::Transport)?;
+    Ok(responses.into_iter().map(parse_response).collect())
 }
 
-pub async fn get_current_block_number(ctx: &RpcContext) -> Result<u64, String> {
+/// Ask every endpoint that is due for it which spec version it serves, so requests and results go
+/// through the matching codec. Run at startup and from the health checks.
+pub async fn detect_spec_versions(ctx: &RpcContext) {
+    if ctx.replaying() {
+        return;
+    }
     let payload = serde_json::json!({
         "jsonrpc": "2.0",
-        "method": "starknet_blockNumber",
+        "method": "starknet_specVersion",
         "params": [],
         "id": 1
     });
-    
-    let response = rpc_call(ctx, &payload).await?;
-    response
-        .get("result")
-        .and_then(|v| v.as_u64())
-        .ok_or_else(|| "Failed to parse block number".to_string())
-}
-
-
-
-pub async fn get_events(
-    ctx: &RpcContext,
-    address: &str,
-    from_block: Option<&str>,
-    to_block: Option<&str>,
-    chunk_size: u32,
-    continuation: Option<&str>,
-) -> Result<Value, String> {
-    let mut filter = serde_json::json!({
-        "address": address,
-        "chunk_size": chunk_size,
-    });
-    if let Some(f) = from_block { 
-        filter["from_block"] = if f == "latest" || f == "pending" {
-            serde_json::Value::String(f.to_string())
-        } else {
-            serde_json::json!({"block_number": f.parse::<u64>().unwrap_or(0)})
-        };
-    }
-    if let Some(t) = to_block { 
-        filter["to_block"] = if t == "latest" || t == "pending" {
-            serde_json::Value::String(t.to_string())
-        } else {
-            serde_json::json!({"block_number": t.parse::<u64>().unwrap_or(0)})
-        };
+
+    for (_, pool) in ctx.pools() {
+        for endpoint in pool.endpoints().iter().filter(|e| e.due_for_check() && e.spec_check_due()) {
+            match call_endpoint(&ctx.http, endpoint, &payload, RpcPriority::Live).await {
+                Ok(response) => match response.get("result").and_then(|v| v.as_str()) {
+                    Some(reported) => endpoint.record_spec_version(reported),
+                    None => eprintln!("⚠️  RPC endpoint {} did not report a spec version: {}", endpoint.url, response),
+                },
+                Err(EndpointError { retry_after: Some(retry_after), .. }) => endpoint.limiter.throttle(retry_after),
+                Err(EndpointError { message, .. }) => endpoint.record_failure(&message),
+            }
+        }
     }
-    if let Some(c) = continuation { filter["continuation_token"] = serde_json::Value::String(c.to_string()); }
+}
 
+/// Poll every endpoint of every network for its head, so ejected endpoints are retested and lagging ones
+/// are spotted even when no indexer asks for the head. Spec versions are rechecked along the way.
+pub async fn run_endpoint_health_checks(ctx: RpcContext, interval: Duration) {
+    if ctx.replaying() {
+        return;
+    }
     let payload = serde_json::json!({
         "jsonrpc": "2.0",
-        "method": "starknet_getEvents",
-        "params": [filter],
+        "method": "starknet_blockNumber",
+        "params": [],
         "id": 1
     });
-    rpc_call(ctx, &payload).await
+
+    loop {
+        for (_, pool) in ctx.pools() {
+            for endpoint in pool.endpoints().iter().filter(|e| e.due_for_check()) {
+                let started = Instant::now();
+                match call_endpoint(&ctx.http, endpoint, &payload, RpcPriority::Live).await {
+                    Ok(response) => match response.get("result").and_then(|v| v.as_u64()) {
+                        Some(head) => {
+                            endpoint.record_success(started.elapsed());
+                            endpoint.record_head(head);
+                        }
+                        None => endpoint.record_failure(&format!("no block number in {}", response)),
+                    },
+                    Err(EndpointError { retry_after: Some(retry_after), .. }) => endpoint.limiter.throttle(retry_after),
+                    Err(EndpointError { message, .. }) => endpoint.record_failure(&message),
+                }
+            }
+        }
+        detect_spec_versions(&ctx).await;
+
+        tokio::time::sleep(interval).await;
+    }
 }
 
-#[allow(dead_code)]
-pub async fn get_block_with_tx_hashes_by_number(ctx: &RpcContext, block_number: u64) -> Result<Value, String> {
+/// Send one request and return its `result` as `T`, mapping a JSON-RPC `error` member to `RpcError`
+pub async fn request<T: DeserializeOwned>(ctx: &RpcContext, method: &str, params: Value) -> Result<T, RpcError> {
     let payload = serde_json::json!({
         "jsonrpc": "2.0",
-        "method": "starknet_getBlockWithTxHashes",
-        "params": [{"block_number": block_number}],
+        "method": method,
+        "params": params,
         "id": 1
     });
-    rpc_call(ctx, &payload).await
+    let response = rpc_call(ctx, &payload).await.map_err(RpcError::Transport)?;
+    parse_response(response)
 }
 
-#[allow(dead_code)]
-pub async fn get_transaction_by_hash(ctx: &RpcContext, tx_hash: &str) -> Result<Value, String> {
-    let payload = serde_json::json!({
-        "jsonrpc": "2.0",
-        "method": "starknet_getTransactionByHash",
-        "params": [tx_hash],
-        "id": 1
-    });
-    rpc_call(ctx, &payload).await
+/// `result` of a JSON-RPC response, or the error it carries
+pub fn parse_response<T: DeserializeOwned>(response: Value) -> Result<T, RpcError> {
+    if let Some(error) = response.get("error") {
+        return Err(RpcError::from_error_object(error));
+    }
+    let result = response.get("result")
+        .cloned()
+        .ok_or_else(|| RpcError::InvalidResponse(format!("no result in {}", response)))?;
+    serde_json::from_value(result).map_err(|e| RpcError::InvalidResponse(e.to_string()))
+}
+
+/// Class currently deployed at `address`. Looked up by class hash, so the class itself can be served
+/// from the RPC cache and only the (cheap) hash lookup reaches the provider.
+pub async fn get_contract_class(ctx: &RpcContext, address: &str) -> Result<ContractClass, RpcError> {
+    let class_hash = get_class_hash_at(ctx, BlockId::Pending, address).await?;
+    get_class(ctx, BlockId::Pending, &class_hash).await
+}
+
+pub async fn get_class(ctx: &RpcContext, block_id: BlockId, class_hash: &str) -> Result<ContractClass, RpcError> {
+    request(ctx, "starknet_getClass", serde_json::json!([block_id, class_hash])).await
+}
+
+pub async fn get_contract_abi_string(ctx: &RpcContext, address: &str) -> Result<String, RpcError> {
+    let class = get_contract_class(ctx, address).await?;
+    class.abi_json()
+        .map(|abi| abi.to_string())
+        .ok_or_else(|| RpcError::InvalidResponse("missing abi in class".to_string()))
+}
+
+/// ABI of a declared class
+pub async fn get_class_abi(ctx: &RpcContext, class_hash: &str) -> Result<Value, RpcError> {
+    get_class(ctx, BlockId::Pending, class_hash).await?
+        .abi_json()
+        .ok_or_else(|| RpcError::InvalidResponse(format!("class {} has no abi", class_hash)))
+}
+
+/// Value of a storage slot of a contract, as a felt
+pub async fn get_storage_at(ctx: &RpcContext, address: &str, key: &str, block_id: BlockId) -> Result<String, RpcError> {
+    request(ctx, "starknet_getStorageAt", serde_json::json!([address, key, block_id])).await
+}
+
+pub async fn get_class_hash_at(ctx: &RpcContext, block_id: BlockId, address: &str) -> Result<String, RpcError> {
+    request(ctx, "starknet_getClassHashAt", serde_json::json!([block_id, address])).await
+}
+
+pub async fn get_current_block_number(ctx: &RpcContext) -> Result<u64, RpcError> {
+    request(ctx, "starknet_blockNumber", serde_json::json!([])).await
+}
+
+/// Chain id reported by the node, as a felt (e.g. "0x534e5f4d41494e" for SN_MAIN)
+pub async fn get_chain_id(ctx: &RpcContext) -> Result<String, RpcError> {
+    request(ctx, "starknet_chainId", serde_json::json!([])).await
+}
+
+/// Hash of the block at `block_number`, or None if the node does not have that block
+pub async fn get_block_hash(ctx: &RpcContext, block_number: u64) -> Result<Option<String>, RpcError> {
+    match get_block_with_tx_hashes(ctx, BlockId::Number(block_number)).await {
+        Ok(block) => Ok(block.block_hash),
+        Err(RpcError::BlockNotFound) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// One page of `starknet_getEvents`; pass `continuation_token` back in the filter for the next page
+pub async fn get_events_chunk(ctx: &RpcContext, filter: &EventFilter) -> Result<EventsChunk, RpcError> {
+    request(ctx, "starknet_getEvents", serde_json::json!([filter])).await
+}
+
+pub async fn get_block_with_tx_hashes(ctx: &RpcContext, block_id: BlockId) -> Result<BlockWithTxHashes, RpcError> {
+    request(ctx, "starknet_getBlockWithTxHashes", serde_json::json!([block_id])).await
+}
+
+/// Block with each transaction next to its receipt. Spec 0.6 providers have no such method, so the
+/// block is assembled from `starknet_getBlockWithTxs` and a batch of receipts in the same shape.
+pub async fn get_block_with_receipts(ctx: &RpcContext, block_number: u64) -> Result<Value, RpcError> {
+    let block_id = BlockId::Number(block_number);
+    match request(ctx, "starknet_getBlockWithReceipts", serde_json::json!([block_id])).await {
+        Err(RpcError::MethodNotFound(_)) => {}
+        result => return result,
+    }
+
+    let mut block: Value = request(ctx, "starknet_getBlockWithTxs", serde_json::json!([block_id])).await?;
+    let transactions = block.get("transactions").and_then(|t| t.as_array()).cloned().unwrap_or_default();
+    let tx_hashes: Vec<String> = transactions.iter()
+        .map(|transaction| transaction.get("transaction_hash").and_then(|h| h.as_str()).unwrap_or_default().to_string())
+        .collect();
+    let receipts = get_transaction_receipts::<Value>(ctx, &tx_hashes).await?;
+
+    let mut with_receipts = Vec::with_capacity(transactions.len());
+    for (transaction, receipt) in transactions.into_iter().zip(receipts) {
+        with_receipts.push(serde_json::json!({"transaction": transaction, "receipt": receipt?}));
+    }
+    block["transactions"] = Value::Array(with_receipts);
+    Ok(block)
+}
+
+pub async fn get_state_update(ctx: &RpcContext, block_number: u64) -> Result<Value, RpcError> {
+    request(ctx, "starknet_getStateUpdate", serde_json::json!([BlockId::Number(block_number)])).await
+}
+
+pub async fn trace_block_transactions(ctx: &RpcContext, block_number: u64) -> Result<Value, RpcError> {
+    request(ctx, "starknet_traceBlockTransactions", serde_json::json!([BlockId::Number(block_number)])).await
+}
+
+/// Starknet selector of a name: keccak256 truncated to 250 bits, as a padded hex felt.
+pub fn sn_keccak(name: &str) -> String {
+    let mut hash: [u8; 32] = Keccak256::digest(name.as_bytes()).into();
+    hash[0] &= 0x03;
+    format!("0x{}", hex::encode(hash))
 }
 
 // Enhanced ABI parser that fully supports unlimited nested structs
@@ -158,7 +591,8 @@ pub async fn get_transaction_by_hash(ctx: &RpcContext, tx_hash: &str) -> Result<
 struct AbiType {
     #[allow(dead_code)]
     name: String,
-    members: Vec<AbiMember>,
+    members: Vec<AbiMember>, // Variants in declaration order for enums
+    is_enum: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -168,21 +602,80 @@ struct AbiMember {
     is_key: bool,
 }
 
+#[derive(Debug, Clone)]
+struct AbiEvent {
+    name: String, // Variant name, prefixed by the components it is emitted through, e.g. "ERC20Component::Transfer"
+    selectors: Vec<String>, // Leading keys the event is emitted with, one per nested enum level
+    def: AbiType,
+}
+
+#[derive(Debug, Clone)]
+struct AbiFunction {
+    name: String,
+    inputs: Vec<AbiMember>,
+    outputs: Vec<AbiMember>, // Unnamed in Cairo 1 ABIs
+}
+
 #[derive(Debug, Clone)]
 struct AbiParser {
     types: std::collections::HashMap<String, AbiType>,
-    events: std::collections::HashMap<String, AbiType>,
+    events: Vec<AbiEvent>,
+    // Keyed by normalized entry point selector
+    functions: std::collections::HashMap<String, AbiFunction>,
+    hints: DecodeHints,
+}
+
+/// How to read `felt252` values that the ABI alone cannot tell apart from numbers, usually set in a
+/// deployment contract's metadata: `{"shortStrings": ["Transfer.memo", "symbol"], "guessStrings": false}`
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodeHints {
+    /// Members holding a short string, as `Owner.member` (owner being the event, struct or function
+    /// name without its path) or just `member` for every owner
+    #[serde(default)]
+    pub short_strings: Vec<String>,
+    /// Read every felt252 that looks like text as a short string, e.g. `0x4142` as "AB"
+    #[serde(default)]
+    pub guess_strings: bool,
+}
+
+impl DecodeHints {
+    /// Hints in a metadata JSON object; other metadata is ignored
+    pub fn from_metadata(metadata: &Value) -> Result<Self, String> {
+        serde_json::from_value(metadata.clone()).map_err(|e| format!("Invalid decoding hints in metadata: {}", e))
+    }
+
+    fn is_short_string(&self, owner: &str, member: &str) -> bool {
+        let owner = owner.rsplit("::").next().unwrap_or(owner);
+        self.short_strings.iter().any(|hint| match hint.split_once('.') {
+            Some((hint_owner, hint_member)) => hint_owner == owner && hint_member == member,
+            None => hint == member,
+        })
+    }
 }
 
 impl AbiParser {
-    fn new(abi_json: &serde_json::Value) -> Self {
+    fn new(abi_json: &serde_json::Value, hints: &DecodeHints) -> Self {
         let mut parser = AbiParser {
             types: std::collections::HashMap::new(),
-            events: std::collections::HashMap::new(),
+            events: Vec::new(),
+            functions: std::collections::HashMap::new(),
+            hints: hints.clone(),
         };
         
         if let Some(arr) = abi_json.as_array() {
+            // Functions can be top-level or nested inside interface items
+            for item in arr {
+                parser.collect_function(item);
+                if let Some(items) = item.get("items").and_then(|v| v.as_array()) {
+                    for nested in items {
+                        parser.collect_function(nested);
+                    }
+                }
+            }
+
             // First pass: collect all struct and enum definitions
+            let mut event_items = std::collections::HashMap::new();
             for item in arr {
                 if let Some(item_type) = item.get("type").and_then(|v| v.as_str()) {
                     if let Some(name) = item.get("name").and_then(|v| v.as_str()) {
@@ -192,43 +685,160 @@ impl AbiParser {
                                 parser.types.insert(name.to_string(), abi_type);
                             },
                             "event" => {
-                                if item.get("kind").and_then(|k| k.as_str()) == Some("struct") {
-                                    let abi_type = Self::parse_type_definition(item);
-                                    let short_name = name.split("::").last().unwrap_or(name).to_string();
-                                    parser.events.insert(short_name, abi_type);
-                                } else if item.get("kind").and_then(|k| k.as_str()) == Some("enum") {
-                                    // Handle enum events - extract individual event variants
-                                    if let Some(variants) = item.get("variants").and_then(|v| v.as_array()) {
-                                        for variant in variants {
-                                            if let Some(variant_name) = variant.get("name").and_then(|n| n.as_str()) {
-                                                if let Some(variant_type) = variant.get("type").and_then(|t| t.as_str()) {
-                                                    // Look for the corresponding struct definition
-                                                    for nested_item in arr {
-                                                        if let Some(nested_name) = nested_item.get("name").and_then(|n| n.as_str()) {
-                                                            if nested_name.ends_with(variant_type) || nested_name == variant_type {
-                                                                if nested_item.get("type").and_then(|t| t.as_str()) == Some("struct") {
-                                                                    let event_struct = Self::parse_type_definition(nested_item);
-                                                                    parser.events.insert(variant_name.to_string(), event_struct);
-                                                                }
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
+                                event_items.insert(name.to_string(), item.clone());
                             },
                             _ => {}
                         }
                     }
                 }
             }
+            parser.collect_events(&event_items);
         }
         
         parser
     }
+
+    /// Resolve every struct event to the selector keys it is emitted with. Walking down from the root
+    /// event enums, each `nested` variant adds `sn_keccak(variant name)` and `flat` variants add none.
+    /// A struct event that no enum reaches is emitted under its own name.
+    ///
+    /// Events of the contract itself are named by their variant; events reached through a component's
+    /// event enum are prefixed with the component, e.g. `ERC20Component::Transfer`. Enums that only
+    /// group the contract's events add no prefix.
+    fn collect_events(&mut self, items: &std::collections::HashMap<String, serde_json::Value>) {
+        let referenced: std::collections::HashSet<&str> = items.values()
+            .filter_map(|item| item.get("variants").and_then(|v| v.as_array()))
+            .flatten()
+            .filter_map(|variant| variant.get("type").and_then(|t| t.as_str()))
+            .collect();
+        let mut roots: Vec<&String> = items.keys().filter(|name| !referenced.contains(name.as_str())).collect();
+        roots.sort();
+
+        for root in roots {
+            let item = &items[root];
+            match item.get("kind").and_then(|k| k.as_str()) {
+                Some("enum") => self.collect_event_variants(items, item, &[], &[], 0),
+                Some("struct") => {
+                    let short_name = root.split("::").last().unwrap_or(root).to_string();
+                    self.events.push(AbiEvent {
+                        selectors: vec![sn_keccak(&short_name)],
+                        name: short_name,
+                        def: Self::parse_type_definition(item),
+                    });
+                }
+                None if item.get("data").is_some() => self.events.push(Self::parse_legacy_event(root, item)),
+                _ => {}
+            }
+        }
+    }
+
+    /// Cairo 0 event: `keys` and `data` member lists instead of a kind, emitted with `sn_keccak(name)`
+    /// as its only selector
+    fn parse_legacy_event(name: &str, item: &serde_json::Value) -> AbiEvent {
+        let members = |list: &str, is_key: bool| -> Vec<AbiMember> {
+            item.get(list).and_then(|v| v.as_array()).into_iter().flatten().filter_map(|member| {
+                Some(AbiMember {
+                    name: member.get("name")?.as_str()?.to_string(),
+                    type_name: member.get("type")?.as_str()?.to_string(),
+                    is_key,
+                })
+            }).collect()
+        };
+        AbiEvent {
+            name: name.to_string(),
+            selectors: vec![sn_keccak(name)],
+            def: AbiType { name: name.to_string(), members: [members("keys", true), members("data", false)].concat(), is_enum: false },
+        }
+    }
+
+    fn collect_event_variants(
+        &mut self,
+        items: &std::collections::HashMap<String, serde_json::Value>,
+        event_enum: &serde_json::Value,
+        selectors: &[String],
+        components: &[String],
+        depth: usize,
+    ) {
+        // Event enums cannot be recursive, but a malformed ABI should not overflow the stack
+        if depth > 16 {
+            return;
+        }
+        for variant in event_enum.get("variants").and_then(|v| v.as_array()).into_iter().flatten() {
+            let (Some(variant_name), Some(variant_type)) = (
+                variant.get("name").and_then(|n| n.as_str()),
+                variant.get("type").and_then(|t| t.as_str()),
+            ) else {
+                continue;
+            };
+
+            let mut variant_selectors = selectors.to_vec();
+            if variant.get("kind").and_then(|k| k.as_str()) != Some("flat") {
+                variant_selectors.push(sn_keccak(variant_name));
+            }
+
+            let Some(item) = items.get(variant_type) else {
+                continue;
+            };
+            match item.get("kind").and_then(|k| k.as_str()) {
+                Some("enum") => {
+                    let mut variant_components = components.to_vec();
+                    variant_components.extend(component_name(variant_type).map(String::from));
+                    self.collect_event_variants(items, item, &variant_selectors, &variant_components, depth + 1)
+                }
+                Some("struct") => self.events.push(AbiEvent {
+                    name: components.iter().map(String::as_str).chain([variant_name]).collect::<Vec<_>>().join("::"),
+                    selectors: variant_selectors,
+                    def: Self::parse_type_definition(item),
+                }),
+                _ => {}
+            }
+        }
+    }
+
+    /// Event whose selector keys open `keys`; the longest match wins, so a nested event is preferred
+    /// over an outer one sharing its prefix
+    fn match_event(&self, keys: &[serde_json::Value]) -> Option<&AbiEvent> {
+        let keys: Vec<String> = keys.iter()
+            .map(|k| crate::database::Database::normalize_address(k.as_str().unwrap_or_default()).to_lowercase())
+            .collect();
+        self.events.iter()
+            .filter(|event| event.selectors.len() <= keys.len() && event.selectors.iter().zip(&keys).all(|(s, k)| s == k))
+            .max_by_key(|event| event.selectors.len())
+    }
     
+    fn collect_function(&mut self, item: &serde_json::Value) {
+        let item_type = item.get("type").and_then(|v| v.as_str()).unwrap_or("");
+        if !matches!(item_type, "function" | "l1_handler" | "constructor") {
+            return;
+        }
+        let Some(name) = item.get("name").and_then(|v| v.as_str()) else {
+            return;
+        };
+
+        let inputs = item.get("inputs")
+            .and_then(|v| v.as_array())
+            .map(|inputs| inputs.iter().filter_map(|input| {
+                Some(AbiMember {
+                    name: input.get("name")?.as_str()?.to_string(),
+                    type_name: input.get("type")?.as_str()?.to_string(),
+                    is_key: false,
+                })
+            }).collect())
+            .unwrap_or_default();
+        let outputs = item.get("outputs")
+            .and_then(|v| v.as_array())
+            .map(|outputs| outputs.iter().filter_map(|output| {
+                Some(AbiMember {
+                    name: output.get("name").and_then(|n| n.as_str()).unwrap_or_default().to_string(),
+                    type_name: output.get("type")?.as_str()?.to_string(),
+                    is_key: false,
+                })
+            }).collect())
+            .unwrap_or_default();
+
+        self.functions.insert(sn_keccak(name), AbiFunction { name: name.to_string(), inputs, outputs });
+    }
+
     fn parse_type_definition(item: &serde_json::Value) -> AbiType {
         let name = item.get("name").and_then(|v| v.as_str()).unwrap_or("Unknown").to_string();
         let mut members = Vec::new();
@@ -269,407 +879,668 @@ impl AbiParser {
             }
         }
         
-        AbiType { name, members }
+        let is_enum = item.get("variants").is_some();
+        AbiType { name, members, is_enum }
     }
     
-    #[allow(dead_code)]
-    fn decode_value(&self, value: &serde_json::Value, type_name: &str) -> serde_json::Value {
-        // Handle basic types
-        if let Some(decoded) = self.decode_basic_type(value, type_name) {
-            return decoded;
+    /// Decode one value of `type_name` from the cursor, taking as many felts as its Cairo Serde encoding
+    /// has: one for scalars, a length and the elements for arrays and spans, a variant index and the
+    /// payload for enums, and the members in order for structs and tuples.
+    fn decode_value(&self, cursor: &mut FeltCursor, type_name: &str, depth: usize) -> Result<DecodedValue, String> {
+        if depth > 64 {
+            return Err(format!("{} is nested too deeply", type_name));
         }
-        
-        // Handle complex types (structs)
-        if let Some(struct_def) = self.types.get(type_name) {
-            return self.decode_struct(value, struct_def);
+
+        // Integers are range-checked against their width; u256 and u512 join their u128 limbs
+        if let Some(bits) = cairo_serde::unsigned_bits(type_name) {
+            return cairo_serde::read_unsigned(cursor, bits, type_name).map(|value| DecodedValue::Unsigned { bits, value });
         }
-        
-        // Fallback: return raw value
-        value.clone()
-    }
-    
-    fn decode_basic_type(&self, value: &serde_json::Value, type_name: &str) -> Option<serde_json::Value> {
-        if let Some(s) = value.as_str() {
-            match type_name {
-                "felt252" | "core::felt252" | "felt" => {
-                    // Convert felt252 to readable string
-                    Some(serde_json::Value::String(self.felt_to_string(s)))
-                },
-                t if t.starts_with("core::integer::u") || ["u8", "u16", "u32", "u64", "u128"].contains(&t) => {
-                    // Handle unsigned integers with better overflow handling
-                    let hex_str = s.trim_start_matches("0x");
-                    
-                    // Handle special case of all F's (max values)
-                    if hex_str.chars().all(|c| c == 'f' || c == 'F') {
-                        // Return the appropriate max value for the type
-                        let max_val = match t {
-                            "u8" | "core::integer::u8" => 255u64,
-                            "u16" | "core::integer::u16" => 65535u64,
-                            "u32" | "core::integer::u32" => 4294967295u64,
-                            "u64" | "core::integer::u64" => u64::MAX,
-                            _ => u64::MAX,
-                        };
-                        return Some(serde_json::Value::Number(max_val.into()));
-                    }
-                    
-                    // Try to parse as hex first
-                    if let Ok(num) = u64::from_str_radix(hex_str, 16) {
-                        Some(serde_json::Value::Number(num.into()))
-                    } else if let Ok(num) = s.parse::<u64>() {
-                        Some(serde_json::Value::Number(num.into()))
-                    } else {
-                        // For overflow cases, try u128 and return as string
-                        if let Ok(big_num) = u128::from_str_radix(hex_str, 16) {
-                            Some(serde_json::Value::String(big_num.to_string()))
-                        } else {
-                            Some(serde_json::Value::String(s.to_string()))
-                        }
-                    }
-                },
-                "core::integer::u256" | "u256" => {
-                    // For u256, try to parse as number if possible (use u64 limit for JSON compatibility)
-                    if let Ok(num) = u64::from_str_radix(s.trim_start_matches("0x"), 16) {
-                        Some(serde_json::Value::Number(num.into()))
-                    } else {
-                        // For very large numbers, return as string
-                        Some(serde_json::Value::String(s.to_string()))
-                    }
-                },
-                "core::starknet::contract_address::ContractAddress" | "ContractAddress" | "contract_address" => {
-                    Some(serde_json::Value::String(s.to_string()))
-                },
-                "core::bool" | "bool" => {
-                    // Decode boolean from felt
-                    if s == "0x0" || s == "0" {
-                        Some(serde_json::Value::Bool(false))
-                    } else {
-                        Some(serde_json::Value::Bool(true))
-                    }
-                },
-                // Handle signed integers
-                t if t.starts_with("core::integer::i") || ["i8", "i16", "i32", "i64", "i128"].contains(&t) => {
-                    if let Ok(num) = i64::from_str_radix(s.trim_start_matches("0x"), 16) {
-                        Some(serde_json::Value::Number(num.into()))
-                    } else if let Ok(num) = s.parse::<i64>() {
-                        Some(serde_json::Value::Number(num.into()))
-                    } else {
-                        Some(serde_json::Value::String(s.to_string()))
-                    }
-                },
-                // Handle ByteArray (Cairo strings)
-                "core::byte_array::ByteArray" | "ByteArray" => {
-                    Some(serde_json::Value::String(self.felt_to_string(s)))
-                },
-                // Handle ClassHash
-                "core::starknet::class_hash::ClassHash" | "ClassHash" => {
-                    Some(serde_json::Value::String(s.to_string()))
-                },
-                _ => None
-            }
-        } else {
-            None
+        if let Some(limbs) = cairo_serde::wide_unsigned_limbs(type_name) {
+            return cairo_serde::read_wide_unsigned(cursor, limbs, type_name).map(|value| DecodedValue::Unsigned { bits: 128 * limbs as u64, value });
         }
-    }
-    
-    fn felt_to_string(&self, felt_hex: &str) -> String {
-        // Remove 0x prefix if present
-        let hex_str = felt_hex.trim_start_matches("0x");
-        
-        // Handle special cases first
-        if hex_str == "ffffffffffffffffffffffffffffffff" || hex_str.chars().all(|c| c == 'f' || c == 'F') {
-            // This is likely a max value or error condition, convert to decimal
-            if let Ok(num) = u128::from_str_radix(hex_str, 16) {
-                return num.to_string();
-            }
+        // Signed integers are P - |x| when negative
+        if let Some(bits) = cairo_serde::signed_bits(type_name) {
+            return cairo_serde::read_signed(cursor, bits, type_name).map(|value| DecodedValue::Signed { bits, value });
         }
-        
-        // Try to decode as UTF-8 string first
-        if hex_str.len() % 2 == 0 && hex_str.len() <= 64 { // Reasonable length for string
-            if let Ok(bytes) = hex::decode(hex_str) {
-                // Remove trailing zeros
-                let trimmed_bytes: Vec<u8> = bytes.into_iter()
-                    .rev()
-                    .skip_while(|&b| b == 0)
-                    .collect::<Vec<_>>()
-                    .into_iter()
-                    .rev()
-                    .collect();
-                
-                // Try to convert to UTF-8 string
-                if let Ok(utf8_string) = String::from_utf8(trimmed_bytes.clone()) {
-                    // Check if it's a readable string (printable ASCII or valid UTF-8)
-                    // Allow alphanumeric, spaces, and common punctuation
-                    if !utf8_string.is_empty() && 
-                       utf8_string.chars().all(|c| c.is_ascii_alphanumeric() || 
-                                              c.is_ascii_punctuation() || 
-                                              c.is_whitespace()) &&
-                       utf8_string.len() > 1 { // Avoid single character strings from random hex
-                        return utf8_string;
-                    }
+
+        if is_byte_array(type_name) {
+            return cairo_serde::read_byte_array(cursor).map(DecodedValue::Text);
+        }
+        if is_short_string_type(type_name) && !self.types.contains_key(type_name) {
+            return cairo_serde::short_string(cursor.next(type_name)?).map(DecodedValue::Text);
+        }
+
+        if let Some(decoded) = cursor.peek().and_then(|felt| self.decode_basic_type(felt, type_name)) {
+            cursor.next(type_name)?;
+            return Ok(decoded);
+        }
+
+        if let Some(members) = cairo_serde::tuple_members(type_name) {
+            if members.is_empty() {
+                return Ok(DecodedValue::Null); // Unit, e.g. the payload of `Option::None`
+            }
+            // Cairo 0 tuples may name their members, `(x: felt, y: felt)`; those read as objects
+            let members: Vec<(Option<&str>, &str)> = members.into_iter().map(cairo_serde::named_member).collect();
+            if members.iter().all(|(name, _)| name.is_some()) {
+                let mut decoded = Vec::new();
+                for (name, member_type) in members {
+                    decoded.push((name.unwrap_or_default().to_string(), self.decode_value(cursor, member_type, depth + 1)?));
                 }
+                return Ok(DecodedValue::Struct(decoded));
             }
+            return members.into_iter()
+                .map(|(_, member)| self.decode_value(cursor, member, depth + 1))
+                .collect::<Result<Vec<_>, _>>()
+                .map(DecodedValue::List);
         }
-        
-        // Try to parse as number (for values that should be numbers)
-        if let Ok(num) = u128::from_str_radix(hex_str, 16) {
-            // If it's a reasonable number, return as decimal string
-            if num <= u64::MAX as u128 {
-                return (num as u64).to_string();
-            } else {
-                // For very large numbers, return as decimal string
-                return num.to_string();
+
+        if let Some(element) = cairo_serde::array_element(type_name) {
+            let len = cursor.next_usize(type_name)?;
+            return self.decode_elements(cursor, type_name, element, len, depth);
+        }
+
+        if let Some(inner) = cairo_serde::generic_arg(type_name, "core::zeroable::NonZero::<") {
+            return self.decode_value(cursor, inner, depth + 1);
+        }
+
+        if let Some(inner) = cairo_serde::generic_arg(type_name, "core::option::Option::<") {
+            // Some is variant 0, None variant 1; the declared enum (if any) says the same
+            return match cursor.next_usize(type_name)? {
+                0 => self.decode_value(cursor, inner, depth + 1),
+                1 => Ok(DecodedValue::Null),
+                index => Err(format!("{} has no variant {}", type_name, index)),
+            };
+        }
+
+        if let Some(def) = self.types.get(type_name) {
+            if def.is_enum {
+                let index = cursor.next_usize(type_name)?;
+                let variant = def.members.get(index)
+                    .ok_or_else(|| format!("{} has no variant {}", type_name, index))?;
+                let payload = self.decode_member(cursor, type_name, variant, depth + 1)?;
+                // Unit variants read as their name, others as {"Variant": payload}
+                return Ok(DecodedValue::Variant {
+                    name: variant.name.clone(),
+                    payload: Some(Box::new(payload)).filter(|_| variant.type_name != "()"),
+                });
+            }
+            let mut decoded = Vec::new();
+            for member in &def.members {
+                decoded.push((member.name.clone(), self.decode_member(cursor, type_name, member, depth + 1)?));
             }
+            return Ok(DecodedValue::Struct(decoded));
+        }
+
+        if cursor.remaining() == 0 {
+            Err(format!("no felts left for {}", type_name))
+        } else {
+            Err(format!("unknown type {}", type_name))
         }
-        
-        // Fallback to original hex value if nothing else works
-        felt_hex.to_string()
-    }
-    
-    #[allow(dead_code)]
-    fn decode_struct(&self, _value: &serde_json::Value, _struct_def: &AbiType) -> serde_json::Value {
-        // Struct decoding is complex because structs are serialized as flattened values
-        // This requires a different approach - we need to handle this at the event level
-        // where we have access to the full data/keys arrays
-        serde_json::Value::Null
     }
-    
-    #[allow(dead_code)]
-    fn decode_struct_from_arrays(&self, keys: &[serde_json::Value], data: &[serde_json::Value], 
-                                 struct_def: &AbiType, key_index: &mut usize, data_index: &mut usize) -> serde_json::Value {
-        let mut decoded = serde_json::Map::new();
-        
-        for member in &struct_def.members {
-            let decoded_value = if member.is_key {
-                // This field comes from keys array
-                if let Some(key_val) = keys.get(*key_index) {
-                    *key_index += 1;
-                    self.decode_value_recursive(keys, data, key_val, &member.type_name, key_index, data_index)
-                } else {
-                    serde_json::Value::Null
-                }
+
+    /// A member of an event, struct or function named `owner`. Members that the hints declare as short
+    /// strings have their felt252 values read as text, also inside options, arrays and tuples.
+    fn decode_member(&self, cursor: &mut FeltCursor, owner: &str, member: &AbiMember, depth: usize) -> Result<DecodedValue, String> {
+        if self.hints.is_short_string(owner, &member.name) {
+            let type_name = if is_felt252(&member.type_name) {
+                "shortstring".to_string()
             } else {
-                // This field comes from data array
-                if let Some(data_val) = data.get(*data_index) {
-                    *data_index += 1;
-                    self.decode_value_recursive(keys, data, data_val, &member.type_name, key_index, data_index)
-                } else {
-                    serde_json::Value::Null
-                }
+                member.type_name.replace("core::felt252", "core::shortstring")
             };
-            
-            decoded.insert(member.name.clone(), decoded_value);
+            return self.decode_value(cursor, &type_name, depth);
         }
-        
-        serde_json::Value::Object(decoded)
+        self.decode_value(cursor, &member.type_name, depth)
     }
-    
-    fn decode_value_recursive(&self, _keys: &[serde_json::Value], _data: &[serde_json::Value], 
-                             value: &serde_json::Value, type_name: &str, 
-                             _key_index: &mut usize, _data_index: &mut usize) -> serde_json::Value {
-        // Handle basic types first - these don't require additional array consumption
-        if let Some(decoded) = self.decode_basic_type(value, type_name) {
-            return decoded;
-        }
-        
-        // Handle complex types (nested structs) - these would require array consumption
-        // For now, we'll implement this as a simple case since full struct serialization 
-        // in Starknet is complex and depends on the exact contract implementation
-        if let Some(_struct_def) = self.types.get(type_name) {
-            // For nested structs, the proper implementation would need to:
-            // 1. Determine how many array positions this struct consumes
-            // 2. Extract those positions and recursively decode them
-            // 3. Properly handle nested fields
-            // This is a complex feature that would need more sophisticated handling
-            return value.clone(); // Return raw value for now
-        }
-        
-        // Handle arrays/spans - complex serialization
-        if type_name.starts_with("core::array::Array::<") || type_name.starts_with("core::array::Span::<") {
-            return value.clone(); // Return raw value for now
-        }
-        
-        // Handle Option types - moderately complex
-        if type_name.starts_with("core::option::Option::<") {
-            return value.clone(); // Return raw value for now
+
+    /// Decode `members` of `owner` in order, key members from `keys` and the others from `data`. Decoding
+    /// stops at the first malformed value, whose error is returned with the members decoded before it.
+    fn decode_members<'f>(&self, owner: &str, members: &[AbiMember], keys: &mut FeltCursor<'f>, data: &mut FeltCursor<'f>) -> (Vec<(String, DecodedValue)>, Option<String>) {
+        let (values, error) = self.decode_member_list(owner, members, keys, data);
+        let decoded = members.iter().map(|member| member.name.clone()).zip(values).collect();
+        if error.is_some() {
+            return (decoded, error);
         }
-        
-        // Fallback: return raw value
-        value.clone()
-    }
-    
-    #[allow(dead_code)]
-    fn decode_array(&self, _keys: &[serde_json::Value], _data: &[serde_json::Value], 
-                   value: &serde_json::Value, _type_name: &str, 
-                   _key_index: &mut usize, _data_index: &mut usize) -> serde_json::Value {
-        // Array decoding: first value is length, followed by elements
-        // This is complex and depends on the exact serialization format
-        // For now, return the raw value
-        value.clone()
+        let unread = [("keys", keys.remaining()), ("data", data.remaining())]
+            .into_iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(what, count)| format!("{} {} felts left unread", count, what))
+            .collect::<Vec<_>>();
+        (decoded, Some(unread.join(", ")).filter(|e| !e.is_empty()))
     }
-    
-    #[allow(dead_code)]
-    fn decode_option(&self, _keys: &[serde_json::Value], _data: &[serde_json::Value], 
-                    value: &serde_json::Value, _type_name: &str, 
-                    _key_index: &mut usize, _data_index: &mut usize) -> serde_json::Value {
-        // Option decoding: first value indicates Some(0) or None(1), then the value if Some
-        // For now, return the raw value
-        value.clone()
+
+    /// Values of `members` in order, up to the first malformed one. Cairo 0 arrays are a `T*` member
+    /// whose length is the felt member just before it (by convention `<name>_len`).
+    fn decode_member_list<'f>(&self, owner: &str, members: &[AbiMember], keys: &mut FeltCursor<'f>, data: &mut FeltCursor<'f>) -> (Vec<DecodedValue>, Option<String>) {
+        let mut values = Vec::new();
+        let mut pointer_len = None;
+        for (index, member) in members.iter().enumerate() {
+            let cursor = if member.is_key { &mut *keys } else { &mut *data };
+            let next_is_pointer = members.get(index + 1).is_some_and(|next| next.type_name.ends_with('*'));
+            let value = if let Some(element) = member.type_name.strip_suffix('*') {
+                match pointer_len.take() {
+                    Some(len) => self.decode_elements(cursor, &member.type_name, element, len, 0),
+                    None => Err(format!("{} has no length member before it", member.type_name)),
+                }
+            } else if next_is_pointer && is_felt252(&member.type_name) {
+                cursor.next_usize(&member.type_name).map(|len| {
+                    pointer_len = Some(len);
+                    DecodedValue::Felt(len.into()) // Its declared type; only the cursor needs the length as a number
+                })
+            } else {
+                self.decode_member(cursor, owner, member, 0)
+            };
+            match value {
+                Ok(value) => values.push(value),
+                Err(e) => return (values, Some(format!("{}: {}", member.name, e))),
+            }
+        }
+        (values, None)
     }
-    
-    fn decode_member_from_keys(&self, keys: &[serde_json::Value], data: &[serde_json::Value], 
-                              type_name: &str, key_index: &mut usize, data_index: &mut usize) -> serde_json::Value {
-        if *key_index < keys.len() {
-            let key_val = &keys[*key_index];
-            *key_index += 1;
-            self.decode_value_recursive(keys, data, key_val, type_name, key_index, data_index)
-        } else {
-            serde_json::Value::Null
+
+    /// `len` values of `element`, the items of an array of `type_name`
+    fn decode_elements(&self, cursor: &mut FeltCursor, type_name: &str, element: &str, len: usize, depth: usize) -> Result<DecodedValue, String> {
+        if len > cursor.remaining() {
+            return Err(format!("{} of length {} with only {} felts left", type_name, len, cursor.remaining()));
         }
+        (0..len)
+            .map(|_| self.decode_value(cursor, element, depth + 1))
+            .collect::<Result<Vec<_>, _>>()
+            .map(DecodedValue::List)
     }
     
-    fn decode_member_from_data(&self, keys: &[serde_json::Value], data: &[serde_json::Value], 
-                              type_name: &str, key_index: &mut usize, data_index: &mut usize) -> serde_json::Value {
-        if *data_index < data.len() {
-            let data_val = &data[*data_index];
-            *data_index += 1;
-            self.decode_value_recursive(keys, data, data_val, type_name, key_index, data_index)
-        } else {
-            serde_json::Value::Null
+    fn decode_basic_type(&self, value: &serde_json::Value, type_name: &str) -> Option<DecodedValue> {
+        let felt = value.as_str()?;
+        match type_name {
+            t if is_felt252(t) => {
+                // Text only when asked for; a felt is as often a number or a hash
+                if self.hints.guess_strings {
+                    if let Some(text) = cairo_serde::guess_short_string(felt) {
+                        return Some(DecodedValue::Text(text));
+                    }
+                }
+                DecodedValue::felt(felt)
+            },
+            "core::starknet::contract_address::ContractAddress" | "ContractAddress" | "contract_address"
+            | "core::starknet::class_hash::ClassHash" | "ClassHash"
+            | "core::starknet::eth_address::EthAddress"
+            | "core::starknet::storage_access::StorageAddress" => DecodedValue::address(felt),
+            "core::bool" | "bool" => {
+                // Decode boolean from felt
+                Some(DecodedValue::Bool(!(felt == "0x0" || felt == "0")))
+            },
+            "core::bytes_31::bytes31" => DecodedValue::felt(felt),
+            _ => None
         }
     }
 }
 
+/// Component of an event enum: the module holding a component's `Event` enum, e.g. `ERC20Component`
+/// for `openzeppelin::token::erc20::erc20::ERC20Component::Event`. Other enums only group events.
+fn component_name(enum_type: &str) -> Option<&str> {
+    let mut path = enum_type.rsplit("::");
+    match (path.next(), path.next()) {
+        (Some("Event"), Some(module)) => Some(module),
+        _ => None,
+    }
+}
+
+/// Whether an event type passes a filter of event types. Filters match the full type, such as
+/// `ERC20Component::Transfer`, or its trailing parts, so `Transfer` matches transfers of every component.
+pub fn event_type_matches(filters: &[String], event_type: &str) -> bool {
+    filters.iter().any(|filter| {
+        filter == event_type
+            || event_type.strip_suffix(filter.as_str()).is_some_and(|prefix| prefix.ends_with("::"))
+    })
+}
+
+fn is_felt252(type_name: &str) -> bool {
+    matches!(type_name, "felt252" | "core::felt252" | "felt")
+}
+
+fn is_byte_array(type_name: &str) -> bool {
+    matches!(type_name, "core::byte_array::ByteArray" | "ByteArray")
+}
+
+/// Felt aliases that ABIs use to declare a short string, e.g. `ShortString` or `shortstring`
+fn is_short_string_type(type_name: &str) -> bool {
+    type_name.rsplit("::").next().is_some_and(|name| name.eq_ignore_ascii_case("shortstring"))
+}
+
 pub fn decode_event_using_abi(abi_json: &serde_json::Value, event: &serde_json::Value) -> (String, serde_json::Value) {
+    decode_event_with_hints(abi_json, event, &DecodeHints::default())
+}
+
+/// The event's type and its data as `ValueFormat::Typed` JSON
+pub fn decode_event_with_hints(abi_json: &serde_json::Value, event: &serde_json::Value, hints: &DecodeHints) -> (String, serde_json::Value) {
+    let decoded = decode_event(abi_json, event, hints);
+    let data = decoded.render(ValueFormat::Typed);
+    (decoded.event_type, data)
+}
+
+pub fn decode_event(abi_json: &serde_json::Value, event: &serde_json::Value, hints: &DecodeHints) -> DecodedEvent {
     let keys = event.get("keys").and_then(|v| v.as_array()).cloned().unwrap_or_default();
     let data = event.get("data").and_then(|v| v.as_array()).cloned().unwrap_or_default();
 
-    let parser = AbiParser::new(abi_json);
+    let parser = AbiParser::new(abi_json, hints);
     
-    // Get the event selector (first key) to match against ABI
-    let _event_selector = keys.first().and_then(|k| k.as_str()).unwrap_or("");
-    
-    // Try to find matching event definition by calculating event selectors
-    let mut best_match: Option<(&String, &AbiType)> = None;
-    let mut max_matching_fields = 0;
+    // Match the selector keys exactly; events of the same shape (Transfer, Approval) differ only there
+    if let Some(event) = parser.match_event(&keys) {
+        let mut key_cursor = FeltCursor::at(&keys, event.selectors.len()); // Members follow the selector keys
+        let mut data_cursor = FeltCursor::new(&data);
+        // A payload that does not fit the ABI is reported rather than guessed at
+        let (members, error) = parser.decode_members(&event.name, &event.def.members, &mut key_cursor, &mut data_cursor);
+        return DecodedEvent { event_type: event.name.clone(), members, error, keys, data };
+    }
     
-    for (event_name, event_def) in &parser.events {
-        // Try to match this event definition with the actual event data
-        // We'll use a heuristic approach: find the event definition that can decode the most fields successfully
-        let expected_key_count = event_def.members.iter().filter(|m| m.is_key).count();
-        let expected_data_count = event_def.members.iter().filter(|m| !m.is_key).count();
-        
-        // Calculate how well this event definition matches the actual data structure
-        let mut matching_score = 0;
-        
-        // Check if the key/data counts make sense
-        if keys.len() >= expected_key_count + 1 && data.len() >= expected_data_count {
-            matching_score += expected_key_count + expected_data_count;
-            
-            // If this event has more matching fields than previous candidates, consider it better
-            if matching_score > max_matching_fields {
-                max_matching_fields = matching_score;
-                best_match = Some((event_name, event_def));
-            }
+    // Fallback: the data felts as they are
+    let members = data.iter().enumerate()
+        .map(|(idx, val)| {
+            let value = val.as_str().and_then(DecodedValue::felt).unwrap_or_else(|| DecodedValue::from_stored(val));
+            (format!("field_{}", idx), value)
+        })
+        .collect();
+    DecodedEvent { event_type: "Unknown".to_string(), members, error: None, keys, data }
+}
+
+/// Resolve an entry point selector against the ABI and decode its calldata and return data.
+/// Returns `None` when the selector is not part of the ABI.
+pub fn decode_function_call_using_abi(
+    abi_json: &serde_json::Value,
+    entry_point_selector: &str,
+    calldata: &[serde_json::Value],
+    result: &[serde_json::Value],
+    hints: &DecodeHints,
+) -> Option<(String, serde_json::Value, serde_json::Value)> {
+    let parser = AbiParser::new(abi_json, hints);
+    let selector = crate::database::Database::normalize_address(entry_point_selector);
+    let function = parser.functions.get(&selector)?;
+
+    let (inputs, error) = parser.decode_members(&function.name, &function.inputs, &mut FeltCursor::new(&[]), &mut FeltCursor::new(calldata));
+    let mut inputs = match DecodedValue::Struct(inputs).render(ValueFormat::Typed) {
+        serde_json::Value::Object(inputs) => inputs,
+        _ => serde_json::Map::new(),
+    };
+    if let Some(error) = error {
+        inputs.insert("_error".to_string(), serde_json::Value::String(error));
+    }
+
+    // Outputs are a list in declaration order; a malformed one ends the list
+    let (outputs, error) = parser.decode_member_list(&function.name, &function.outputs, &mut FeltCursor::new(&[]), &mut FeltCursor::new(result));
+    let mut outputs: Vec<_> = outputs.iter().map(|output| output.render(ValueFormat::Typed)).collect();
+    if let Some(error) = error {
+        outputs.push(serde_json::json!({ "_error": error }));
+    }
+
+    Some((function.name.clone(), serde_json::Value::Object(inputs), serde_json::Value::Array(outputs)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_development_networks_bypass_the_cache() {
+        let path = std::env::temp_dir().join(format!("rpc_cache_{}.db", uuid::Uuid::new_v4()));
+        let cache = RpcCache::open(&format!("sqlite:{}", path.to_string_lossy()), 1024, 10).await.unwrap();
+        let devnet = RpcContext::new(Arc::new(NetworkRegistry::builtin_only("devnet"))).unwrap().with_cache(Arc::new(cache));
+
+        assert!(devnet.response_cache().is_none());
+        assert!(devnet.for_network("local").unwrap().response_cache().is_none());
+        assert!(devnet.for_network("testnet").unwrap().response_cache().is_some());
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn test_cached_calls_are_recorded() {
+        let path = std::env::temp_dir().join(format!("rpc_cache_{}.db", uuid::Uuid::new_v4()));
+        let dir = std::env::temp_dir().join(format!("rpc_fixtures_{}", uuid::Uuid::new_v4()));
+        let cache = RpcCache::open(&format!("sqlite:{}", path.to_string_lossy()), 1024 * 1024, 10).await.unwrap();
+        let request = |method: &str, params: Value| serde_json::json!({"jsonrpc": "2.0", "method": method, "params": params, "id": 1});
+        let class = request("starknet_getClass", serde_json::json!(["latest", "0xabc"]));
+        let headers = [1, 2].map(|n| request("starknet_getBlockWithTxHashes", serde_json::json!([{"block_number": n}])));
+        let answer = |result: Value| serde_json::json!({"jsonrpc": "2.0", "id": 1, "result": result});
+
+        // A warm cache answers every call, so none of them reaches the providers
+        cache.put("SN_SEPOLIA", &class, &answer(serde_json::json!({"abi": "[]"})), None).await;
+        for (n, header) in headers.iter().enumerate() {
+            cache.put("SN_SEPOLIA", header, &answer(serde_json::json!({"block_number": n + 1})), Some(100)).await;
         }
+        let registry = Arc::new(NetworkRegistry::builtin_only("testnet"));
+        let recorder = RpcContext::new(registry.clone()).unwrap()
+            .with_cache(Arc::new(cache))
+            .with_fixtures(Arc::new(RpcFixtures::new(&dir, FixtureMode::Record).unwrap()));
+        rpc_call(&recorder, &class).await.unwrap();
+        rpc_batch_call(&recorder, &headers).await.unwrap();
+
+        let replayer = RpcContext::new(registry).unwrap()
+            .with_fixtures(Arc::new(RpcFixtures::new(&dir, FixtureMode::Replay).unwrap()));
+        assert_eq!(rpc_call(&replayer, &class).await.unwrap()["result"]["abi"], "[]");
+        let replayed = rpc_batch_call(&replayer, &headers).await.unwrap();
+        assert_eq!(replayed.iter().map(|r| r["result"]["block_number"].as_u64().unwrap()).collect::<Vec<_>>(), vec![1, 2]);
+
+        let _ = std::fs::remove_file(path);
+        let _ = std::fs::remove_dir_all(dir);
     }
-    
-    // Use the best matching event definition
-    if let Some((event_name, event_def)) = best_match {
-        let mut decoded = serde_json::Map::new();
-        let mut key_index = 1; // Skip first key (event selector)
-        let mut data_index = 0;
-        
-        // Decode each member using recursive decoding for full struct support
-        for member in &event_def.members {
-            let decoded_value = if member.is_key {
-                // This field comes from keys array
-                parser.decode_member_from_keys(&keys, &data, &member.type_name, &mut key_index, &mut data_index)
+
+    #[test]
+    fn test_batch_responses_are_matched_by_id() {
+        let raw: Value = serde_json::from_str(include_str!("../tests/fixtures/rpc/batch_headers.json")).unwrap();
+        let responses = match_batch_responses(3, raw.as_array().unwrap().clone());
+
+        assert_eq!(responses.len(), 3);
+        assert_eq!(responses[0]["result"]["block_number"], 812001);
+        // Not answered by the provider
+        assert_eq!(responses[1]["id"], 1);
+        assert_eq!(responses[1]["error"]["code"], -32603);
+        assert_eq!(responses[2]["error"]["message"], "Block not found");
+    }
+
+    /// ABI of `contracts/src/lib.cairo` and one event of each kind, from the synthetic replay fixtures
+    fn event_test_contract() -> (Value, Vec<Value>) {
+        let class: Value = serde_json::from_str(include_str!("../tests/fixtures/replay/event_test_contract/mainnet/starknet_getClass.json")).unwrap();
+        let events: Value = serde_json::from_str(include_str!("../tests/fixtures/replay/event_test_contract/mainnet/starknet_getEvents.json")).unwrap();
+        let abi = serde_json::from_str(class[0]["responses"][0]["result"]["abi"].as_str().unwrap()).unwrap();
+        (abi, events[0]["responses"][0]["result"]["events"].as_array().unwrap().clone())
+    }
+
+    #[test]
+    fn test_events_are_matched_by_selector() {
+        let (abi, events) = event_test_contract();
+        // The test contract emits its felt252 values as short strings
+        let hints = DecodeHints {
+            short_strings: ["Felt252Event.value", "optional_value", "SingleParamEvent.value", "param1", "non_indexed_param", "indexed_felt", "non_indexed_felt"]
+                .map(String::from).to_vec(),
+            guess_strings: false,
+        };
+        let expected = [
+            ("U8Event", "value", serde_json::json!(255)),
+            ("U16Event", "value", serde_json::json!(65535)),
+            ("U32Event", "value", serde_json::json!(4294967295u64)),
+            ("U64Event", "value", serde_json::json!("18446744073709551615")),
+            ("U128Event", "value", serde_json::json!("340282366920938463463374607431768211455")),
+            ("U256Event", "value", serde_json::json!("115792089237316195423570985008687907853269984665640564039457584007913129639935")),
+            ("Felt252Event", "value", serde_json::json!("test_felt252")),
+            ("BoolEvent", "value", serde_json::json!(true)),
+            ("OptionEvent", "optional_value", serde_json::json!("optional_value")),
+            ("AddressEvent", "address", serde_json::json!("0x0123456789012345678901234567890123456789012345678901234567890123")),
+            ("NoParamsEvent", "", serde_json::json!({})),
+            ("SingleParamEvent", "value", serde_json::json!("single_param")),
+            ("MultipleParamsEvent", "param1", serde_json::json!("param1")),
+            ("IndexedParamsEvent", "non_indexed_param", serde_json::json!("non_indexed")),
+            ("MixedParamsEvent", "indexed_felt", serde_json::json!("indexed_felt")),
+        ];
+        assert_eq!(events.len(), expected.len());
+
+        for (event, (name, member, value)) in events.iter().zip(expected) {
+            let (event_type, decoded) = decode_event_with_hints(&abi, event, &hints);
+            assert_eq!(event_type, name);
+            let actual = if member.is_empty() {
+                // Every member of the event, leaving out the raw keys and data stored next to them
+                let members = decoded.as_object().unwrap().iter().filter(|(field, _)| !crate::decoded_value::is_reserved_field(field));
+                Value::Object(members.map(|(field, value)| (field.clone(), value.clone())).collect())
             } else {
-                // This field comes from data array
-                parser.decode_member_from_data(&keys, &data, &member.type_name, &mut key_index, &mut data_index)
+                decoded[member].clone()
             };
-            
-            decoded.insert(member.name.clone(), decoded_value);
-        }
-        
-        if !event_def.members.is_empty() {
-            // Include raw keys and data for debugging
-            decoded.insert("_keys".to_string(), serde_json::Value::Array(keys.clone()));
-            decoded.insert("_raw_data".to_string(), serde_json::Value::Array(data.clone()));
-            
-            return (event_name.clone(), serde_json::Value::Object(decoded));
+            assert_eq!(actual, value, "{}.{}", name, member);
+            assert!(decoded.get("_error").is_none(), "{}: {}", name, decoded["_error"]);
         }
+
+        // Members after a two-felt u256 are read from the right felts
+        let (_, multiple) = decode_event_with_hints(&abi, &events[12], &hints);
+        assert_eq!(multiple["param2"], "1000000000000000000");
+        assert_eq!(multiple["param3"], true);
+        let (_, mixed) = decode_event_with_hints(&abi, &events[14], &hints);
+        assert_eq!(mixed["non_indexed_felt"], "non_indexed_felt");
+        assert_eq!(mixed["non_indexed_bool"], false);
+
+        // The outer selector alone, or a variant of another enum, matches nothing
+        let truncated = serde_json::json!({"keys": [events[0]["keys"][0]], "data": []});
+        assert_eq!(decode_event_using_abi(&abi, &truncated).0, "Unknown");
+        let crossed = serde_json::json!({"keys": [events[0]["keys"][0], events[9]["keys"][1], "0x1"], "data": []});
+        assert_eq!(decode_event_using_abi(&abi, &crossed).0, "Unknown");
     }
-    
-    // Fallback: return raw data with field names if possible
-    let mut decoded = serde_json::Map::new();
-    for (idx, val) in data.iter().enumerate() {
-        decoded.insert(format!("field_{}", idx), val.clone());
+
+    #[test]
+    fn test_events_of_the_same_shape_are_told_apart() {
+        let abi = serde_json::json!([
+            {"type": "event", "name": "token::Transfer", "kind": "struct", "members": [
+                {"name": "from", "type": "core::starknet::contract_address::ContractAddress", "kind": "key"},
+                {"name": "to", "type": "core::starknet::contract_address::ContractAddress", "kind": "key"},
+                {"name": "value", "type": "core::felt252", "kind": "data"}
+            ]},
+            {"type": "event", "name": "token::Approval", "kind": "struct", "members": [
+                {"name": "owner", "type": "core::starknet::contract_address::ContractAddress", "kind": "key"},
+                {"name": "spender", "type": "core::starknet::contract_address::ContractAddress", "kind": "key"},
+                {"name": "value", "type": "core::felt252", "kind": "data"}
+            ]},
+            {"type": "event", "name": "token::Event", "kind": "enum", "variants": [
+                {"name": "Transfer", "type": "token::Transfer", "kind": "nested"},
+                {"name": "Approval", "type": "token::Approval", "kind": "nested"}
+            ]}
+        ]);
+        let event = |name: &str| serde_json::json!({"keys": [sn_keccak(name), "0x1", "0x2"], "data": ["0x3"]});
+
+        let (transfer, decoded) = decode_event_using_abi(&abi, &event("Transfer"));
+        assert_eq!(transfer, "Transfer");
+        assert_eq!(decoded["from"], crate::database::Database::normalize_address("0x1"));
+        let (approval, decoded) = decode_event_using_abi(&abi, &event("Approval"));
+        assert_eq!(approval, "Approval");
+        assert_eq!(decoded["spender"], crate::database::Database::normalize_address("0x2"));
+        assert_eq!(decode_event_using_abi(&abi, &event("Mint")).0, "Unknown");
     }
-    decoded.insert("_keys".to_string(), serde_json::Value::Array(keys.clone()));
-    decoded.insert("_raw_data".to_string(), serde_json::Value::Array(data.clone()));
-    
-    ("Unknown".to_string(), serde_json::Value::Object(decoded))
-}
-
-#[allow(dead_code)]
-fn decode_cairo_value(value: &serde_json::Value, cairo_type: &str) -> serde_json::Value {
-    match value {
-        serde_json::Value::String(s) => {
-            match cairo_type {
-                "felt252" | "felt" => {
-                    // Try to decode felt as different formats
-                    if let Ok(num) = s.parse::<u64>() {
-                        // If it's a reasonable number, show both hex and decimal
-                        serde_json::json!({
-                            "hex": s,
-                            "decimal": num.to_string(),
-                            "type": "felt252"
-                        })
-                    } else {
-                        // Keep as hex string
-                        serde_json::json!({
-                            "hex": s,
-                            "type": "felt252"
-                        })
-                    }
-                },
-                "u8" | "u16" | "u32" | "u64" | "u128" | "u256" => {
-                    // Parse as number if possible
-                    if let Ok(num) = u64::from_str_radix(s.trim_start_matches("0x"), 16) {
-                        serde_json::json!({
-                            "value": num,
-                            "hex": s,
-                            "type": cairo_type
-                        })
-                    } else if let Ok(num) = s.parse::<u64>() {
-                        serde_json::json!({
-                            "value": num,
-                            "type": cairo_type
-                        })
-                    } else {
-                        serde_json::json!({
-                            "raw": s,
-                            "type": cairo_type
-                        })
-                    }
-                },
-                "ContractAddress" | "contract_address" => {
-                    serde_json::json!({
-                        "address": s,
-                        "type": "ContractAddress"
-                    })
-                },
-                _ => {
-                    // Default: return the raw value with type info
-                    serde_json::json!({
-                        "raw": s,
-                        "type": cairo_type
-                    })
-                }
-            }
-        },
-        _ => value.clone()
+
+    fn serde_abi() -> Value {
+        serde_json::json!([
+            {"type": "struct", "name": "game::Position", "members": [
+                {"name": "x", "type": "core::integer::u32"},
+                {"name": "y", "type": "core::integer::u32"}
+            ]},
+            {"type": "struct", "name": "game::Player", "members": [
+                {"name": "id", "type": "core::felt252"},
+                {"name": "position", "type": "game::Position"},
+                {"name": "path", "type": "core::array::Span::<game::Position>"}
+            ]},
+            {"type": "enum", "name": "game::Action", "variants": [
+                {"name": "Idle", "type": "()"},
+                {"name": "Move", "type": "game::Position"},
+                {"name": "Say", "type": "(core::felt252, core::bool)"}
+            ]},
+            {"type": "event", "name": "game::Played", "kind": "struct", "members": [
+                {"name": "player", "type": "game::Player", "kind": "key"},
+                {"name": "scores", "type": "core::array::Array::<core::integer::u8>", "kind": "data"},
+                {"name": "bonus", "type": "core::option::Option::<core::integer::u16>", "kind": "data"},
+                {"name": "missed", "type": "core::option::Option::<core::integer::u16>", "kind": "data"},
+                {"name": "actions", "type": "core::array::Array::<game::Action>", "kind": "data"},
+                {"name": "last", "type": "core::integer::u8", "kind": "data"}
+            ]},
+            {"type": "event", "name": "game::Event", "kind": "enum", "variants": [
+                {"name": "Played", "type": "game::Played", "kind": "nested"}
+            ]}
+        ])
     }
-}
 
+    #[test]
+    fn test_cairo_serde_values_take_their_own_felts() {
+        let keys = serde_json::json!([sn_keccak("Played"), "0x7", "0x1", "0x2", "0x2", "0x3", "0x4", "0x5", "0x6"]);
+        let data = serde_json::json!([
+            "0x3", "0xa", "0xb", "0xc", // scores
+            "0x0", "0x64",              // bonus: Some(100)
+            "0x1",                      // missed: None
+            "0x3", "0x0", "0x1", "0x8", "0x9", "0x2", "0x6869", "0x1", // Idle, Move(8, 9), Say('hi', true)
+            "0x2a"                      // last
+        ]);
+        let (name, decoded) = decode_event_using_abi(&serde_abi(), &serde_json::json!({"keys": keys, "data": data}));
+
+        assert_eq!(name, "Played");
+        assert!(decoded.get("_error").is_none(), "{}", decoded["_error"]);
+        assert_eq!(decoded["player"], serde_json::json!({
+            "id": "0x7", "position": {"x": 1, "y": 2}, "path": [{"x": 3, "y": 4}, {"x": 5, "y": 6}]
+        }));
+        assert_eq!(decoded["scores"], serde_json::json!([10, 11, 12]));
+        assert_eq!(decoded["bonus"], 100);
+        assert_eq!(decoded["missed"], Value::Null);
+        assert_eq!(decoded["actions"], serde_json::json!(["Idle", {"Move": {"x": 8, "y": 9}}, {"Say": ["0x6869", true]}]));
+        assert_eq!(decoded["last"], 42);
+
+        // Hinted as a short string, the variant's felt reads as text
+        let hints = DecodeHints { short_strings: vec!["Action.Say".to_string()], guess_strings: false };
+        let (_, decoded) = decode_event_with_hints(&serde_abi(), &serde_json::json!({"keys": keys, "data": data}), &hints);
+        assert_eq!(decoded["actions"][2], serde_json::json!({"Say": ["hi", true]}));
+    }
+
+    #[test]
+    fn test_component_events_are_named_by_their_path() {
+        let abi = serde_json::json!([
+            {"type": "event", "name": "openzeppelin::token::erc20::ERC20Component::Transfer", "kind": "struct", "members": [
+                {"name": "from", "type": "core::starknet::contract_address::ContractAddress", "kind": "key"},
+                {"name": "to", "type": "core::starknet::contract_address::ContractAddress", "kind": "key"},
+                {"name": "value", "type": "core::integer::u256", "kind": "data"}
+            ]},
+            {"type": "event", "name": "openzeppelin::token::erc20::ERC20Component::Event", "kind": "enum", "variants": [
+                {"name": "Transfer", "type": "openzeppelin::token::erc20::ERC20Component::Transfer", "kind": "nested"}
+            ]},
+            {"type": "event", "name": "openzeppelin::access::ownable::OwnableComponent::OwnershipTransferred", "kind": "struct", "members": [
+                {"name": "previous_owner", "type": "core::starknet::contract_address::ContractAddress", "kind": "key"},
+                {"name": "new_owner", "type": "core::starknet::contract_address::ContractAddress", "kind": "key"}
+            ]},
+            {"type": "event", "name": "openzeppelin::access::ownable::OwnableComponent::Event", "kind": "enum", "variants": [
+                {"name": "OwnershipTransferred", "type": "openzeppelin::access::ownable::OwnableComponent::OwnershipTransferred", "kind": "nested"}
+            ]},
+            {"type": "event", "name": "token::Token::Minted", "kind": "struct", "members": [
+                {"name": "amount", "type": "core::integer::u128", "kind": "data"}
+            ]},
+            {"type": "event", "name": "token::Token::Event", "kind": "enum", "variants": [
+                {"name": "ERC20Event", "type": "openzeppelin::token::erc20::ERC20Component::Event", "kind": "flat"},
+                {"name": "OwnableEvent", "type": "openzeppelin::access::ownable::OwnableComponent::Event", "kind": "nested"},
+                {"name": "Minted", "type": "token::Token::Minted", "kind": "nested"}
+            ]}
+        ]);
+
+        // Flat: the component's own selector only
+        let transfer = serde_json::json!({"keys": [sn_keccak("Transfer"), "0x1", "0x2"], "data": ["0x5", "0x0"]});
+        let (name, decoded) = decode_event_using_abi(&abi, &transfer);
+        assert_eq!(name, "ERC20Component::Transfer");
+        assert_eq!(decoded["value"], "5");
+
+        // Nested: the contract's variant selector, then the component's
+        let ownership = serde_json::json!({"keys": [sn_keccak("OwnableEvent"), sn_keccak("OwnershipTransferred"), "0x1", "0x2"], "data": []});
+        let (name, decoded) = decode_event_using_abi(&abi, &ownership);
+        assert_eq!(name, "OwnableComponent::OwnershipTransferred");
+        assert_eq!(decoded["new_owner"], crate::database::Database::normalize_address("0x2"));
+        let unprefixed = serde_json::json!({"keys": [sn_keccak("OwnershipTransferred"), "0x1", "0x2"], "data": []});
+        assert_eq!(decode_event_using_abi(&abi, &unprefixed).0, "Unknown");
+
+        let minted = serde_json::json!({"keys": [sn_keccak("Minted")], "data": ["0x7"]});
+        assert_eq!(decode_event_using_abi(&abi, &minted).0, "Minted");
+
+        let filters = |filters: &[&str]| filters.iter().map(|f| f.to_string()).collect::<Vec<_>>();
+        assert!(event_type_matches(&filters(&["Transfer"]), "ERC20Component::Transfer"));
+        assert!(event_type_matches(&filters(&["ERC20Component::Transfer"]), "ERC20Component::Transfer"));
+        assert!(event_type_matches(&filters(&["Approval", "Minted"]), "Minted"));
+        assert!(!event_type_matches(&filters(&["Transfer"]), "OwnableComponent::OwnershipTransferred"));
+        assert!(!event_type_matches(&filters(&["Component::Transfer"]), "ERC20Component::Transfer"));
+    }
+
+    #[test]
+    fn test_cairo0_events_are_decoded() {
+        let erc20: Value = serde_json::from_str(include_str!("../tests/fixtures/abi/cairo0_erc20.json")).unwrap();
+        let transfer = serde_json::json!({"keys": [sn_keccak("Transfer")], "data": ["0x1", "0x2", "0xde0b6b3a7640000", "0x0"]});
+        let (name, decoded) = decode_event_using_abi(&erc20, &transfer);
+        assert_eq!(name, "Transfer");
+        assert!(decoded.get("_error").is_none(), "{}", decoded["_error"]);
+        assert_eq!(decoded["from_"], "0x1");
+        assert_eq!(decoded["value"], "1000000000000000000");
+
+        let registry: Value = serde_json::from_str(include_str!("../tests/fixtures/abi/cairo0_registry.json")).unwrap();
+        let batch = serde_json::json!({
+            "keys": [sn_keccak("batch_recorded"), "0xabc"],
+            "data": [
+                "0x2", "0xa", "0xb",             // ids
+                "0x1", "0x3", "0x4",             // points
+                "0x5", "0x6",                    // bounds
+                "0x7", "0x8", "0x9"              // pair
+            ]
+        });
+        let (name, decoded) = decode_event_using_abi(&registry, &batch);
+        assert_eq!(name, "batch_recorded");
+        assert!(decoded.get("_error").is_none(), "{}", decoded["_error"]);
+        assert_eq!(decoded["owner"], "0xabc");
+        assert_eq!(decoded["ids_len"], "0x2"); // A felt, as declared
+        assert_eq!(decoded["ids"], serde_json::json!(["0xa", "0xb"]));
+        assert_eq!(decoded["points"], serde_json::json!([{"x": "0x3", "y": "0x4"}]));
+        assert_eq!(decoded["bounds"], serde_json::json!({"low": "0x5", "high": "0x6"}));
+        assert_eq!(decoded["pair"], serde_json::json!(["0x7", {"x": "0x8", "y": "0x9"}]));
+
+        // A length beyond the payload is reported
+        let short = serde_json::json!({"keys": [sn_keccak("batch_recorded"), "0xabc"], "data": ["0x5", "0xa"]});
+        assert!(decode_event_using_abi(&registry, &short).1["_error"].as_str().unwrap().starts_with("ids:"));
+
+        // Calldata and named outputs of Cairo 0 functions
+        let (name, inputs, outputs) = decode_function_call_using_abi(
+            &erc20, &sn_keccak("transfer"), &["0x1".into(), "0x2a".into(), "0x0".into()], &["0x1".into()], &DecodeHints::default(),
+        ).unwrap();
+        assert_eq!(name, "transfer");
+        assert_eq!(inputs["amount"], "42");
+        assert_eq!(outputs, serde_json::json!(["0x1"]));
+        let (_, _, outputs) = decode_function_call_using_abi(
+            &registry, &sn_keccak("get_ids"), &["0xabc".into()], &["0x2".into(), "0xa".into(), "0xb".into()], &DecodeHints::default(),
+        ).unwrap();
+        assert_eq!(outputs, serde_json::json!(["0x2", ["0xa", "0xb"]]));
+    }
+
+    #[test]
+    fn test_text_is_decoded_only_where_declared() {
+        let abi = serde_json::json!([
+            {"type": "event", "name": "profile::Renamed", "kind": "struct", "members": [
+                {"name": "name", "type": "core::byte_array::ByteArray", "kind": "data"},
+                {"name": "ticker", "type": "core::felt252", "kind": "data"},
+                {"name": "tag", "type": "ShortString", "kind": "data"},
+                {"name": "amount", "type": "core::felt252", "kind": "data"}
+            ]}
+        ]);
+        // "Starknet ByteArray decoding tests" is 31 bytes in one word, then 2 pending bytes
+        let event = serde_json::json!({"keys": [sn_keccak("Renamed")], "data": [
+            "0x1", "0x537461726b6e657420427974654172726179206465636f64696e6720746573", "0x7473", "0x2",
+            "0x535452", "0x6e6577", "0x4142"
+        ]});
+
+        let (_, decoded) = decode_event_using_abi(&abi, &event);
+        assert!(decoded.get("_error").is_none(), "{}", decoded["_error"]);
+        assert_eq!(decoded["name"], "Starknet ByteArray decoding tests");
+        assert_eq!(decoded["tag"], "new");
+        // Plain felts stay felts
+        assert_eq!(decoded["ticker"], "0x535452");
+        assert_eq!(decoded["amount"], "0x4142");
+
+        let declared = DecodeHints { short_strings: vec!["Renamed.ticker".to_string()], guess_strings: false };
+        let (_, decoded) = decode_event_with_hints(&abi, &event, &declared);
+        assert_eq!(decoded["ticker"], "STR");
+        assert_eq!(decoded["amount"], "0x4142");
+
+        let guessed = DecodeHints { short_strings: Vec::new(), guess_strings: true };
+        assert_eq!(decode_event_with_hints(&abi, &event, &guessed).1["amount"], "AB");
+
+        let hints = DecodeHints::from_metadata(&serde_json::json!({"shortStrings": ["ticker"], "startBlock": 5})).unwrap();
+        assert!(hints.is_short_string("profile::Renamed", "ticker"));
+        assert!(!hints.is_short_string("Renamed", "amount"));
+    }
+
+    #[test]
+    fn test_malformed_payloads_are_reported() {
+        let event = |data: Value| serde_json::json!({
+            "keys": [sn_keccak("Played"), "0x7", "0x1", "0x2", "0x0"],
+            "data": data
+        });
+        let error = |data: Value| decode_event_using_abi(&serde_abi(), &event(data)).1["_error"].as_str().unwrap_or_default().to_string();
+
+        // Array longer than the payload
+        assert!(error(serde_json::json!(["0x5", "0x1"])).starts_with("scores:"));
+        // Variant index out of range
+        assert!(error(serde_json::json!(["0x0", "0x1", "0x1", "0x1", "0x7", "0x1"])).contains("no variant 7"));
+        // Felts the ABI does not account for
+        assert!(error(serde_json::json!(["0x0", "0x1", "0x1", "0x0", "0x1", "0x2"])).contains("1 data felts left unread"));
+        // Truncated
+        assert!(error(serde_json::json!(["0x0", "0x1", "0x1", "0x0"])).starts_with("last:"));
+    }
+}
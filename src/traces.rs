@@ -0,0 +1,251 @@
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::sync::OnceCell;
+use crate::database::Database;
+
+/// Blocks whose traces are kept for the other contract indexers of the network
+const SHARED_TRACE_BLOCKS: usize = 256;
+
+/// Root invocations of a transaction trace, in execution order, with the phase they belong to.
+const TRACE_ROOTS: [(&str, &str); 5] = [
+    ("validate_invocation", "validate"),
+    ("execute_invocation", "execute"),
+    ("constructor_invocation", "constructor"),
+    ("function_invocation", "l1_handler"),
+    ("fee_transfer_invocation", "fee_transfer"),
+];
+
+/// A single invocation into a watched contract, flattened out of a transaction trace
+#[derive(Debug, Clone)]
+pub struct TracedCall {
+    pub contract_address: String,
+    pub caller_address: String,
+    pub entry_point_selector: String,
+    pub entry_point_type: String,
+    pub call_type: String,
+    pub calldata: Vec<Value>,
+    pub result: Vec<Value>,
+    pub phase: String,
+    /// 0 for calls made directly by the transaction, +1 for every nested call
+    pub depth: u32,
+    /// Position of the invocation in a pre-order walk of the whole transaction trace
+    pub call_index: u32,
+}
+
+/// `starknet_traceBlockTransactions` results shared by every contract indexer, so a block is traced once
+/// per network however many contracts watch it. Only recent blocks are kept, and failures are not.
+#[derive(Default)]
+pub struct SharedTraces {
+    blocks: Mutex<VecDeque<((String, u64), TraceCell)>>, // Keyed by network and block number, oldest first
+}
+
+/// Traces of one block, set by the first indexer to fetch them
+type TraceCell = Arc<OnceCell<Arc<Value>>>;
+
+impl SharedTraces {
+    /// Traces of a block, fetched with `fetch` by the first indexer to ask; the others wait for its result
+    pub async fn block<F, Fut, E>(&self, network: &str, block_number: u64, fetch: F) -> Result<Arc<Value>, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Value, E>>,
+    {
+        let cell = {
+            let mut blocks = self.blocks.lock().unwrap();
+            let key = (network.to_string(), block_number);
+            match blocks.iter().find(|(k, _)| *k == key) {
+                Some((_, cell)) => cell.clone(),
+                None => {
+                    let cell = Arc::new(OnceCell::new());
+                    blocks.push_back((key, cell.clone()));
+                    if blocks.len() > SHARED_TRACE_BLOCKS {
+                        blocks.pop_front();
+                    }
+                    cell
+                }
+            }
+        };
+        cell.get_or_try_init(|| async { fetch().await.map(Arc::new) }).await.cloned()
+    }
+
+    /// Drop the traces of `from_block` onwards after a reorg replaced those blocks
+    pub fn forget(&self, network: &str, from_block: u64) {
+        self.blocks.lock().unwrap().retain(|((n, block), _)| n != network || *block < from_block);
+    }
+}
+
+/// Split a `starknet_traceBlockTransactions` result into `(transaction_hash, trace_root)` pairs.
+pub fn block_trace_entries(block_traces: &Value) -> Vec<(String, &Value)> {
+    block_traces
+        .as_array()
+        .map(|traces| traces.iter().filter_map(|entry| {
+            let tx_hash = entry.get("transaction_hash")?.as_str()?.to_string();
+            let trace_root = entry.get("trace_root")?;
+            Some((tx_hash, trace_root))
+        }).collect())
+        .unwrap_or_default()
+}
+
+/// Walk every call tree of a transaction trace and return the invocations into `contract_address`.
+pub fn calls_into(trace_root: &Value, contract_address: &str) -> Vec<TracedCall> {
+    let target = Database::normalize_address(contract_address);
+    let mut calls = Vec::new();
+    let mut call_index = 0;
+
+    for (root_key, phase) in TRACE_ROOTS {
+        if let Some(invocation) = trace_root.get(root_key) {
+            // Reverted executions only carry a revert_reason and have no call tree
+            if invocation.get("contract_address").is_some() {
+                walk_invocation(invocation, &target, phase, 0, &mut call_index, &mut calls);
+            }
+        }
+    }
+
+    calls
+}
+
+fn walk_invocation(
+    invocation: &Value,
+    target: &str,
+    phase: &str,
+    depth: u32,
+    call_index: &mut u32,
+    calls: &mut Vec<TracedCall>,
+) {
+    let index = *call_index;
+    *call_index += 1;
+
+    let field = |name: &str| invocation.get(name).and_then(|v| v.as_str()).unwrap_or("").to_string();
+    let felts = |name: &str| invocation.get(name).and_then(|v| v.as_array()).cloned().unwrap_or_default();
+
+    let contract_address = field("contract_address");
+    if Database::normalize_address(&contract_address) == target {
+        calls.push(TracedCall {
+            contract_address: target.to_string(),
+            caller_address: Database::normalize_address(&field("caller_address")),
+            entry_point_selector: field("entry_point_selector"),
+            entry_point_type: field("entry_point_type"),
+            call_type: field("call_type"),
+            calldata: felts("calldata"),
+            result: felts("result"),
+            phase: phase.to_string(),
+            depth,
+            call_index: index,
+        });
+    }
+
+    if let Some(children) = invocation.get("calls").and_then(|v| v.as_array()) {
+        for child in children {
+            walk_invocation(child, target, phase, depth + 1, call_index, calls);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::starknet::decode_function_call_using_abi;
+
+    const ROUTER: &str = "0x041fd22b238fa21cfcf5dd45a8548974d8263b3a531a60388411c5e230f97023";
+    const TOKEN: &str = "0x04718f5a0fc34cc1af16a1cdee98ffb20c31f5cd61d6ab07201858f4287c938d";
+
+    /// Synthetic `starknet_traceBlockTransactions` result: spec-shaped, made-up hashes and addresses
+    fn block_traces() -> Value {
+        serde_json::from_str(include_str!("../tests/fixtures/traces/block_traces.json")).unwrap()
+    }
+
+    #[test]
+    fn test_internal_calls_are_found_at_every_depth() {
+        let traces = block_traces();
+        let entries = block_trace_entries(&traces["result"]);
+        assert_eq!(entries.len(), 2);
+
+        let (tx_hash, trace_root) = &entries[0];
+        assert_eq!(tx_hash, "0x5d2e6b2b0b5a2d2d4b0c1b4e3c3a6a3d8c9e6f6a7b1c2d3e4f5a6b7c8d9e0f1");
+
+        let calls = calls_into(trace_root, TOKEN);
+        // transferFrom from the router during execution, then the fee transfer
+        assert_eq!(calls.len(), 2);
+
+        let swap_leg = &calls[0];
+        assert_eq!(swap_leg.phase, "execute");
+        assert_eq!(swap_leg.depth, 2);
+        assert_eq!(swap_leg.caller_address, Database::normalize_address(ROUTER));
+        assert_eq!(swap_leg.call_type, "CALL");
+
+        let fee = &calls[1];
+        assert_eq!(fee.phase, "fee_transfer");
+        assert_eq!(fee.depth, 0);
+        assert!(fee.call_index > swap_leg.call_index);
+    }
+
+    #[test]
+    fn test_reverted_execution_has_no_calls() {
+        let traces = block_traces();
+        let entries = block_trace_entries(&traces["result"]);
+        let (_, reverted) = &entries[1];
+
+        let calls = calls_into(reverted, ROUTER);
+        assert!(calls.is_empty());
+    }
+
+    #[test]
+    fn test_single_transaction_trace() {
+        let trace: Value = serde_json::from_str(include_str!("../tests/fixtures/traces/l1_handler_trace.json")).unwrap();
+        let calls = calls_into(&trace["result"], TOKEN);
+
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].phase, "l1_handler");
+        assert_eq!(calls[0].depth, 1);
+    }
+
+    #[test]
+    fn test_traced_call_is_decoded_with_abi() {
+        let abi: Value = serde_json::from_str(include_str!("../tests/fixtures/traces/erc20_abi.json")).unwrap();
+        let traces = block_traces();
+        let entries = block_trace_entries(&traces["result"]);
+        let calls = calls_into(entries[0].1, TOKEN);
+
+        let (name, inputs, outputs) = decode_function_call_using_abi(
+            &abi,
+            &calls[0].entry_point_selector,
+            &calls[0].calldata,
+            &calls[0].result,
+            &Default::default(),
+        ).unwrap();
+
+        assert_eq!(name, "transfer_from");
+        assert_eq!(inputs["sender"], Database::normalize_address("0x3f1a2b"));
+        assert_eq!(inputs["recipient"], ROUTER);
+        assert_eq!(outputs, serde_json::json!([true]));
+    }
+
+    #[tokio::test]
+    async fn test_block_traces_are_fetched_once_per_network() {
+        let shared = SharedTraces::default();
+        let fetches = std::sync::atomic::AtomicUsize::new(0);
+        let fetch = |ok: bool| {
+            fetches.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            async move { if ok { Ok(block_traces()) } else { Err("trace not supported".to_string()) } }
+        };
+
+        // Every contract of the network after the first reads the same result
+        for _ in 0..3 {
+            let traces = shared.block("mainnet", 7, || fetch(true)).await.unwrap();
+            assert_eq!(block_trace_entries(&traces["result"]).len(), 2);
+        }
+        assert_eq!(fetches.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        // Another network has its own blocks, and failures are asked again
+        shared.block("sepolia", 7, || fetch(true)).await.unwrap();
+        assert!(shared.block("mainnet", 8, || fetch(false)).await.is_err());
+        shared.block("mainnet", 8, || fetch(true)).await.unwrap();
+        assert_eq!(fetches.load(std::sync::atomic::Ordering::SeqCst), 4);
+
+        shared.forget("mainnet", 8);
+        shared.block("mainnet", 7, || fetch(true)).await.unwrap();
+        shared.block("mainnet", 8, || fetch(true)).await.unwrap();
+        assert_eq!(fetches.load(std::sync::atomic::Ordering::SeqCst), 5);
+    }
+}